@@ -0,0 +1,120 @@
+use crate::database::Database;
+use crate::workspaces::scratch_dir;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DATABASE_ENTRY: &str = "database.db";
+/// Bumped when the archive layout changes incompatibly.
+const FORMAT_VERSION: u32 = 1;
+
+/// Describes an exported workspace. Settings such as the recipe id prefix
+/// live in the database itself and travel with it.
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    pub workspace: String,
+    pub app_version: String,
+}
+
+/// Writes the workspace's database and a manifest into one compressed file
+/// that `read_manifest` and `extract_database` can read on another machine.
+pub fn export_archive(db: &Database, workspace: &str, dest: &Path) -> Result<(), String> {
+    let scratch = scratch_dir("export")?;
+    let snapshot = scratch.join(DATABASE_ENTRY);
+    let result = db
+        .backup_to(&snapshot)
+        .map_err(|e| format!("Failed to snapshot database: {}", e))
+        .and_then(|()| write_archive(&snapshot, workspace, dest));
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+fn write_archive(snapshot: &Path, workspace: &str, dest: &Path) -> Result<(), String> {
+    let manifest = ArchiveManifest {
+        format_version: FORMAT_VERSION,
+        workspace: workspace.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let file = File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    zip.start_file(MANIFEST_ENTRY, options).map_err(|e| e.to_string())?;
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+    zip.write_all(&json).map_err(|e| e.to_string())?;
+
+    zip.start_file(DATABASE_ENTRY, options.large_file(true)).map_err(|e| e.to_string())?;
+    let mut database = File::open(snapshot).map_err(|e| e.to_string())?;
+    io::copy(&mut database, &mut zip).map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reads an exported archive's manifest.
+pub fn read_manifest(src: &Path) -> Result<ArchiveManifest, String> {
+    let mut archive = open_archive(src)?;
+    let mut entry = archive.by_name(MANIFEST_ENTRY).map_err(|_| "Archive has no manifest")?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).map_err(|e| e.to_string())?;
+    let manifest: ArchiveManifest =
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err("Archive was made by a newer version of the app".to_string());
+    }
+    if manifest.workspace.trim().is_empty() {
+        return Err("Archive has no workspace name".to_string());
+    }
+    Ok(manifest)
+}
+
+/// Unpacks an exported archive's database to `db_dest`.
+pub fn extract_database(src: &Path, db_dest: &Path) -> Result<(), String> {
+    let mut archive = open_archive(src)?;
+    let mut entry = archive.by_name(DATABASE_ENTRY).map_err(|_| "Archive has no database")?;
+    if let Some(parent) = db_dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut out = File::create(db_dest).map_err(|e| e.to_string())?;
+    io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn open_archive(src: &Path) -> Result<ZipArchive<File>, String> {
+    let file = File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    ZipArchive::new(file).map_err(|e| format!("Not a workspace archive: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_db;
+
+    #[test]
+    fn test_round_trip() {
+        let db = temp_db();
+        db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let dir = scratch_dir("archive-test").unwrap();
+        let archive = dir.join("pack.zip");
+        export_archive(&db, "My pack", &archive).unwrap();
+
+        assert_eq!(read_manifest(&archive).unwrap().workspace, "My pack");
+        let copy = dir.join("copy.db");
+        extract_database(&archive, &copy).unwrap();
+        assert_eq!(Database::new(copy).unwrap().list_mods(None, true).unwrap().len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unnamed_archive_is_rejected() {
+        let dir = scratch_dir("archive-test").unwrap();
+        let archive = dir.join("pack.zip");
+        export_archive(&temp_db(), " ", &archive).unwrap();
+        assert!(read_manifest(&archive).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::database::{Database, RecipeSearch, RecipeSort, SortKey};
+use crate::matching::MatchMode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::thread;
+
+/// Upper bound on read connections opened for one batch.
+const MAX_READERS: usize = 4;
+
+/// One query in a batch; `key` names its slot in the response.
+#[derive(Deserialize)]
+pub struct BatchRequest {
+    pub key: String,
+    pub query: BatchQuery,
+}
+
+/// The read-only queries a batch can run, tagged by `type`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchQuery {
+    SearchByOutput {
+        item: String,
+        #[serde(default)]
+        match_mode: MatchMode,
+        #[serde(default)]
+        sort: RecipeSort,
+    },
+    SearchByIngredient {
+        item: String,
+        #[serde(default)]
+        match_mode: MatchMode,
+        #[serde(default)]
+        expand_tags: bool,
+        #[serde(default)]
+        sort: RecipeSort,
+    },
+    SearchRecipes { filters: Box<RecipeSearch> },
+    RecipeFacets { filters: Box<RecipeSearch> },
+    ListRecipes {
+        offset: i64,
+        limit: i64,
+        sort: Option<RecipeSort>,
+    },
+    GetRecipes { ids: Vec<i64> },
+    RecipeCount,
+    RecipeTypes,
+    FluidConversions,
+    ItemNames { items: Vec<String>, locale: String },
+    ListMachines,
+    MachineRecipes { machine_id: String },
+    ListEnchantments,
+    SearchEnchantments { query: String },
+    ListWorldgen { kind: Option<String> },
+    SearchWorldgen { reference: String },
+    ListTrades,
+    ItemSources { item: String },
+    ItemComponents { item: Option<String> },
+    SearchConfigs { query: String, limit: Option<i64> },
+    ModRecipeTrend { mod_id: i64 },
+    QuarantinedJars,
+    ListMods {
+        loader: Option<String>,
+        #[serde(default)]
+        include_hidden: bool,
+    },
+    ListItems { namespace: Option<String> },
+    SearchItems {
+        query: String,
+        limit: Option<i64>,
+        #[serde(default)]
+        match_mode: MatchMode,
+    },
+}
+
+/// Either the query's result or why it failed; one failure does not fail the batch.
+#[derive(Serialize)]
+pub struct BatchResult {
+    pub value: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Spreads the requests over up to `MAX_READERS` read connections and runs
+/// them in parallel. If a thread panics, its queries come back as errors.
+pub fn run_batch(db: &Database, requests: Vec<BatchRequest>) -> HashMap<String, BatchResult> {
+    if requests.is_empty() {
+        return HashMap::new();
+    }
+    let readers = requests.len().min(MAX_READERS);
+    let chunk_size = requests.len().div_ceil(readers);
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = requests
+            .chunks(chunk_size)
+            .map(|chunk| (chunk, scope.spawn(move || run_chunk(db, chunk))))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|(chunk, handle)| {
+                handle.join().unwrap_or_else(|_| {
+                    let error = || BatchResult { value: None, error: Some("Query panicked".to_string()) };
+                    chunk.iter().map(|request| (request.key.clone(), error())).collect()
+                })
+            })
+            .collect()
+    })
+}
+
+fn run_chunk(db: &Database, requests: &[BatchRequest]) -> Vec<(String, BatchResult)> {
+    let reader = db.open_reader();
+    requests
+        .iter()
+        .map(|request| {
+            let result = match &reader {
+                Ok(reader) => run_query(reader, &request.query),
+                Err(e) => Err(format!("Failed to open read connection: {}", e)),
+            };
+            let result = match result {
+                Ok(value) => BatchResult { value: Some(value), error: None },
+                Err(error) => BatchResult { value: None, error: Some(error) },
+            };
+            (request.key.clone(), result)
+        })
+        .collect()
+}
+
+fn run_query(db: &Database, query: &BatchQuery) -> Result<Value, String> {
+    match query {
+        BatchQuery::SearchByOutput { item, match_mode, sort } => {
+            to_value(db.search_by_output(item, *match_mode, *sort))
+        }
+        BatchQuery::SearchByIngredient { item, match_mode, expand_tags, sort } => {
+            to_value(db.search_by_ingredient(item, *match_mode, *expand_tags, *sort))
+        }
+        BatchQuery::SearchRecipes { filters } => to_value(db.search_recipes(filters)),
+        BatchQuery::RecipeFacets { filters } => to_value(db.recipe_facets(filters)),
+        BatchQuery::ListRecipes { offset, limit, sort } => {
+            to_value(db.list_recipes(*offset, *limit, sort.unwrap_or(RecipeSort::by(SortKey::Mod))))
+        }
+        BatchQuery::GetRecipes { ids } => to_value(db.get_recipes(ids)),
+        BatchQuery::RecipeCount => to_value(db.get_recipe_count()),
+        BatchQuery::RecipeTypes => to_value(db.recipe_type_counts()),
+        BatchQuery::FluidConversions => to_value(db.browse_fluid_conversions()),
+        BatchQuery::ItemNames { items, locale } => to_value(db.get_item_names(items, locale)),
+        BatchQuery::ListMachines => to_value(db.list_machines()),
+        BatchQuery::MachineRecipes { machine_id } => to_value(db.list_machine_recipes(machine_id)),
+        BatchQuery::ListEnchantments => to_value(db.list_enchantments()),
+        BatchQuery::SearchEnchantments { query } => to_value(db.search_enchantments(query)),
+        BatchQuery::ListWorldgen { kind } => to_value(db.list_worldgen(kind.as_deref())),
+        BatchQuery::SearchWorldgen { reference } => to_value(db.search_worldgen(reference)),
+        BatchQuery::ListTrades => to_value(db.list_trades()),
+        BatchQuery::ItemSources { item } => to_value(db.get_item_sources(item)),
+        BatchQuery::ItemComponents { item } => to_value(db.list_item_components(item.as_deref())),
+        BatchQuery::SearchConfigs { query, limit } => {
+            to_value(db.search_configs(query, limit.unwrap_or(200)))
+        }
+        BatchQuery::ModRecipeTrend { mod_id } => to_value(db.get_mod_recipe_trend(*mod_id)),
+        BatchQuery::QuarantinedJars => to_value(db.list_quarantined_jars()),
+        BatchQuery::ListMods { loader, include_hidden } => {
+            to_value(db.list_mods(loader.as_deref(), *include_hidden))
+        }
+        BatchQuery::ListItems { namespace } => to_value(db.list_items(namespace.as_deref())),
+        BatchQuery::SearchItems { query, limit, match_mode } => {
+            to_value(db.search_items(query, limit.unwrap_or(50), *match_mode))
+        }
+    }
+}
+
+fn to_value<T: Serialize>(result: rusqlite::Result<T>) -> Result<Value, String> {
+    let value = result.map_err(|e| format!("Query failed: {}", e))?;
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
@@ -0,0 +1,330 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Crafting table types; every other recipe type counts as a machine.
+const HAND_CRAFTING: [&str; 2] = ["minecraft:crafting_shaped", "minecraft:crafting_shapeless"];
+
+/// A recipe as complexity scoring sees it.
+pub struct ComplexityRecipe {
+    pub id: i64,
+    pub output: String,
+    pub recipe_type: String,
+    /// Item and tag ids, tags starting with `#`
+    pub ingredients: Vec<String>,
+}
+
+/// How far an item sits from raw materials along its shallowest route.
+#[derive(Serialize)]
+pub struct ItemComplexity {
+    pub item: String,
+    pub namespace: String,
+    /// Recipes deep along the longest branch; 0 for raw materials
+    pub depth: u32,
+    pub raw_materials: Vec<String>,
+    pub machine_types: Vec<String>,
+    /// Row id of the recipe the scores follow
+    pub recipe: Option<i64>,
+    /// Depth plus raw material and machine type counts
+    pub score: usize,
+}
+
+/// Scores of one namespace's items, for comparing mods.
+#[derive(Serialize)]
+pub struct NamespaceComplexity {
+    pub namespace: String,
+    pub items: usize,
+    pub average_score: f64,
+    pub max_score: usize,
+}
+
+/// An item no recipe can be scored for, because every recipe for it needs
+/// a tag that is undefined or empty, or another such item.
+#[derive(Serialize)]
+pub struct UnscoredItem {
+    pub item: String,
+    /// Ingredients of its recipes that could not be scored
+    pub blocked_by: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ComplexityReport {
+    pub items: Vec<ItemComplexity>,
+    pub namespaces: Vec<NamespaceComplexity>,
+    pub unscored: Vec<UnscoredItem>,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplexitySort {
+    #[default]
+    Score,
+    Depth,
+    RawMaterials,
+    MachineTypes,
+}
+
+/// Which scored items to return, most complex first.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ComplexityFilter {
+    pub namespaces: Vec<String>,
+    pub min_score: Option<usize>,
+    pub max_score: Option<usize>,
+    /// Leaves out raw materials
+    pub crafted_only: bool,
+    pub sort: ComplexitySort,
+    pub limit: Option<usize>,
+}
+
+#[derive(Clone)]
+struct Resolved {
+    depth: u32,
+    raw: BTreeSet<String>,
+    machines: BTreeSet<String>,
+    recipe: Option<i64>,
+}
+
+impl Resolved {
+    fn raw(item: &str) -> Self {
+        Resolved { depth: 0, raw: BTreeSet::from([item.to_string()]), machines: BTreeSet::new(), recipe: None }
+    }
+}
+
+/// Scores every item the recipes make or use. Each item follows its
+/// shallowest recipe, and a tag its shallowest member. Items reachable only
+/// through loops, like ingots and blocks that craft into each other with no
+/// other source, count as raw materials. Items held up by undefined or
+/// empty tags are listed as unscored.
+pub fn score_items(recipes: &[ComplexityRecipe], tag_entries: &[(String, String)], filter: &ComplexityFilter) -> ComplexityReport {
+    let mut options: HashMap<&str, Vec<&ComplexityRecipe>> = HashMap::new();
+    for recipe in recipes {
+        options.entry(&recipe.output).or_default().push(recipe);
+    }
+    let mut members: HashMap<String, Vec<&str>> = HashMap::new();
+    for (tag, entry) in tag_entries {
+        members.entry(format!("#{}", tag)).or_default().push(entry);
+    }
+
+    let mut resolved: HashMap<String, Resolved> = HashMap::new();
+    for recipe in recipes {
+        for ingredient in &recipe.ingredients {
+            if !ingredient.starts_with('#') && !options.contains_key(ingredient.as_str()) {
+                resolved.insert(ingredient.clone(), Resolved::raw(ingredient));
+            }
+        }
+    }
+    for entries in members.values() {
+        for entry in entries.iter().filter(|e| !e.starts_with('#') && !options.contains_key(*e)) {
+            resolved.insert(entry.to_string(), Resolved::raw(entry));
+        }
+    }
+
+    // Undefined and empty tags can never settle, nor can items that need
+    // them for every recipe; they are reported instead of becoming raw
+    let mut dead: HashSet<&str> = HashSet::new();
+    loop {
+        let is_dead = |id: &str| dead.contains(id) || (id.starts_with('#') && !members.contains_key(id));
+        let dead_tags = members
+            .iter()
+            .filter(|(tag, entries)| !dead.contains(tag.as_str()) && entries.iter().all(|e| is_dead(e)))
+            .map(|(tag, _)| tag.as_str());
+        let dead_items = options
+            .iter()
+            .filter(|(output, candidates)| {
+                !dead.contains(**output) && candidates.iter().all(|recipe| recipe.ingredients.iter().any(|i| is_dead(i)))
+            })
+            .map(|(output, _)| *output);
+        let newly_dead: Vec<&str> = dead_tags.chain(dead_items).collect();
+        if newly_dead.is_empty() {
+            break;
+        }
+        dead.extend(newly_dead);
+    }
+
+    // Rounds settle items in order of depth. When a round settles nothing,
+    // what is left is held up by loops and its ingredients become raw.
+    loop {
+        let mut settled: Vec<(String, Resolved)> = Vec::new();
+        for (tag, entries) in &members {
+            if resolved.contains_key(tag) {
+                continue;
+            }
+            if let Some(best) = entries.iter().filter_map(|e| resolved.get(*e)).min_by_key(|r| r.depth) {
+                settled.push((tag.clone(), best.clone()));
+            }
+        }
+        for (output, candidates) in &options {
+            if resolved.contains_key(*output) {
+                continue;
+            }
+            let best = candidates
+                .iter()
+                .filter_map(|recipe| {
+                    let inputs: Option<Vec<&Resolved>> = recipe.ingredients.iter().map(|i| resolved.get(i)).collect();
+                    inputs.map(|inputs| combine(recipe, &inputs))
+                })
+                .min_by_key(|r| r.depth);
+            if let Some(best) = best {
+                settled.push((output.to_string(), best));
+            }
+        }
+
+        if settled.is_empty() {
+            let stuck: Vec<String> = options
+                .values()
+                .flatten()
+                .filter(|recipe| !resolved.contains_key(&recipe.output))
+                .flat_map(|recipe| &recipe.ingredients)
+                .filter(|i| !i.starts_with('#') && !resolved.contains_key(*i) && !dead.contains(i.as_str()))
+                .cloned()
+                .collect();
+            if stuck.is_empty() {
+                break;
+            }
+            for item in stuck {
+                resolved.insert(item.clone(), Resolved::raw(&item));
+            }
+        }
+        resolved.extend(settled);
+    }
+
+    let mut unscored: Vec<UnscoredItem> = options
+        .iter()
+        .filter(|(output, _)| !resolved.contains_key(**output))
+        .map(|(output, candidates)| {
+            let blocked_by: BTreeSet<&String> = candidates
+                .iter()
+                .flat_map(|recipe| &recipe.ingredients)
+                .filter(|i| !resolved.contains_key(*i))
+                .collect();
+            UnscoredItem { item: output.to_string(), blocked_by: blocked_by.into_iter().cloned().collect() }
+        })
+        .filter(|u| filter.namespaces.is_empty() || filter.namespaces.iter().any(|ns| u.item.starts_with(&format!("{}:", ns))))
+        .collect();
+    unscored.sort_by(|a, b| a.item.cmp(&b.item));
+
+    let mut items: Vec<ItemComplexity> = resolved
+        .into_iter()
+        .filter(|(item, _)| !item.starts_with('#'))
+        .map(|(item, r)| ItemComplexity {
+            namespace: item.split_once(':').map_or("minecraft", |(ns, _)| ns).to_string(),
+            item,
+            depth: r.depth,
+            score: r.depth as usize + r.raw.len() + r.machines.len(),
+            raw_materials: r.raw.into_iter().collect(),
+            machine_types: r.machines.into_iter().collect(),
+            recipe: r.recipe,
+        })
+        .filter(|i| filter.namespaces.is_empty() || filter.namespaces.contains(&i.namespace))
+        .filter(|i| !filter.crafted_only || i.recipe.is_some())
+        .collect();
+
+    let mut by_namespace: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+    for item in &items {
+        let entry = by_namespace.entry(&item.namespace).or_default();
+        entry.0 += 1;
+        entry.1 += item.score;
+        entry.2 = entry.2.max(item.score);
+    }
+    let mut namespaces: Vec<NamespaceComplexity> = by_namespace
+        .into_iter()
+        .map(|(namespace, (count, total, max))| NamespaceComplexity {
+            namespace: namespace.to_string(),
+            items: count,
+            average_score: total as f64 / count as f64,
+            max_score: max,
+        })
+        .collect();
+    namespaces.sort_by(|a, b| b.average_score.total_cmp(&a.average_score).then_with(|| a.namespace.cmp(&b.namespace)));
+
+    items.retain(|i| filter.min_score.is_none_or(|min| i.score >= min) && filter.max_score.is_none_or(|max| i.score <= max));
+    items.sort_by(|a, b| {
+        let key = |i: &ItemComplexity| match filter.sort {
+            ComplexitySort::Score => i.score,
+            ComplexitySort::Depth => i.depth as usize,
+            ComplexitySort::RawMaterials => i.raw_materials.len(),
+            ComplexitySort::MachineTypes => i.machine_types.len(),
+        };
+        key(b).cmp(&key(a)).then_with(|| a.item.cmp(&b.item))
+    });
+    if let Some(limit) = filter.limit {
+        items.truncate(limit);
+    }
+    ComplexityReport { items, namespaces, unscored }
+}
+
+fn combine(recipe: &ComplexityRecipe, inputs: &[&Resolved]) -> Resolved {
+    let mut result = Resolved {
+        depth: 1 + inputs.iter().map(|r| r.depth).max().unwrap_or(0),
+        raw: BTreeSet::new(),
+        machines: BTreeSet::new(),
+        recipe: Some(recipe.id),
+    };
+    for input in inputs {
+        result.raw.extend(input.raw.iter().cloned());
+        result.machines.extend(input.machines.iter().cloned());
+    }
+    if !HAND_CRAFTING.contains(&recipe.recipe_type.as_str()) {
+        result.machines.insert(recipe.recipe_type.clone());
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(id: i64, output: &str, recipe_type: &str, ingredients: &[&str]) -> ComplexityRecipe {
+        ComplexityRecipe {
+            id,
+            output: output.to_string(),
+            recipe_type: recipe_type.to_string(),
+            ingredients: ingredients.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_score_items() {
+        let recipes = vec![
+            recipe(1, "a:ingot", "minecraft:smelting", &["a:ore"]),
+            recipe(2, "a:block", "minecraft:crafting_shaped", &["a:ingot"]),
+            recipe(3, "a:ingot", "minecraft:crafting_shapeless", &["a:block"]),
+            recipe(4, "b:gear", "create:pressing", &["#c:ingots", "b:stick"]),
+            recipe(5, "b:loop", "minecraft:crafting_shaped", &["b:other"]),
+            recipe(6, "b:other", "minecraft:crafting_shaped", &["b:loop"]),
+        ];
+        let tags = vec![("c:ingots".to_string(), "a:ingot".to_string())];
+        let report = score_items(&recipes, &tags, &ComplexityFilter::default());
+        let find = |item: &str| report.items.iter().find(|i| i.item == item).unwrap();
+
+        assert_eq!(find("a:ingot").depth, 1);
+        assert_eq!(find("a:ingot").recipe, Some(1));
+        assert_eq!(find("a:block").depth, 2);
+        let gear = find("b:gear");
+        assert_eq!(gear.depth, 2);
+        assert_eq!(gear.raw_materials, vec!["a:ore", "b:stick"]);
+        assert_eq!(gear.machine_types, vec!["create:pressing", "minecraft:smelting"]);
+        assert_eq!(gear.score, 6);
+        assert_eq!(report.items[0].item, "b:gear");
+        assert!(find("b:loop").depth <= 1);
+        assert!(report.unscored.is_empty());
+    }
+
+    #[test]
+    fn test_undefined_tags_are_reported() {
+        let recipes = vec![
+            recipe(1, "a:plate", "create:pressing", &["#c:missing"]),
+            recipe(3, "a:plate", "minecraft:crafting_shapeless", &["#c:plates"]),
+            recipe(2, "a:casing", "minecraft:crafting_shaped", &["a:plate", "a:log"]),
+        ];
+        let tags = vec![("c:plates".to_string(), "#c:undefined".to_string())];
+        let report = score_items(&recipes, &tags, &ComplexityFilter::default());
+        assert!(report.items.iter().all(|i| i.item != "a:plate" && i.item != "a:casing"));
+        let blocked: Vec<(&str, &[String])> =
+            report.unscored.iter().map(|u| (u.item.as_str(), u.blocked_by.as_slice())).collect();
+        assert_eq!(blocked, vec![
+            ("a:casing", &["a:plate".to_string()][..]),
+            ("a:plate", &["#c:missing".to_string(), "#c:plates".to_string()][..]),
+        ]);
+    }
+}
@@ -0,0 +1,102 @@
+use serde_json::{Map, Value};
+
+/// Data component defaults found for an item in a data file.
+#[derive(Debug, Default, PartialEq)]
+pub struct ItemComponents {
+    pub item: String,
+    pub nutrition: Option<i32>,
+    pub saturation: Option<f64>,
+    pub max_stack_size: Option<i32>,
+    pub max_damage: Option<i32>,
+}
+
+/// Finds every `components` block (recipe results, loot `set_components`
+/// functions, ...) that sets food, stack size or durability, paired with the
+/// item it applies to: the `id`/`item` beside it, or the nearest enclosing
+/// loot entry `name`.
+pub fn collect_item_components(json_str: &str) -> Result<Vec<ItemComponents>, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let mut found = Vec::new();
+    collect(&value, None, &mut found);
+    Ok(found)
+}
+
+fn collect(value: &Value, item: Option<&str>, found: &mut Vec<ItemComponents>) {
+    match value {
+        Value::Array(arr) => arr.iter().for_each(|v| collect(v, item, found)),
+        Value::Object(obj) => {
+            let item = ["id", "item", "name"]
+                .iter()
+                .find_map(|key| obj.get(*key).and_then(|v| v.as_str()))
+                .or(item);
+
+            if let (Some(item), Some(Value::Object(components))) = (item, obj.get("components")) {
+                if let Some(parsed) = parse_components(item, components) {
+                    found.push(parsed);
+                }
+            }
+            obj.values().for_each(|v| collect(v, item, found));
+        }
+        _ => {}
+    }
+}
+
+fn parse_components(item: &str, components: &Map<String, Value>) -> Option<ItemComponents> {
+    let get = |name: &str| {
+        components
+            .get(&format!("minecraft:{}", name))
+            .or(components.get(name))
+    };
+    let as_i32 = |v: &Value| v.as_i64().map(|v| v as i32);
+
+    let food = get("food");
+    let parsed = ItemComponents {
+        item: item.to_string(),
+        nutrition: food.and_then(|f| f.get("nutrition")).and_then(as_i32),
+        saturation: food.and_then(|f| f.get("saturation")).and_then(|v| v.as_f64()),
+        max_stack_size: get("max_stack_size").and_then(as_i32),
+        max_damage: get("max_damage").and_then(as_i32),
+    };
+
+    let has_any = parsed.nutrition.is_some()
+        || parsed.saturation.is_some()
+        || parsed.max_stack_size.is_some()
+        || parsed.max_damage.is_some();
+    has_any.then_some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_item_components() {
+        let recipe = r#"{
+            "type": "minecraft:crafting_shapeless",
+            "ingredients": [{"item": "minecraft:bread"}],
+            "result": {
+                "id": "farmersdelight:sandwich",
+                "components": {"minecraft:food": {"nutrition": 10, "saturation": 0.8}, "minecraft:max_stack_size": 16}
+            }
+        }"#;
+        assert_eq!(collect_item_components(recipe).unwrap(), vec![ItemComponents {
+            item: "farmersdelight:sandwich".to_string(),
+            nutrition: Some(10),
+            saturation: Some(0.8),
+            max_stack_size: Some(16),
+            max_damage: None,
+        }]);
+
+        let loot = r#"{"pools": [{"entries": [{
+            "type": "minecraft:item",
+            "name": "minecraft:iron_sword",
+            "functions": [{"function": "minecraft:set_components", "components": {"minecraft:max_damage": 500}}]
+        }]}]}"#;
+        let found = collect_item_components(loot).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].item, "minecraft:iron_sword");
+        assert_eq!(found[0].max_damage, Some(500));
+    }
+}
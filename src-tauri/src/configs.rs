@@ -0,0 +1,185 @@
+use std::fs;
+use std::path::Path;
+
+/// File extensions read when scanning an instance's `config/` directory.
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "json", "json5", "cfg"];
+
+/// One setting: its dotted key path within the file and its raw value text.
+#[derive(Debug, PartialEq)]
+pub struct ConfigValue {
+    pub key: String,
+    pub value: String,
+}
+
+/// Reads every config file under `dir`, recursively. Returns each file's path
+/// relative to `dir` (with `/` separators) and the settings found in it.
+pub fn scan_config_dir(dir: &str) -> Result<Vec<(String, Vec<ConfigValue>)>, String> {
+    let root = Path::new(dir);
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", dir));
+    }
+
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current)
+            .map_err(|e| format!("Failed to read directory: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let is_config = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| CONFIG_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+            if !is_config {
+                continue;
+            }
+            // Unreadable or binary files are skipped
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            files.push((relative, parse_config(&contents)));
+        }
+    }
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// Line-based reader covering TOML, Forge `.cfg` and (formatted) JSON/JSON5.
+/// Keys are prefixed with their enclosing `[section]` and `name {` blocks.
+pub fn parse_config(contents: &str) -> Vec<ConfigValue> {
+    let mut values = Vec::new();
+    let mut section: Vec<String> = Vec::new();
+    let mut blocks: Vec<String> = Vec::new();
+    let mut lines = contents.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') && !line.contains('=') {
+            let name = line.trim_matches(|c| c == '[' || c == ']').trim();
+            section = name.split('.').map(|s| unquote(s.trim())).collect();
+            continue;
+        }
+        if line.starts_with('}') || line.starts_with(']') {
+            blocks.pop();
+            continue;
+        }
+
+        let Some((key, value)) = split_key_value(line) else {
+            // Forge .cfg category `general {` or a bare JSON `{`
+            if let Some(name) = line.strip_suffix('{') {
+                blocks.push(unquote(name.trim()));
+            }
+            continue;
+        };
+
+        if value == "{" {
+            blocks.push(key);
+            continue;
+        }
+
+        // Multi-line arrays are joined into one value
+        let mut value = value.trim_end_matches(',').to_string();
+        if value.starts_with('[') && !value.contains(']') {
+            for next in lines.by_ref() {
+                value.push(' ');
+                value.push_str(next);
+                if next.starts_with(']') {
+                    break;
+                }
+            }
+            value = value.trim_end_matches(',').to_string();
+        }
+
+        let path: Vec<&str> = section
+            .iter()
+            .chain(&blocks)
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+            .chain(std::iter::once(key.as_str()))
+            .collect();
+        values.push(ConfigValue { key: path.join("."), value });
+    }
+
+    values
+}
+
+/// Splits `key = value`, `B:key=value` or `"key": value`.
+fn split_key_value(line: &str) -> Option<(String, String)> {
+    let (key, rest) = if let Some(quoted) = line.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], quoted[end + 1..].trim_start())
+    } else {
+        let end = line.find(['=', ':'])?;
+        // Forge .cfg type prefixes such as `B:` or `S:` are part of the key line
+        let end = if line[end..].starts_with(':') && end == 1 {
+            end + 1 + line[end + 1..].find('=')?
+        } else {
+            end
+        };
+        (line[..end].trim(), &line[end..])
+    };
+
+    let value = rest.strip_prefix('=').or(rest.strip_prefix(':'))?.trim();
+    if key.is_empty() {
+        return None;
+    }
+    let key = key.split_once(':').filter(|(t, _)| t.len() == 1).map_or(key, |(_, k)| k);
+    Some((unquote(key), value.to_string()))
+}
+
+fn unquote(s: &str) -> String {
+    s.trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(contents: &str) -> Vec<(String, String)> {
+        parse_config(contents).into_iter().map(|v| (v.key, v.value)).collect()
+    }
+
+    #[test]
+    fn test_parse_toml() {
+        let toml = r#"
+            # Whether ores generate
+            [worldgen.ores]
+            enabled = true
+            blacklist = [
+                "minecraft:nether",
+            ]
+        "#;
+        assert_eq!(pairs(toml), vec![
+            ("worldgen.ores.enabled".to_string(), "true".to_string()),
+            ("worldgen.ores.blacklist".to_string(), r#"[ "minecraft:nether", ]"#.to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_cfg_and_json() {
+        let cfg = "general {\n    B:enableRecipes=true\n    S:mode=hard\n}\n";
+        assert_eq!(pairs(cfg), vec![
+            ("general.enableRecipes".to_string(), "true".to_string()),
+            ("general.mode".to_string(), "hard".to_string()),
+        ]);
+
+        let json = "{\n  \"machines\": {\n    \"speed\": 2.5,\n    \"item\": \"minecraft:iron_ingot\"\n  }\n}";
+        assert_eq!(pairs(json), vec![
+            ("machines.speed".to_string(), "2.5".to_string()),
+            ("machines.item".to_string(), "\"minecraft:iron_ingot\"".to_string()),
+        ]);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::recipe_parser::{self, canonical_recipe_type};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A crafting recipe as conflict detection sees it.
+pub struct CraftingRecipe {
+    pub id: i64,
+    pub recipe_id: String,
+    pub mod_name: String,
+    pub recipe_type: String,
+    pub result_item: Option<String>,
+    pub raw_json: String,
+}
+
+/// Two recipes the game cannot tell apart that make different items; only
+/// the one loaded first is ever used.
+#[derive(Serialize)]
+pub struct RecipeConflict {
+    pub first: ConflictingRecipe,
+    pub second: ConflictingRecipe,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ConflictingRecipe {
+    pub id: i64,
+    pub recipe_id: String,
+    pub mod_name: String,
+    pub result_item: Option<String>,
+}
+
+/// Pairs of shaped recipes with the same pattern and inputs, or shapeless
+/// recipes with the same ingredients, that make different items. Copies of
+/// one recipe id are overrides, not conflicts, and are skipped.
+pub fn find_conflicts(recipes: &[CraftingRecipe]) -> Vec<RecipeConflict> {
+    let mut groups: HashMap<String, Vec<&CraftingRecipe>> = HashMap::new();
+    for recipe in recipes {
+        if let Some(signature) = signature(&recipe.recipe_type, &recipe.raw_json) {
+            groups.entry(signature).or_default().push(recipe);
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    for group in groups.values().filter(|group| group.len() > 1) {
+        for (i, a) in group.iter().enumerate() {
+            for b in &group[i + 1..] {
+                if a.result_item != b.result_item && a.recipe_id != b.recipe_id {
+                    conflicts.push(RecipeConflict { first: summary(a), second: summary(b) });
+                }
+            }
+        }
+    }
+    conflicts.sort_by(|x, y| (&x.first.recipe_id, &x.second.recipe_id).cmp(&(&y.first.recipe_id, &y.second.recipe_id)));
+    conflicts
+}
+
+fn summary(recipe: &CraftingRecipe) -> ConflictingRecipe {
+    ConflictingRecipe {
+        id: recipe.id,
+        recipe_id: recipe.recipe_id.clone(),
+        mod_name: recipe.mod_name.clone(),
+        result_item: recipe.result_item.clone(),
+    }
+}
+
+/// What the game matches a crafting grid against. Shaped grids match
+/// anywhere on the grid and mirrored too, so patterns are shrunk to the
+/// cells they use and the smaller of a grid and its mirror stands for both.
+fn signature(recipe_type: &str, raw_json: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(raw_json).ok()?;
+    match canonical_recipe_type(recipe_type).as_str() {
+        "minecraft:crafting_shaped" => {
+            let key = value.get("key")?.as_object()?;
+            let rows: Vec<Vec<String>> = recipe_parser::extract_pattern(&value)
+                .iter()
+                .map(|row| {
+                    row.chars()
+                        .map(|symbol| key.get(&symbol.to_string()).map(recipe_parser::ingredient_key).unwrap_or_default())
+                        .collect()
+                })
+                .collect();
+            let grid = |rows: &[Vec<String>]| rows.iter().map(|row| row.join(",")).collect::<Vec<_>>().join("/");
+            let mirrored: Vec<Vec<String>> = rows.iter().map(|row| row.iter().rev().cloned().collect()).collect();
+            Some(format!("shaped:{}", grid(&rows).min(grid(&mirrored))))
+        }
+        "minecraft:crafting_shapeless" => {
+            let mut ingredients: Vec<String> = value
+                .get("ingredients")?
+                .as_array()?
+                .iter()
+                .map(recipe_parser::ingredient_key)
+                .collect();
+            ingredients.sort();
+            Some(format!("shapeless:{}", ingredients.join(",")))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(id: i64, recipe_id: &str, recipe_type: &str, result: &str, raw_json: &str) -> CraftingRecipe {
+        CraftingRecipe {
+            id,
+            recipe_id: recipe_id.to_string(),
+            mod_name: "test.jar".to_string(),
+            recipe_type: recipe_type.to_string(),
+            result_item: Some(result.to_string()),
+            raw_json: raw_json.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let left = r#"{"pattern": ["a ", "aa"], "key": {"a": {"item": "minecraft:stick"}}}"#;
+        let right = r#"{"pattern": [" b", "bb"], "key": {"b": [{"item": "minecraft:stick"}]}}"#;
+        let shapeless_a = r#"{"ingredients": [{"tag": "c:ingots"}, {"item": "minecraft:stick"}]}"#;
+        let shapeless_b = r#"{"ingredients": [{"item": "minecraft:stick"}, {"tag": "c:ingots"}]}"#;
+        let recipes = vec![
+            recipe(1, "a:one", "minecraft:crafting_shaped", "a:one", left),
+            recipe(2, "b:two", "crafting_shaped", "b:two", right),
+            recipe(3, "a:three", "minecraft:crafting_shapeless", "a:three", shapeless_a),
+            recipe(4, "b:four", "minecraft:crafting_shapeless", "a:three", shapeless_b),
+        ];
+        let conflicts = find_conflicts(&recipes);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].first.id, conflicts[0].second.id), (1, 2));
+    }
+
+    #[test]
+    fn test_shifted_patterns_conflict() {
+        let left = r#"{"pattern": ["a ", "  "], "key": {"a": {"item": "minecraft:stick"}}}"#;
+        let right = r#"{"pattern": [" a", " "], "key": {"a": {"item": "minecraft:stick"}}}"#;
+        let recipes = vec![
+            recipe(1, "a:one", "minecraft:crafting_shaped", "a:one", left),
+            recipe(2, "b:two", "minecraft:crafting_shaped", "b:two", right),
+        ];
+        assert_eq!(find_conflicts(&recipes).len(), 1);
+    }
+}
@@ -0,0 +1,471 @@
+use crate::database::{Database, Recipe};
+use crate::recipe_parser;
+use rusqlite::Result as SqliteResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Stops a tree from growing without bound on packs with huge recipe webs.
+const MAX_NODES: usize = 5000;
+
+/// How to choose among recipes while resolving a tree.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct TreeOptions {
+    /// Recipe types never used, e.g. `create:crushing` to skip recycling
+    pub exclude_types: Vec<String>,
+    /// Recipe (by row id) to use for an item instead of the default pick
+    pub preferred_recipes: HashMap<String, i64>,
+    /// Item to use for a tag instead of the default pick
+    pub preferred_items: HashMap<String, String>,
+    /// Items treated as raw materials even when a recipe makes them
+    pub raw_items: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CraftingNode {
+    /// Item id, or tag id starting with `#`
+    pub item: String,
+    pub count: i64,
+    /// Item chosen to stand in for a tag
+    pub resolved_item: Option<String>,
+    pub recipe: Option<TreeRecipe>,
+    /// Row ids of the other recipes that make the item
+    pub alternatives: Vec<i64>,
+    pub status: NodeStatus,
+    pub children: Vec<CraftingNode>,
+}
+
+/// The recipe a node was resolved with.
+#[derive(Serialize)]
+pub struct TreeRecipe {
+    pub id: i64,
+    pub recipe_id: String,
+    pub recipe_type: String,
+    pub mod_name: String,
+    /// Crafts needed to make the node's count
+    pub crafts: i64,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    Crafted,
+    /// No recipe makes it, or it was marked raw
+    Raw,
+    /// The item is already being made further up this branch
+    Repeat,
+    /// Recipes exist but the depth limit was reached
+    DepthLimit,
+    /// The node limit was reached
+    Truncated,
+}
+
+/// An item and how many of it to make.
+#[derive(Deserialize)]
+pub struct PlanTarget {
+    pub item: String,
+    pub count: i64,
+}
+
+/// Everything needed to make a set of targets, with shared intermediates
+/// made once.
+#[derive(Serialize)]
+pub struct ShoppingList {
+    /// Crafts in an order that makes every ingredient before it is used
+    pub steps: Vec<PlanStep>,
+    /// Items no chosen recipe makes, with total amounts
+    pub raw_materials: Vec<PlanLine>,
+}
+
+#[derive(Serialize)]
+pub struct PlanStep {
+    pub item: String,
+    pub needed: i64,
+    pub recipe_id: String,
+    pub crafts: i64,
+    /// Made beyond what is needed, since recipes make whole batches
+    pub leftover: i64,
+}
+
+#[derive(Serialize)]
+pub struct PlanLine {
+    pub item: String,
+    pub count: i64,
+}
+
+struct Resolver<'a> {
+    db: &'a Database,
+    options: &'a TreeOptions,
+    excluded_types: HashSet<String>,
+    recipes: HashMap<String, Vec<Recipe>>,
+    nodes: usize,
+}
+
+/// Resolves how `count` of `item` is made, down to `depth` levels of
+/// recipes. Only item ingredients are followed.
+pub fn build_tree(db: &Database, item: &str, count: i64, depth: u32, options: &TreeOptions) -> SqliteResult<CraftingNode> {
+    let mut resolver = Resolver {
+        db,
+        options,
+        excluded_types: options.exclude_types.iter().map(|t| recipe_parser::canonical_recipe_type(t)).collect(),
+        recipes: HashMap::new(),
+        nodes: 0,
+    };
+    resolver.resolve(item, count.max(1), depth, &mut Vec::new())
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, item: &str, count: i64, depth: u32, path: &mut Vec<String>) -> SqliteResult<CraftingNode> {
+        self.nodes += 1;
+        let mut node = CraftingNode {
+            item: item.to_string(),
+            count,
+            resolved_item: None,
+            recipe: None,
+            alternatives: Vec::new(),
+            status: NodeStatus::Raw,
+            children: Vec::new(),
+        };
+        let target = match item.strip_prefix('#') {
+            Some(tag) => match self.pick_tag_item(tag)? {
+                Some(member) => {
+                    node.resolved_item = Some(member.clone());
+                    member
+                }
+                None => return Ok(node),
+            },
+            None => item.to_string(),
+        };
+        if self.options.raw_items.contains(&target) {
+            return Ok(node);
+        }
+        if path.contains(&target) {
+            node.status = NodeStatus::Repeat;
+            return Ok(node);
+        }
+
+        let candidates = self.recipes_for(&target)?;
+        let Some(recipe) = choose_recipe(&candidates, &target, path, self.options.preferred_recipes.get(&target)) else {
+            return Ok(node);
+        };
+        node.alternatives = candidates.iter().map(|r| r.id).filter(|id| *id != recipe.id).collect();
+        if depth == 0 {
+            node.status = NodeStatus::DepthLimit;
+            return Ok(node);
+        }
+        if self.nodes >= MAX_NODES {
+            node.status = NodeStatus::Truncated;
+            return Ok(node);
+        }
+
+        let crafts = crafts_needed(count, recipe.result_count);
+        node.status = NodeStatus::Crafted;
+        node.recipe = Some(TreeRecipe {
+            id: recipe.id,
+            recipe_id: recipe.recipe_id.clone(),
+            recipe_type: recipe.canonical_type.clone(),
+            mod_name: recipe.mod_name.clone(),
+            crafts,
+        });
+        path.push(target);
+        for ingredient in &recipe.ingredients {
+            let child = self.resolve(&ingredient.item, ingredient.count as i64 * crafts, depth - 1, path)?;
+            node.children.push(child);
+        }
+        path.pop();
+        Ok(node)
+    }
+
+    /// Recipes making `item`, minus excluded types, regardless of the pinned
+    /// filter; cached since the same items come up in many branches.
+    fn recipes_for(&mut self, item: &str) -> SqliteResult<Vec<Recipe>> {
+        if let Some(recipes) = self.recipes.get(item) {
+            return Ok(recipes.clone());
+        }
+        let mut recipes = self.db.recipes_making(item)?;
+        recipes.retain(|r| !self.excluded_types.contains(&r.canonical_type));
+        self.recipes.insert(item.to_string(), recipes.clone());
+        Ok(recipes)
+    }
+
+    /// The preferred item for a tag, else its first member that some recipe
+    /// makes, else its first member.
+    fn pick_tag_item(&mut self, tag: &str) -> SqliteResult<Option<String>> {
+        if let Some(item) = self.options.preferred_items.get(&format!("#{}", tag)) {
+            return Ok(Some(item.clone()));
+        }
+        let members = self.db.tag_members(tag)?;
+        for member in &members {
+            if !self.recipes_for(member)?.is_empty() {
+                return Ok(Some(member.clone()));
+            }
+        }
+        Ok(members.into_iter().next())
+    }
+}
+
+/// Totals what making every target takes. Recipes are chosen as in
+/// `build_tree`; an item gets one recipe for the whole plan, and demand for it
+/// is summed before its crafts are counted so batches are shared.
+pub fn plan(db: &Database, targets: &[PlanTarget], depth: u32, options: &TreeOptions) -> SqliteResult<ShoppingList> {
+    let mut choices = HashMap::new();
+    let mut tag_items = HashMap::new();
+    for target in targets {
+        let tree = build_tree(db, &target.item, target.count, depth, options)?;
+        collect_choices(&tree, &mut choices, &mut tag_items);
+    }
+    let ids: Vec<i64> = choices.values().copied().collect();
+    let recipes: HashMap<i64, Recipe> = db.get_recipes(&ids)?.into_iter().map(|r| (r.id, r)).collect();
+    // Recipes removed since the trees were built leave their items raw
+    choices.retain(|_, id| recipes.contains_key(id));
+    let ingredients = |recipe: &Recipe| -> Vec<(String, i64)> {
+        recipe
+            .ingredients
+            .iter()
+            .map(|i| (tag_items.get(&i.item).cloned().unwrap_or_else(|| i.item.clone()), i.count as i64))
+            .collect()
+    };
+
+    // Items ordered so each comes before everything its recipe uses. When
+    // chosen recipes form a loop, one item on it counts as raw, which frees
+    // the rest of the loop and everything after it.
+    let mut uses: HashMap<&str, usize> = choices.keys().map(|item| (item.as_str(), 0)).collect();
+    let mut users: HashMap<String, Vec<&str>> = HashMap::new();
+    for (item, id) in &choices {
+        for (ingredient, _) in ingredients(&recipes[id]) {
+            if let Some(count) = uses.get_mut(ingredient.as_str()) {
+                *count += 1;
+                users.entry(ingredient).or_default().push(item);
+            }
+        }
+    }
+    let mut ready: Vec<&str> = uses.iter().filter(|(_, n)| **n == 0).map(|(item, _)| *item).collect();
+    ready.sort();
+    let mut order = Vec::new();
+    let mut looped = HashSet::new();
+    loop {
+        while let Some(item) = ready.pop() {
+            uses.remove(item);
+            if !looped.contains(item) {
+                order.push(item.to_string());
+            }
+            for (ingredient, _) in ingredients(&recipes[&choices[item]]) {
+                if let Some(count) = uses.get_mut(ingredient.as_str()) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push(choices.get_key_value(&ingredient).unwrap().0);
+                    }
+                }
+            }
+        }
+        // Every item left is used by another item left, so walking from user
+        // to user must come back around a loop
+        let Some(mut item) = uses.keys().min().copied() else {
+            break;
+        };
+        let mut seen = HashSet::new();
+        while seen.insert(item) {
+            item = users[item].iter().filter(|user| uses.contains_key(*user)).min().unwrap();
+        }
+        looped.insert(item);
+        ready.push(item);
+    }
+
+    let mut demand: HashMap<String, i64> = HashMap::new();
+    for target in targets {
+        let item = tag_items.get(&target.item).unwrap_or(&target.item);
+        *demand.entry(item.clone()).or_default() += target.count.max(1);
+    }
+    let mut steps = Vec::new();
+    for item in order {
+        let Some(needed) = demand.remove(&item).filter(|n| *n > 0) else {
+            continue;
+        };
+        let recipe = &recipes[&choices[&item]];
+        let crafts = crafts_needed(needed, recipe.result_count);
+        for (ingredient, count) in ingredients(recipe) {
+            *demand.entry(ingredient).or_default() += count * crafts;
+        }
+        steps.push(PlanStep {
+            leftover: crafts * recipe.result_count.unwrap_or(1).max(1) as i64 - needed,
+            item,
+            needed,
+            recipe_id: recipe.recipe_id.clone(),
+            crafts,
+        });
+    }
+    steps.reverse();
+
+    let mut raw_materials: Vec<PlanLine> = demand.into_iter().map(|(item, count)| PlanLine { item, count }).collect();
+    raw_materials.sort_by(|a, b| a.item.cmp(&b.item));
+    Ok(ShoppingList { steps, raw_materials })
+}
+
+/// First recipe chosen for each item in a tree, and the item chosen for
+/// each tag.
+fn collect_choices(node: &CraftingNode, choices: &mut HashMap<String, i64>, tag_items: &mut HashMap<String, String>) {
+    if let Some(resolved) = &node.resolved_item {
+        tag_items.entry(node.item.clone()).or_insert_with(|| resolved.clone());
+    }
+    if let Some(recipe) = &node.recipe {
+        let item = node.resolved_item.as_ref().unwrap_or(&node.item);
+        choices.entry(item.clone()).or_insert(recipe.id);
+    }
+    for child in &node.children {
+        collect_choices(child, choices, tag_items);
+    }
+}
+
+/// The preferred recipe if given, else the first that does not use an item
+/// already being made on this branch, else the first.
+fn choose_recipe<'r>(candidates: &'r [Recipe], item: &str, path: &[String], preferred: Option<&i64>) -> Option<&'r Recipe> {
+    if let Some(recipe) = preferred.and_then(|id| candidates.iter().find(|r| r.id == *id)) {
+        return Some(recipe);
+    }
+    candidates
+        .iter()
+        .find(|r| !r.ingredients.iter().any(|i| i.item == item || path.contains(&i.item)))
+        .or_else(|| candidates.first())
+}
+
+/// Crafts of a recipe making `per_craft` items needed for `count` items.
+fn crafts_needed(count: i64, per_craft: Option<i32>) -> i64 {
+    let per_craft = per_craft.unwrap_or(1).max(1) as i64;
+    (count + per_craft - 1) / per_craft
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PinnedFilter;
+    use crate::test_support::{add_shapeless, temp_db};
+
+    fn target(item: &str) -> PlanTarget {
+        PlanTarget { item: item.to_string(), count: 1 }
+    }
+
+    fn raw(list: &ShoppingList) -> Vec<&str> {
+        list.raw_materials.iter().map(|line| line.item.as_str()).collect()
+    }
+
+    fn statuses(node: &CraftingNode) -> Vec<(&str, NodeStatus)> {
+        node.children.iter().map(|child| (child.item.as_str(), child.status)).collect()
+    }
+
+    #[test]
+    fn test_build_tree_ignores_pinned_filter() {
+        let db = temp_db();
+        let a = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        let b = db.insert_mod("b.jar", "/mods/b.jar", None).unwrap();
+        add_shapeless(&db, a, "a:x", "a:x", &["b:y"]);
+        add_shapeless(&db, b, "b:y", "b:y", &["b:z"]);
+        db.set_pinned_filter(PinnedFilter { mod_id: Some(a), ..Default::default() });
+
+        let tree = build_tree(&db, "a:x", 1, 10, &TreeOptions::default()).unwrap();
+        assert_eq!(statuses(&tree), vec![("b:y", NodeStatus::Crafted)]);
+        assert_eq!(statuses(&tree.children[0]), vec![("b:z", NodeStatus::Raw)]);
+    }
+
+    #[test]
+    fn test_build_tree_repeat_and_depth_limit() {
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:x", "a:x", &["a:y"]);
+        add_shapeless(&db, pack, "a:y", "a:y", &["a:x"]);
+
+        let tree = build_tree(&db, "a:x", 1, 10, &TreeOptions::default()).unwrap();
+        assert_eq!(tree.status, NodeStatus::Crafted);
+        assert_eq!(statuses(&tree.children[0]), vec![("a:x", NodeStatus::Repeat)]);
+
+        let tree = build_tree(&db, "a:x", 1, 1, &TreeOptions::default()).unwrap();
+        assert_eq!(statuses(&tree), vec![("a:y", NodeStatus::DepthLimit)]);
+        assert!(tree.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_build_tree_options() {
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:x_from_y", "a:x", &["a:y"]);
+        add_shapeless(&db, pack, "a:x_from_z", "a:x", &["a:z"]);
+        add_shapeless(&db, pack, "a:y", "a:y", &["a:w"]);
+
+        let tree = build_tree(&db, "a:x", 1, 10, &TreeOptions::default()).unwrap();
+        assert_eq!(tree.recipe.as_ref().unwrap().recipe_id, "a:x_from_y");
+        assert_eq!(tree.alternatives.len(), 1);
+        assert_eq!(statuses(&tree), vec![("a:y", NodeStatus::Crafted)]);
+
+        let options = TreeOptions { raw_items: vec!["a:y".to_string()], ..Default::default() };
+        let tree = build_tree(&db, "a:x", 1, 10, &options).unwrap();
+        assert_eq!(statuses(&tree), vec![("a:y", NodeStatus::Raw)]);
+        assert!(tree.children[0].recipe.is_none());
+
+        let options = TreeOptions {
+            preferred_recipes: HashMap::from([("a:x".to_string(), tree.alternatives[0])]),
+            ..Default::default()
+        };
+        let tree = build_tree(&db, "a:x", 1, 10, &options).unwrap();
+        assert_eq!(tree.recipe.as_ref().unwrap().recipe_id, "a:x_from_z");
+        assert_eq!(statuses(&tree), vec![("a:z", NodeStatus::Raw)]);
+    }
+
+    #[test]
+    fn test_build_tree_resolves_tags() {
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:gear", "a:gear", &["#c:plates"]);
+        add_shapeless(&db, pack, "a:iron_plate", "a:iron_plate", &["a:iron"]);
+        db.insert_tag_entries(pack, "c:plates", &["a:copper_plate".to_string(), "a:iron_plate".to_string()]).unwrap();
+
+        // The member some recipe makes wins over the first member
+        let tree = build_tree(&db, "a:gear", 1, 10, &TreeOptions::default()).unwrap();
+        let plate = &tree.children[0];
+        assert_eq!(plate.item, "#c:plates");
+        assert_eq!(plate.resolved_item.as_deref(), Some("a:iron_plate"));
+        assert_eq!(plate.status, NodeStatus::Crafted);
+
+        let options = TreeOptions {
+            preferred_items: HashMap::from([("#c:plates".to_string(), "a:copper_plate".to_string())]),
+            ..Default::default()
+        };
+        let tree = build_tree(&db, "a:gear", 1, 10, &options).unwrap();
+        let plate = &tree.children[0];
+        assert_eq!(plate.resolved_item.as_deref(), Some("a:copper_plate"));
+        assert_eq!(plate.status, NodeStatus::Raw);
+    }
+
+    #[test]
+    fn test_plan_breaks_loops() {
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:x", "a:x", &["a:b", "a:d"]);
+        add_shapeless(&db, pack, "a:b", "a:b", &["a:c"]);
+        add_shapeless(&db, pack, "a:c", "a:c", &["a:b", "a:d"]);
+        add_shapeless(&db, pack, "a:d", "a:d", &["a:e"]);
+
+        let list = plan(&db, &[target("a:x")], 10, &TreeOptions::default()).unwrap();
+        let steps: Vec<&str> = list.steps.iter().map(|s| s.item.as_str()).collect();
+        assert_eq!(steps, vec!["a:d", "a:x"]);
+        assert_eq!(raw(&list), vec!["a:b", "a:e"]);
+    }
+
+    #[test]
+    fn test_plan_tag_target() {
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:gear", "a:gear", &["a:plate"]);
+        db.insert_tag_entries(pack, "c:gears", &["a:gear".to_string()]).unwrap();
+
+        let list = plan(&db, &[target("#c:gears")], 10, &TreeOptions::default()).unwrap();
+        assert_eq!(list.steps.len(), 1);
+        assert_eq!(list.steps[0].item, "a:gear");
+        assert_eq!(raw(&list), vec!["a:plate"]);
+    }
+
+    #[test]
+    fn test_crafts_needed() {
+        assert_eq!(crafts_needed(1, Some(4)), 1);
+        assert_eq!(crafts_needed(9, Some(4)), 3);
+        assert_eq!(crafts_needed(3, None), 3);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::kubejs::Replacement;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Settings key holding the workspace's `ScriptEngine`.
+pub const SCRIPT_ENGINE_SETTING: &str = "script_engine";
+
+/// Which recipe scripting mod a pack manages its recipes with.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptEngine {
+    #[default]
+    KubeJs,
+    CraftTweaker,
+}
+
+/// Type crafting table recipes go through `craftingTable` for.
+const CRAFTING_TYPES: [&str; 2] = ["minecraft:crafting_shaped", "minecraft:crafting_shapeless"];
+
+/// A ZenScript file removing `removals` (recipe id and type) and swapping
+/// in `replacements`, by recipe id. Recipes added here are named after the
+/// id they replace, with the colon turned into a dot.
+pub fn script(removals: &[(String, String)], replacements: &[Replacement]) -> Result<String, String> {
+    let mut out = String::from("// Generated by modpack-workbench\n\n");
+    for (id, recipe_type) in removals {
+        out.push_str(&format!("{}.removeByName({});\n", manager(recipe_type), quote(id)));
+    }
+    for replacement in replacements {
+        let value: Value = serde_json::from_str(&replacement.json)
+            .map_err(|e| format!("Invalid JSON for {}: {}", replacement.recipe_id, e))?;
+        let recipe_type = value.get("type").and_then(Value::as_str).unwrap_or("minecraft:crafting_shaped");
+        let name = quote(&replacement.recipe_id.replace(':', "."));
+        out.push_str(&format!("{}.removeByName({});\n", manager(recipe_type), quote(&replacement.recipe_id)));
+        let add = crafting_call(&value, &name)
+            .unwrap_or_else(|| format!("{}.addJsonRecipe({}, {})", manager(recipe_type), name, value));
+        out.push_str(&format!("{};\n", add));
+    }
+    Ok(out)
+}
+
+fn manager(recipe_type: &str) -> String {
+    if CRAFTING_TYPES.contains(&recipe_type) {
+        "craftingTable".to_string()
+    } else {
+        format!("<recipetype:{}>", recipe_type)
+    }
+}
+
+/// `addShaped` or `addShapeless` for plain crafting recipes; None when the
+/// recipe needs `addJsonRecipe`.
+fn crafting_call(value: &Value, name: &str) -> Option<String> {
+    let result = value.get("result")?;
+    let item = result.as_str().or_else(|| result.get("item").or_else(|| result.get("id"))?.as_str())?;
+    let count = result.get("count").and_then(Value::as_i64).unwrap_or(1);
+    let output = if count == 1 { format!("<item:{}>", item) } else { format!("<item:{}> * {}", item, count) };
+    match value.get("type")?.as_str()?.trim_start_matches("minecraft:") {
+        "crafting_shaped" => {
+            let key = value.get("key")?.as_object()?;
+            let rows: Vec<String> = value
+                .get("pattern")?
+                .as_array()?
+                .iter()
+                .map(|row| {
+                    let slots: Vec<String> = row
+                        .as_str()?
+                        .chars()
+                        .map(|c| match c {
+                            ' ' => Some("<item:minecraft:air>".to_string()),
+                            c => bracket(key.get(&c.to_string())?),
+                        })
+                        .collect::<Option<_>>()?;
+                    Some(format!("[{}]", slots.join(", ")))
+                })
+                .collect::<Option<_>>()?;
+            Some(format!("craftingTable.addShaped({}, {}, [{}])", name, output, rows.join(", ")))
+        }
+        "crafting_shapeless" => {
+            let ingredients: Vec<String> =
+                value.get("ingredients")?.as_array()?.iter().map(bracket).collect::<Option<_>>()?;
+            Some(format!("craftingTable.addShapeless({}, {}, [{}])", name, output, ingredients.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// An ingredient as a bracket handler, `<item:minecraft:stick>` or
+/// `<tag:items:c:rods>`; None for alternatives and other shapes.
+fn bracket(ingredient: &Value) -> Option<String> {
+    if let Some(id) = ingredient.as_str() {
+        return Some(match id.strip_prefix('#') {
+            Some(tag) => format!("<tag:items:{}>", tag),
+            None => format!("<item:{}>", id),
+        });
+    }
+    if let Some(item) = ingredient.get("item").and_then(Value::as_str) {
+        return Some(format!("<item:{}>", item));
+    }
+    let tag = ingredient.get("tag").and_then(Value::as_str)?;
+    Some(format!("<tag:items:{}>", tag))
+}
+
+fn quote(text: &str) -> String {
+    Value::from(text).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script() {
+        let removals = vec![
+            ("minecraft:stick".to_string(), "minecraft:crafting_shaped".to_string()),
+            ("create:crushing/ore".to_string(), "create:crushing".to_string()),
+        ];
+        let replacements = vec![Replacement {
+            recipe_id: "create:gear".to_string(),
+            json: r#"{"type":"minecraft:crafting_shaped","pattern":["I ","II"],"key":{"I":{"tag":"c:ingots"}},"result":{"item":"create:gear","count":2}}"#
+                .to_string(),
+        }];
+        let text = script(&removals, &replacements).unwrap();
+        assert!(text.contains("craftingTable.removeByName(\"minecraft:stick\");\n"));
+        assert!(text.contains("<recipetype:create:crushing>.removeByName(\"create:crushing/ore\");\n"));
+        assert!(text.contains(
+            "craftingTable.addShaped(\"create.gear\", <item:create:gear> * 2, [[<tag:items:c:ingots>, <item:minecraft:air>], [<tag:items:c:ingots>, <tag:items:c:ingots>]]);\n"
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Longest loop searched for unless asked otherwise.
+pub const DEFAULT_MAX_LENGTH: usize = 4;
+/// Stops the search once this many loops are found.
+const MAX_CYCLES: usize = 1000;
+/// Caps the steps the loop search may take inside large components.
+const SEARCH_BUDGET: usize = 2_000_000;
+
+/// A recipe seen as turning one of its inputs into its output.
+#[derive(Serialize, Clone)]
+pub struct RecipeEdge {
+    pub recipe: i64,
+    pub recipe_id: String,
+    pub input: String,
+    pub input_count: i64,
+    /// Items the recipe also takes besides `input`
+    pub other_input_count: i64,
+    pub output: String,
+    pub output_count: i64,
+}
+
+impl RecipeEdge {
+    /// Items made per item put in, counting the recipe's other inputs too.
+    fn ratio(&self) -> f64 {
+        self.output_count.max(1) as f64 / (self.input_count.max(1) + self.other_input_count.max(0)) as f64
+    }
+}
+
+/// Items that craft back into themselves through a chain of recipes.
+#[derive(Serialize)]
+pub struct RecipeCycle {
+    pub steps: Vec<RecipeEdge>,
+    /// Items got back per item put in, once around the loop, counting every
+    /// input of each recipe. Storage loops like ingot to block and back come
+    /// to 1.
+    pub balance: f64,
+    /// Going around the loop makes items from nothing
+    pub duplicates: bool,
+}
+
+/// Finds loops of up to `max_length` recipes, or only the duplicating ones
+/// when `duplicates_only` is set. Between two items only the most generous
+/// recipe is followed, so a loop is reported once and shows its worst case.
+/// Duplicating loops come first.
+pub fn find_cycles(edges: &[RecipeEdge], max_length: usize, duplicates_only: bool) -> Vec<RecipeCycle> {
+    let mut best: HashMap<(&str, &str), &RecipeEdge> = HashMap::new();
+    for edge in edges.iter().filter(|e| e.input != e.output || e.ratio() > 1.0) {
+        let entry = best.entry((edge.input.as_str(), edge.output.as_str())).or_insert(edge);
+        if edge.ratio() > entry.ratio() {
+            *entry = edge;
+        }
+    }
+    let mut names: Vec<&str> = best.keys().flat_map(|(a, b)| [*a, *b]).collect();
+    names.sort();
+    names.dedup();
+    let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, name)| (*name, i)).collect();
+    let mut graph: Vec<Vec<(usize, &RecipeEdge)>> = vec![Vec::new(); names.len()];
+    let mut sorted: Vec<_> = best.into_iter().collect();
+    sorted.sort_by_key(|((a, b), _)| (*a, *b));
+    for ((input, output), edge) in sorted {
+        graph[index[input]].push((index[output], edge));
+    }
+
+    let mut search = Search {
+        component: components(&graph),
+        graph,
+        max_length: max_length.max(1),
+        duplicates_only,
+        cycles: Vec::new(),
+        budget: SEARCH_BUDGET,
+    };
+    for start in 0..search.graph.len() {
+        search.walk(start, start, &mut Vec::new());
+        if search.cycles.len() >= MAX_CYCLES || search.budget == 0 {
+            break;
+        }
+    }
+    let mut cycles = search.cycles;
+    cycles.sort_by(|a, b| b.balance.total_cmp(&a.balance));
+    cycles
+}
+
+struct Search<'a> {
+    graph: Vec<Vec<(usize, &'a RecipeEdge)>>,
+    component: Vec<usize>,
+    max_length: usize,
+    duplicates_only: bool,
+    cycles: Vec<RecipeCycle>,
+    budget: usize,
+}
+
+impl<'a> Search<'a> {
+    /// Walks simple paths from `start` through higher-numbered items of the
+    /// same component, so each loop is found once, from its lowest item.
+    fn walk(&mut self, start: usize, node: usize, path: &mut Vec<(usize, &'a RecipeEdge)>) {
+        for i in 0..self.graph[node].len() {
+            if self.budget == 0 || self.cycles.len() >= MAX_CYCLES {
+                return;
+            }
+            self.budget -= 1;
+            let (next, edge) = self.graph[node][i];
+            if self.component[next] != self.component[start] || next < start {
+                continue;
+            }
+            if next == start {
+                let steps: Vec<RecipeEdge> = path.iter().map(|(_, e)| (*e).clone()).chain([edge.clone()]).collect();
+                let balance = steps.iter().map(RecipeEdge::ratio).product::<f64>();
+                let duplicates = balance > 1.0 + 1e-9;
+                if duplicates || !self.duplicates_only {
+                    self.cycles.push(RecipeCycle { steps, balance, duplicates });
+                }
+            } else if path.len() + 1 < self.max_length && !path.iter().any(|(n, _)| *n == next) {
+                path.push((next, edge));
+                self.walk(start, next, path);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// Strongly connected component of every item (Kosaraju, without recursion).
+fn components(graph: &[Vec<(usize, &RecipeEdge)>]) -> Vec<usize> {
+    let n = graph.len();
+    let mut order = Vec::with_capacity(n);
+    let mut visited = vec![false; n];
+    for root in 0..n {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next_edge)) = stack.pop() {
+            if let Some(&(next, _)) = graph[node].get(next_edge) {
+                stack.push((node, next_edge + 1));
+                if !visited[next] {
+                    visited[next] = true;
+                    stack.push((next, 0));
+                }
+            } else {
+                order.push(node);
+            }
+        }
+    }
+
+    let mut reverse = vec![Vec::new(); n];
+    for (node, edges) in graph.iter().enumerate() {
+        for &(next, _) in edges {
+            reverse[next].push(node);
+        }
+    }
+    let mut component = vec![usize::MAX; n];
+    for (id, &root) in order.iter().rev().enumerate() {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        component[root] = id;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            for &prev in &reverse[node] {
+                if component[prev] == usize::MAX {
+                    component[prev] = id;
+                    stack.push(prev);
+                }
+            }
+        }
+    }
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(input: &str, input_count: i64, output: &str, output_count: i64) -> RecipeEdge {
+        RecipeEdge {
+            recipe: 0,
+            recipe_id: format!("{}_from_{}", output, input),
+            input: input.to_string(),
+            input_count,
+            other_input_count: 0,
+            output: output.to_string(),
+            output_count,
+        }
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let edges = vec![
+            edge("ingot", 9, "block", 1),
+            edge("block", 1, "ingot", 9),
+            edge("ingot", 1, "nugget", 9),
+            edge("nugget", 9, "ingot", 1),
+            edge("gear", 1, "plate", 2),
+            edge("plate", 1, "gear", 1),
+            edge("ingot", 1, "gear", 1),
+        ];
+        let cycles = find_cycles(&edges, 4, false);
+        assert_eq!(cycles.len(), 3);
+        assert!(cycles[0].duplicates);
+        assert_eq!(cycles[0].balance, 2.0);
+        assert!(cycles[1..].iter().all(|c| !c.duplicates && c.balance == 1.0));
+
+        let duplicating = find_cycles(&edges, 4, true);
+        assert_eq!(duplicating.len(), 1);
+        assert_eq!(duplicating[0].balance, 2.0);
+    }
+
+    #[test]
+    fn test_other_inputs_count_against_the_loop() {
+        // Two plates and a stick make two gears; a gear makes two plates
+        let edges = vec![
+            RecipeEdge { other_input_count: 1, ..edge("plate", 2, "gear", 2) },
+            edge("gear", 1, "plate", 2),
+        ];
+        let cycles = find_cycles(&edges, 4, false);
+        assert_eq!(cycles.len(), 1);
+        assert!((cycles[0].balance - 4.0 / 3.0).abs() < 1e-9);
+    }
+}
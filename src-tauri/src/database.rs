@@ -1,30 +1,1289 @@
-use rusqlite::{Connection, Result as SqliteResult};
-use serde::Serialize;
-use std::path::PathBuf;
+use crate::complexity::ComplexityRecipe;
+use crate::components::ItemComponents;
+use crate::configs::ConfigValue;
+use crate::conflicts::CraftingRecipe;
+use crate::cycles::RecipeEdge;
+use crate::depgraph::RecipeDependency;
+use crate::enchantments::ParsedEnchantment;
+use crate::hashing::JarHashes;
+use crate::items;
+use crate::load_order::{self, LoadEntry, ProviderKind};
+use crate::matching::{self, MatchMode};
+use crate::modmeta::{ModDependency, ModMetadata};
+use crate::recipe_diff;
+use crate::recipe_parser::{self, FluidAmount, IngredientCount, ParsedRecipe};
+use crate::recipe_query::Query;
+use crate::scanner;
+use crate::session_diff::{self, RecipeHistory, SessionMod, SessionSnapshot, StaleKind, StaleTarget};
+use crate::stats::{self, CategoryCount, ParserCoverage};
+use crate::trades::ParsedTrade;
+use crate::unification::{self, OutputItem, TagUnification, TagVariant};
+use crate::values::ValueRecipe;
+use rusqlite::backup::Progress;
+use rusqlite::types::Value;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params_from_iter, Connection, DatabaseName, OpenFlags, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Database {
     conn: Mutex<Connection>,
+    /// Read-only connection for queries, so searches don't wait behind
+    /// extraction writes
+    reader: Mutex<Connection>,
+    pinned_filter: Mutex<PinnedFilter>,
+    path: PathBuf,
 }
 
-#[derive(Serialize, Clone)]
+/// A filter pinned for the rest of the session. Every recipe list, search and
+/// count applies it until it is cleared.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct PinnedFilter {
+    /// Recipe id namespace, e.g. `create` for recipes under `data/create/`
+    pub namespace: Option<String>,
+    pub recipe_type: Option<String>,
+    pub mod_id: Option<i64>,
+}
+
+/// Order for recipe lists and searches.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct RecipeSort {
+    pub by: SortKey,
+    pub descending: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    ResultItem,
+    RecipeType,
+    Mod,
+    RecipeId,
+    IngredientCount,
+    /// Extraction order; descending puts the newest first
+    Recent,
+}
+
+impl RecipeSort {
+    pub fn by(by: SortKey) -> Self {
+        RecipeSort { by, descending: false }
+    }
+
+    /// ORDER BY terms over `recipes r` joined with `mods m`. Ties fall back
+    /// to a fixed order so pages stay stable.
+    fn order_clause(&self) -> String {
+        let direction = if self.descending { "DESC" } else { "ASC" };
+        let (key, ties) = match self.by {
+            SortKey::ResultItem => ("r.result_item", "m.name, r.id"),
+            SortKey::RecipeType => ("r.canonical_type", "r.result_item, r.id"),
+            SortKey::Mod => ("m.name COLLATE NOCASE", "r.path, r.id"),
+            SortKey::RecipeId => ("r.recipe_id", "r.id"),
+            SortKey::IngredientCount => {
+                ("(SELECT COUNT(*) FROM recipe_ingredients i WHERE i.recipe_id = r.id)", "r.result_item, r.id")
+            }
+            SortKey::Recent => ("r.id", ""),
+        };
+        match ties {
+            "" => format!("{} {}", key, direction),
+            _ => format!("{} {}, {}", key, direction, ties),
+        }
+    }
+
+    /// The sort key alone, for keyset pages; ties there fall back to the
+    /// row id. Never NULL, so every row compares.
+    fn keyset_key(&self) -> &'static str {
+        match self.by {
+            SortKey::ResultItem => "COALESCE(r.result_item, '')",
+            SortKey::RecipeType => "r.canonical_type",
+            SortKey::Mod => "m.name COLLATE NOCASE",
+            SortKey::RecipeId => "r.recipe_id",
+            SortKey::IngredientCount => "(SELECT COUNT(*) FROM recipe_ingredients i WHERE i.recipe_id = r.id)",
+            SortKey::Recent => "r.id",
+        }
+    }
+
+    /// The value `keyset_key` has for a recipe.
+    fn key_of(&self, recipe: &Recipe) -> JsonValue {
+        match self.by {
+            SortKey::ResultItem => JsonValue::from(recipe.result_item.clone().unwrap_or_default()),
+            SortKey::RecipeType => JsonValue::from(recipe.canonical_type.clone()),
+            SortKey::Mod => JsonValue::from(recipe.mod_name.clone()),
+            SortKey::RecipeId => JsonValue::from(recipe.recipe_id.clone()),
+            SortKey::IngredientCount => JsonValue::from(recipe.ingredients.len()),
+            SortKey::Recent => JsonValue::from(recipe.id),
+        }
+    }
+}
+
+/// Where a page of recipes ended: the sort key and row id of its last
+/// recipe. Clients get it as an opaque string to pass back for the next page.
+#[derive(Serialize, Deserialize)]
+pub struct RecipeCursor {
+    key: JsonValue,
+    id: i64,
+}
+
+impl RecipeCursor {
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_string(self).unwrap_or_default();
+        json.bytes().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn decode(cursor: &str) -> Result<RecipeCursor, String> {
+        let invalid = || "Invalid cursor".to_string();
+        // An odd length leaves a short last chunk, which fails to parse
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| cursor.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(invalid)?;
+        serde_json::from_slice(&bytes).map_err(|_| invalid())
+    }
+
+    fn key_param(&self) -> Value {
+        match &self.key {
+            JsonValue::Number(n) => Value::from(n.as_i64().unwrap_or_default()),
+            JsonValue::String(s) => Value::from(s.clone()),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// One page of a keyset-paginated list.
+#[derive(Serialize)]
+pub struct RecipePage {
+    pub recipes: Vec<Recipe>,
+    /// Unset on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// Criteria for `search_recipes`; every field set must match, on top of the
+/// pinned filter. Item fields are compared using `match_mode`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct RecipeSearch {
+    pub output: Option<String>,
+    pub ingredient: Option<String>,
+    pub recipe_type: Option<String>,
+    pub mod_id: Option<i64>,
+    pub namespace: Option<String>,
+    /// Recipe file path inside its jar, e.g. `data/create/recipes/mixing/`
+    pub path: Option<String>,
+    pub match_mode: MatchMode,
+    /// Also match tag ingredients that contain the ingredient
+    pub expand_tags: bool,
+    /// Result id namespaces to keep, e.g. `minecraft`; empty keeps all
+    pub result_namespaces: Vec<String>,
+    pub exclude_result_namespaces: Vec<String>,
+    /// Keeps recipes with at least one ingredient from these namespaces
+    pub ingredient_namespaces: Vec<String>,
+    /// Drops recipes with any ingredient from these namespaces
+    pub exclude_ingredient_namespaces: Vec<String>,
+    /// Whether any ingredient is a tag such as `#c:ingots/iron`
+    pub uses_tags: Option<bool>,
+    /// Whether any ingredient is a concrete item rather than a tag
+    pub uses_items: Option<bool>,
+    /// Crafting table recipes that fit an N by N grid, e.g. 2 for the
+    /// inventory grid
+    pub fits_grid: Option<i64>,
+    /// Crafting table recipes that do not fit an N-1 by N-1 grid, e.g. 3 for
+    /// recipes that need a crafting table
+    pub needs_grid: Option<i64>,
+    /// Shaped recipes with an ingredient in every one of these slots,
+    /// numbered 0 to 8 left to right, top to bottom
+    pub slots: Vec<i64>,
+    /// Bounds, inclusive, on the number of distinct item ingredients
+    pub min_ingredients: Option<i64>,
+    pub max_ingredients: Option<i64>,
+    /// Bounds, inclusive, on how many items the recipe makes
+    pub min_result_count: Option<i64>,
+    pub max_result_count: Option<i64>,
+    /// Also match hidden recipes and recipes from hidden mods
+    pub include_hidden: bool,
+    pub sort: RecipeSort,
+    pub offset: i64,
+    /// Defaults to 200
+    pub limit: Option<i64>,
+}
+
+impl RecipeSearch {
+    /// Conditions over `recipes r` for every criterion set.
+    fn conditions(&self, params: &mut Vec<Value>) -> Vec<String> {
+        let mut conditions = Vec::new();
+        if let Some(output) = &self.output {
+            conditions.push(matching::condition("r.result_item", output, self.match_mode, params));
+        }
+        if let Some(ingredient) = &self.ingredient {
+            conditions.push(ingredient_condition(ingredient, self.match_mode, self.expand_tags, params));
+        }
+        if let Some(path) = &self.path {
+            conditions.push(matching::condition("r.path", path, self.match_mode, params));
+        }
+        if let Some(recipe_type) = &self.recipe_type {
+            params.push(Value::from(recipe_parser::canonical_recipe_type(recipe_type)));
+            conditions.push(format!("r.canonical_type = ?{}", params.len()));
+        }
+        if let Some(mod_id) = self.mod_id {
+            params.push(Value::from(mod_id));
+            conditions.push(format!("r.mod_id = ?{}", params.len()));
+        }
+        if let Some(namespace) = &self.namespace {
+            params.push(Value::from(namespace.clone()));
+            conditions.push(format!("r.namespace = ?{}", params.len()));
+        }
+        if !self.result_namespaces.is_empty() {
+            let list = placeholders(&self.result_namespaces, params);
+            conditions.push(format!("r.result_namespace IN ({})", list));
+        }
+        if !self.exclude_result_namespaces.is_empty() {
+            let list = placeholders(&self.exclude_result_namespaces, params);
+            conditions.push(format!("(r.result_namespace IS NULL OR r.result_namespace NOT IN ({}))", list));
+        }
+        if !self.ingredient_namespaces.is_empty() {
+            let list = placeholders(&self.ingredient_namespaces, params);
+            conditions.push(format!(
+                "r.id IN (SELECT recipe_id FROM recipe_ingredients WHERE namespace IN ({}))",
+                list
+            ));
+        }
+        if !self.exclude_ingredient_namespaces.is_empty() {
+            let list = placeholders(&self.exclude_ingredient_namespaces, params);
+            conditions.push(format!(
+                "r.id NOT IN (SELECT recipe_id FROM recipe_ingredients WHERE namespace IN ({}))",
+                list
+            ));
+        }
+        let kinds = [(self.uses_tags, "LIKE"), (self.uses_items, "NOT LIKE")];
+        for (wanted, op) in kinds {
+            if let Some(wanted) = wanted {
+                conditions.push(format!(
+                    "{}EXISTS (SELECT 1 FROM recipe_ingredients i WHERE i.recipe_id = r.id AND i.item {} '#%')",
+                    if wanted { "" } else { "NOT " },
+                    op
+                ));
+            }
+        }
+        if let Some(size) = self.fits_grid {
+            conditions.push(grid_condition(size, params));
+        }
+        if let Some(size) = self.needs_grid {
+            conditions.push(format!(
+                "r.canonical_type IN ('minecraft:crafting_shaped', 'minecraft:crafting_shapeless') AND NOT {}",
+                grid_condition(size - 1, params)
+            ));
+        }
+        for slot in &self.slots {
+            params.push(Value::from(slot / 3));
+            params.push(Value::from(slot % 3));
+            let (row, col) = (params.len() - 1, params.len());
+            conditions.push(format!(
+                "r.grid_width > ?{col} AND substr(r.pattern, ?{row} * (r.grid_width + 1) + ?{col} + 1, 1) NOT IN ('', ' ', '/')"
+            ));
+        }
+        let ingredient_count = "(SELECT COUNT(*) FROM recipe_ingredients i WHERE i.recipe_id = r.id)";
+        let bounds = [
+            (ingredient_count, ">=", self.min_ingredients),
+            (ingredient_count, "<=", self.max_ingredients),
+            ("r.result_count", ">=", self.min_result_count),
+            ("r.result_count", "<=", self.max_result_count),
+        ];
+        for (column, op, bound) in bounds {
+            if let Some(bound) = bound {
+                params.push(Value::from(bound));
+                conditions.push(format!("{} {} ?{}", column, op, params.len()));
+            }
+        }
+        conditions
+    }
+}
+
+/// How the matches of a search break down, for filter sidebars.
+#[derive(Serialize)]
+pub struct SearchFacets {
+    pub recipe_types: Vec<CategoryCount>,
+    pub mods: Vec<ModFacet>,
+    /// By recipe id namespace
+    pub namespaces: Vec<CategoryCount>,
+}
+
+#[derive(Serialize)]
+pub struct ModFacet {
+    pub mod_id: i64,
+    pub name: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Clone, Default)]
 pub struct Recipe {
     pub id: i64,
+    pub mod_id: i64,
     pub mod_name: String,
     pub path: String,
+    /// Namespaced id the game knows the recipe by, e.g. `minecraft:iron_block`
+    pub recipe_id: String,
+    /// Type as written in the recipe file
     pub recipe_type: String,
+    /// Namespaced lowercase type that type filters and stats group by
+    pub canonical_type: String,
     pub result_item: Option<String>,
     pub result_count: Option<i32>,
-    pub ingredients: Vec<String>,
+    pub ingredients: Vec<IngredientCount>,
+    pub raw_json: String,
+    pub machine: Option<String>,
+    /// Mod owning the recipe's namespace, which differs from `mod_id` for
+    /// compat recipes shipped by another jar
+    pub owner_mod_id: Option<i64>,
+    pub owner_mod_name: Option<String>,
+    /// How many jars ship a recipe with this id. Above one, all but one of
+    /// them are overridden in game.
+    pub provider_count: i64,
+    pub hidden: bool,
+}
+
+/// A recipe resembling another, for spotting near-duplicates.
+#[derive(Serialize)]
+pub struct SimilarRecipe {
+    pub recipe: Recipe,
+    /// 0 to 1, averaging ingredient similarity with whether the outputs match
+    pub score: f64,
+    /// Shared ingredient amounts over total ingredient amounts, 0 to 1
+    pub ingredient_similarity: f64,
+    pub same_output: bool,
+}
+
+/// A recipe id shipped by several jars, e.g. a vanilla recipe replaced by
+/// datapacks.
+#[derive(Serialize)]
+pub struct RecipeOverride {
+    pub recipe_id: String,
+    pub providers: Vec<RecipeProvider>,
+    /// Row id of the copy players see, the one loaded last
+    pub winner: Option<i64>,
+}
+
+/// A user's note on a recipe id. Notes are keyed by recipe id rather than
+/// row id so they survive re-extraction.
+#[derive(Serialize)]
+pub struct RecipeNote {
+    pub recipe_id: String,
+    pub note: String,
+    /// Free-form review status such as "ok", "needs nerf" or "remove"
+    pub status: Option<String>,
+    pub updated_at: String,
+}
+
+/// A stored search history row; `saved_searches::history` parses the definition.
+pub struct SearchHistoryRow {
+    pub id: i64,
+    pub searched_at: String,
+    pub definition: String,
+    pub result_count: i64,
+}
+
+/// Kinds of bookmark a collection can hold.
+pub const BOOKMARK_RECIPE: &str = "recipe";
+pub const BOOKMARK_ITEM: &str = "item";
+
+/// A named list of bookmarked recipes and items.
+#[derive(Serialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub entry_count: i64,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct CollectionContents {
+    pub collection: Collection,
+    /// Every jar's copy of each bookmarked recipe id
+    pub recipes: Vec<Recipe>,
+    pub items: Vec<Item>,
+    /// Bookmarks whose recipe or item is not in the current extraction
+    pub missing: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct RecipeProvider {
+    /// Row id of this provider's copy of the recipe
+    pub id: i64,
+    pub mod_id: i64,
+    pub mod_name: String,
+    pub path: String,
+    pub kind: ProviderKind,
+}
+
+/// Column list shared by every query that returns full `Recipe` rows.
+const RECIPE_SELECT: &str =
+    "SELECT r.id, r.mod_id, m.name, r.path, r.recipe_id, r.recipe_type, r.result_item, r.result_count,
+            r.raw_json, r.machine, ns.mod_id, om.name, r.canonical_type,
+            (SELECT COUNT(*) FROM recipes o WHERE o.recipe_id = r.recipe_id AND r.recipe_id != ''), r.hidden
+     FROM recipes r
+     JOIN mods m ON r.mod_id = m.id
+     LEFT JOIN namespaces ns ON ns.namespace = r.namespace
+     LEFT JOIN mods om ON om.id = ns.mod_id";
+
+/// Tables filled by extraction, children before the tables they reference.
+const EXTRACTED_TABLES: &[&str] = &[
+    "recipe_ingredients",
+    "recipe_fluids",
+    "recipes",
+    "item_names",
+    "machines",
+    "enchantments",
+    "worldgen_refs",
+    "worldgen",
+    "trade_costs",
+    "trades",
+    "extraction_errors",
+    "item_components",
+    "item_refs",
+    "tag_replacements",
+    "item_tags",
+    "fluid_tags",
+    "items",
+    "mod_dependencies",
+    "namespaces",
+    "mods",
+];
+
+/// Schema changes for databases created by older versions, applied in order.
+/// `PRAGMA user_version` records how many have already been applied.
+const MIGRATIONS: &[&str] = &[
+    "ALTER TABLE recipe_ingredients ADD COLUMN count INTEGER NOT NULL DEFAULT 1;",
+    "CREATE TABLE recipe_fluids (
+        id INTEGER PRIMARY KEY,
+        recipe_id INTEGER NOT NULL REFERENCES recipes(id) ON DELETE CASCADE,
+        fluid TEXT NOT NULL,
+        amount INTEGER,
+        is_output INTEGER NOT NULL
+    );
+    CREATE INDEX idx_fluids_fluid ON recipe_fluids(fluid);
+    CREATE INDEX idx_fluids_recipe ON recipe_fluids(recipe_id);",
+    "CREATE TABLE item_names (
+        item_id TEXT NOT NULL,
+        locale TEXT NOT NULL,
+        name TEXT NOT NULL,
+        PRIMARY KEY (item_id, locale)
+    );",
+    "ALTER TABLE recipes ADD COLUMN machine TEXT;
+    CREATE INDEX idx_recipes_machine ON recipes(machine);
+    CREATE TABLE machines (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        machine_id TEXT NOT NULL,
+        name TEXT,
+        raw_json TEXT NOT NULL,
+        UNIQUE(mod_id, machine_id)
+    );",
+    "CREATE TABLE jar_runs (
+        id INTEGER PRIMARY KEY,
+        jar_path TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        duration_ms INTEGER,
+        status TEXT NOT NULL
+    );
+    CREATE INDEX idx_jar_runs_path ON jar_runs(jar_path);
+    CREATE TABLE quarantined_jars (
+        jar_path TEXT PRIMARY KEY,
+        reason TEXT NOT NULL,
+        quarantined_at TEXT NOT NULL
+    );",
+    "CREATE TABLE enchantments (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        enchantment_id TEXT NOT NULL,
+        description TEXT,
+        supported_items TEXT NOT NULL,
+        max_level INTEGER,
+        weight INTEGER,
+        anvil_cost INTEGER,
+        min_cost_base INTEGER,
+        min_cost_per_level INTEGER,
+        max_cost_base INTEGER,
+        max_cost_per_level INTEGER,
+        effects TEXT NOT NULL,
+        raw_json TEXT NOT NULL,
+        UNIQUE(mod_id, enchantment_id)
+    );
+    CREATE INDEX idx_enchantments_id ON enchantments(enchantment_id);",
+    "ALTER TABLE jar_runs ADD COLUMN mod_key TEXT;
+    ALTER TABLE jar_runs ADD COLUMN recipe_count INTEGER;
+    CREATE INDEX idx_jar_runs_mod_key ON jar_runs(mod_key);",
+    "CREATE TABLE worldgen (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL,
+        entry_id TEXT NOT NULL,
+        raw_json TEXT NOT NULL,
+        UNIQUE(mod_id, kind, entry_id)
+    );
+    CREATE INDEX idx_worldgen_kind ON worldgen(kind);
+    CREATE TABLE worldgen_refs (
+        id INTEGER PRIMARY KEY,
+        worldgen_id INTEGER NOT NULL REFERENCES worldgen(id) ON DELETE CASCADE,
+        ref TEXT NOT NULL
+    );
+    CREATE INDEX idx_worldgen_refs_ref ON worldgen_refs(ref);
+    CREATE INDEX idx_worldgen_refs_worldgen ON worldgen_refs(worldgen_id);",
+    "ALTER TABLE recipes ADD COLUMN recipe_id TEXT NOT NULL DEFAULT '';
+    CREATE INDEX idx_recipes_recipe_id ON recipes(recipe_id);
+    CREATE TABLE settings (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );",
+    "CREATE TABLE trades (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        source TEXT NOT NULL,
+        profession TEXT,
+        level INTEGER,
+        result_item TEXT NOT NULL,
+        result_count INTEGER NOT NULL
+    );
+    CREATE INDEX idx_trades_result ON trades(result_item);
+    CREATE TABLE trade_costs (
+        id INTEGER PRIMARY KEY,
+        trade_id INTEGER NOT NULL REFERENCES trades(id) ON DELETE CASCADE,
+        item TEXT NOT NULL,
+        count INTEGER NOT NULL
+    );
+    CREATE INDEX idx_trade_costs_trade ON trade_costs(trade_id);",
+    "CREATE VIRTUAL TABLE config_entries USING fts5(file, key, value);",
+    "CREATE TABLE extraction_errors (
+        id INTEGER PRIMARY KEY,
+        category TEXT NOT NULL
+    );",
+    "CREATE TABLE item_components (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        path TEXT NOT NULL,
+        item TEXT NOT NULL,
+        nutrition INTEGER,
+        saturation REAL,
+        max_stack_size INTEGER,
+        max_damage INTEGER
+    );
+    CREATE INDEX idx_item_components_item ON item_components(item);",
+    "ALTER TABLE mods ADD COLUMN loader_mod_id TEXT;
+    ALTER TABLE mods ADD COLUMN display_name TEXT;
+    ALTER TABLE mods ADD COLUMN version TEXT;
+    CREATE TABLE mod_dependencies (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        dependency TEXT NOT NULL,
+        version_range TEXT,
+        required INTEGER NOT NULL
+    );
+    CREATE INDEX idx_mod_dependencies_mod ON mod_dependencies(mod_id);",
+    "ALTER TABLE mods ADD COLUMN loader TEXT;
+    ALTER TABLE mods ADD COLUMN environment TEXT;",
+    "ALTER TABLE mods ADD COLUMN parent_mod_id INTEGER REFERENCES mods(id) ON DELETE CASCADE;",
+    "ALTER TABLE mods ADD COLUMN minecraft_versions TEXT;
+    CREATE INDEX idx_mods_loader ON mods(loader);",
+    "ALTER TABLE mods ADD COLUMN icon TEXT;",
+    "ALTER TABLE recipes ADD COLUMN namespace TEXT NOT NULL DEFAULT '';
+    UPDATE recipes SET namespace = substr(recipe_id, 1, instr(recipe_id, ':') - 1);
+    CREATE INDEX idx_recipes_namespace ON recipes(namespace);
+    CREATE TABLE namespaces (
+        namespace TEXT PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE
+    );",
+    // One jar may hold several mods, so the path is no longer unique on its own
+    "CREATE TABLE mods_new (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        path TEXT NOT NULL,
+        scanned_at TEXT NOT NULL,
+        loader_mod_id TEXT,
+        display_name TEXT,
+        version TEXT,
+        loader TEXT,
+        environment TEXT,
+        parent_mod_id INTEGER REFERENCES mods(id) ON DELETE CASCADE,
+        minecraft_versions TEXT,
+        icon TEXT,
+        UNIQUE(path, loader_mod_id)
+    );
+    INSERT INTO mods_new (id, name, path, scanned_at, loader_mod_id, display_name, version, loader, environment,
+                          parent_mod_id, minecraft_versions, icon)
+    SELECT id, name, path, scanned_at, loader_mod_id, display_name, version, loader, environment,
+           parent_mod_id, minecraft_versions, icon
+    FROM mods;
+    DROP TABLE mods;
+    ALTER TABLE mods_new RENAME TO mods;
+    CREATE INDEX idx_mods_loader ON mods(loader);
+    CREATE INDEX idx_mods_path ON mods(path);",
+    "ALTER TABLE mods ADD COLUMN license TEXT;
+    ALTER TABLE mods ADD COLUMN homepage TEXT;
+    ALTER TABLE mods ADD COLUMN issue_tracker TEXT;
+    ALTER TABLE mods ADD COLUMN authors TEXT;",
+    "ALTER TABLE mods ADD COLUMN sha1 TEXT;
+    ALTER TABLE mods ADD COLUMN sha512 TEXT;
+    CREATE INDEX idx_mods_sha1 ON mods(sha1);",
+    "CREATE VIRTUAL TABLE recipes_fts USING fts5(path, raw_json);
+    INSERT INTO recipes_fts (rowid, path, raw_json) SELECT id, path, raw_json FROM recipes;
+    CREATE TRIGGER recipes_fts_insert AFTER INSERT ON recipes BEGIN
+        DELETE FROM recipes_fts WHERE rowid = new.id;
+        INSERT INTO recipes_fts (rowid, path, raw_json) VALUES (new.id, new.path, new.raw_json);
+    END;
+    CREATE TRIGGER recipes_fts_delete AFTER DELETE ON recipes BEGIN
+        DELETE FROM recipes_fts WHERE rowid = old.id;
+    END;",
+    "CREATE TABLE extraction_sessions (
+        id INTEGER PRIMARY KEY,
+        started_at TEXT NOT NULL,
+        folder TEXT,
+        jar_count INTEGER NOT NULL,
+        duration_ms INTEGER,
+        error_count INTEGER,
+        recipe_count INTEGER,
+        status TEXT NOT NULL
+    );
+    ALTER TABLE recipes ADD COLUMN session_id INTEGER REFERENCES extraction_sessions(id);",
+    // Mirrors recipe_parser::canonical_recipe_type
+    "ALTER TABLE recipes ADD COLUMN canonical_type TEXT NOT NULL DEFAULT '';
+    UPDATE recipes SET canonical_type = CASE
+        WHEN lower(trim(recipe_type)) = 'unknown' OR instr(recipe_type, ':') > 0 THEN lower(trim(recipe_type))
+        ELSE 'minecraft:' || lower(trim(recipe_type))
+    END;
+    CREATE INDEX idx_recipes_canonical_type ON recipes(canonical_type);",
+    "CREATE TABLE item_refs (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        item TEXT NOT NULL,
+        source TEXT NOT NULL
+    );
+    CREATE TABLE items (
+        item TEXT PRIMARY KEY,
+        namespace TEXT NOT NULL,
+        path TEXT NOT NULL,
+        display_name TEXT,
+        mod_id INTEGER REFERENCES mods(id) ON DELETE SET NULL
+    );
+    CREATE INDEX idx_items_namespace ON items(namespace);",
+    // Indexes on foreign keys keep cascading deletes from scanning whole tables
+    "CREATE INDEX idx_machines_mod ON machines(mod_id);
+    CREATE INDEX idx_enchantments_mod ON enchantments(mod_id);
+    CREATE INDEX idx_worldgen_mod ON worldgen(mod_id);
+    CREATE INDEX idx_trades_mod ON trades(mod_id);
+    CREATE INDEX idx_item_components_mod ON item_components(mod_id);
+    CREATE INDEX idx_item_refs_mod ON item_refs(mod_id);
+    CREATE INDEX idx_items_mod ON items(mod_id);
+    CREATE INDEX idx_mods_parent ON mods(parent_mod_id);",
+    "ALTER TABLE mods ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE recipes ADD COLUMN hidden INTEGER NOT NULL DEFAULT 0;",
+    // Not in EXTRACTED_TABLES, so notes outlive re-extraction
+    "CREATE TABLE recipe_notes (
+        recipe_id TEXT PRIMARY KEY,
+        note TEXT NOT NULL,
+        status TEXT,
+        updated_at TEXT NOT NULL
+    );",
+    // Bookmarks hold recipe and item ids, not row ids, so like notes they
+    // outlive re-extraction
+    "CREATE TABLE collections (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        created_at TEXT NOT NULL
+    );
+    CREATE TABLE collection_entries (
+        collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL CHECK (kind IN ('recipe', 'item')),
+        target TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        PRIMARY KEY (collection_id, kind, target)
+    );",
+    // Definitions are saved_searches::SavedSearch as JSON
+    "CREATE TABLE saved_searches (
+        name TEXT PRIMARY KEY COLLATE NOCASE,
+        definition TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );",
+    // Definitions are saved_searches::SearchSpec as JSON
+    "CREATE TABLE search_history (
+        id INTEGER PRIMARY KEY,
+        searched_at TEXT NOT NULL,
+        definition TEXT NOT NULL,
+        result_count INTEGER NOT NULL
+    );",
+    "ALTER TABLE jar_runs ADD COLUMN error_count INTEGER;",
+    // Entries are item ids or nested tags written as `#namespace:path`
+    "CREATE TABLE item_tags (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        entry TEXT NOT NULL
+    );
+    CREATE INDEX idx_item_tags_tag ON item_tags(tag);
+    CREATE INDEX idx_item_tags_entry ON item_tags(entry);
+    CREATE INDEX idx_item_tags_mod ON item_tags(mod_id);",
+    // Namespaces of result and ingredient ids; tags count under their own
+    // namespace, so `#forge:ingots` is `forge`
+    "ALTER TABLE recipes ADD COLUMN result_namespace TEXT;
+    UPDATE recipes SET result_namespace = substr(result_item, 1, instr(result_item, ':') - 1)
+        WHERE instr(result_item, ':') > 0;
+    CREATE INDEX idx_recipes_result_namespace ON recipes(result_namespace);
+    ALTER TABLE recipe_ingredients ADD COLUMN namespace TEXT NOT NULL DEFAULT '';
+    UPDATE recipe_ingredients SET namespace = substr(ltrim(item, '#'), 1, instr(ltrim(item, '#'), ':') - 1);
+    CREATE INDEX idx_ingredients_namespace ON recipe_ingredients(namespace);",
+    // Shaped patterns with rows joined by `/`, e.g. `###/ | / | `
+    "ALTER TABLE recipes ADD COLUMN pattern TEXT;
+    ALTER TABLE recipes ADD COLUMN grid_width INTEGER;
+    ALTER TABLE recipes ADD COLUMN grid_height INTEGER;
+    UPDATE recipes SET
+        pattern = (SELECT group_concat(value, '/') FROM json_each(raw_json, '$.pattern')),
+        grid_width = (SELECT MAX(length(value)) FROM json_each(raw_json, '$.pattern')),
+        grid_height = json_array_length(raw_json, '$.pattern')
+        WHERE json_valid(raw_json) AND json_type(raw_json, '$.pattern') = 'array';",
+    // File the reference came from; rows recorded before this are NULL
+    "ALTER TABLE item_refs ADD COLUMN path TEXT;",
+    // What each finished session indexed, kept for diffing sessions. Not
+    // replaced by re-extraction. `mod_id` only links rows within one session.
+    "CREATE TABLE session_mods (
+        session_id INTEGER NOT NULL REFERENCES extraction_sessions(id) ON DELETE CASCADE,
+        mod_id INTEGER NOT NULL,
+        mod_key TEXT NOT NULL,
+        name TEXT NOT NULL,
+        version TEXT
+    );
+    CREATE INDEX idx_session_mods_session ON session_mods(session_id);
+    CREATE TABLE session_recipes (
+        session_id INTEGER NOT NULL REFERENCES extraction_sessions(id) ON DELETE CASCADE,
+        mod_id INTEGER NOT NULL,
+        recipe_id TEXT NOT NULL,
+        raw_json TEXT NOT NULL
+    );
+    CREATE INDEX idx_session_recipes_session ON session_recipes(session_id);
+    CREATE TABLE session_tags (
+        session_id INTEGER NOT NULL REFERENCES extraction_sessions(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        entry TEXT NOT NULL
+    );
+    CREATE INDEX idx_session_tags_session ON session_tags(session_id);",
+    "ALTER TABLE session_mods ADD COLUMN display_name TEXT;",
+    "CREATE INDEX idx_session_recipes_recipe ON session_recipes(recipe_id);",
+    // Jar or data pack the error came from; rows recorded before this are NULL
+    "ALTER TABLE extraction_errors ADD COLUMN path TEXT;
+    CREATE INDEX idx_extraction_errors_path ON extraction_errors(path);",
+    // Jar the lang file came from, so removing a mod drops its names
+    "ALTER TABLE item_names ADD COLUMN mod_id INTEGER REFERENCES mods(id) ON DELETE CASCADE;
+    CREATE INDEX idx_item_names_mod ON item_names(mod_id);",
+    // What the user hid, by mod key and recipe id, so re-extracting keeps it.
+    // A mod's key is its loader mod id, or its jar name without the version
+    "ALTER TABLE mods ADD COLUMN mod_key TEXT NOT NULL DEFAULT '';
+    UPDATE mods SET mod_key = jar_mod_key(name);
+    CREATE TABLE hidden_mods (mod_key TEXT PRIMARY KEY);
+    CREATE TABLE hidden_recipes (
+        mod_key TEXT NOT NULL,
+        recipe_id TEXT NOT NULL,
+        PRIMARY KEY (mod_key, recipe_id)
+    );
+    INSERT OR IGNORE INTO hidden_mods SELECT COALESCE(loader_mod_id, mod_key) FROM mods WHERE hidden = 1;
+    INSERT OR IGNORE INTO hidden_recipes
+        SELECT COALESCE(m.loader_mod_id, m.mod_key), r.recipe_id FROM recipes r JOIN mods m ON r.mod_id = m.id
+        WHERE r.hidden = 1 AND r.recipe_id != '';",
+    // Rows left behind by replaced recipes before recursive triggers were on
+    "DELETE FROM recipes_fts WHERE rowid NOT IN (SELECT id FROM recipes);",
+    // Like item_tags: fluid ids or nested tags written as `#namespace:path`
+    "CREATE TABLE fluid_tags (
+        id INTEGER PRIMARY KEY,
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        entry TEXT NOT NULL
+    );
+    CREATE INDEX idx_fluid_tags_tag ON fluid_tags(tag);
+    CREATE INDEX idx_fluid_tags_mod ON fluid_tags(mod_id);",
+    // Tag files marked `"replace": true`; see apply_tag_replacements
+    "CREATE TABLE tag_replacements (
+        mod_id INTEGER NOT NULL REFERENCES mods(id) ON DELETE CASCADE,
+        kind TEXT NOT NULL CHECK (kind IN ('item', 'fluid')),
+        tag TEXT NOT NULL,
+        PRIMARY KEY (mod_id, kind, tag)
+    );",
+    // Patterns shrunk to the cells they use, so every row is grid_width wide
+    "UPDATE recipes SET
+        pattern = (SELECT group_concat(value, '/') FROM json_each(shrunk_pattern(raw_json))),
+        grid_width = (SELECT MAX(length(value)) FROM json_each(shrunk_pattern(raw_json))),
+        grid_height = NULLIF(json_array_length(shrunk_pattern(raw_json)), 0)
+        WHERE pattern IS NOT NULL;",
+    // Errors recorded before they had a path can never be replaced by an
+    // append run; extracting the jar again records them with one
+    "DELETE FROM extraction_errors WHERE path IS NULL;",
+];
+
+/// The key hidden choices are stored under for a row of `mods m`.
+const HIDE_KEY: &str = "COALESCE(m.loader_mod_id, m.mod_key)";
+
+/// Recipes of `recipes r` that are neither hidden nor from a hidden mod.
+const VISIBLE_RECIPE: &str = "r.hidden = 0 AND r.mod_id NOT IN (SELECT id FROM mods WHERE hidden = 1)";
+
+/// Sets the `hidden` columns from the stored choices, for rows extracted
+/// since they were made.
+const APPLY_HIDDEN: &str = "
+    UPDATE mods SET hidden = COALESCE(loader_mod_id, mod_key) IN (SELECT mod_key FROM hidden_mods);
+    UPDATE recipes SET hidden = 0 WHERE hidden = 1;
+    UPDATE recipes SET hidden = 1 WHERE id IN (
+        SELECT r.id FROM hidden_recipes h
+        JOIN mods m ON h.mod_key = COALESCE(m.loader_mod_id, m.mod_key)
+        JOIN recipes r ON r.mod_id = m.id AND r.recipe_id = h.recipe_id
+    );";
+
+/// An ingredient nothing is known to produce: no recipe makes it, no loot
+/// table drops it and no trade sells it. For a tag, none of its items.
+#[derive(Serialize)]
+pub struct UnobtainableItem {
+    pub item: String,
+    /// Owner of the item's namespace
+    pub mod_name: Option<String>,
+    /// Recipes using it
+    pub consumer_count: i64,
+}
+
+/// A recipe output nothing uses: no recipe or trade takes it and no tag
+/// lists it.
+#[derive(Serialize)]
+pub struct DeadEndOutput {
+    pub item: String,
+    /// Owner of the item's namespace
+    pub mod_name: Option<String>,
+    /// Recipes making it
+    pub producer_count: i64,
+}
+
+/// How much the pack's recipes ask for an item or tag.
+#[derive(Serialize)]
+pub struct IngredientUsage {
+    /// Item id, or tag id starting with `#`
+    pub item: String,
+    pub recipe_count: i64,
+    /// Items asked for across one craft of each recipe
+    pub total_count: i64,
+    /// Recipes using it per mod shipping them, most first
+    pub mods: Vec<ModFacet>,
+}
+
+/// An ingredient tag that no scanned jar defines, or whose items all come
+/// from mods not in the pack. Recipes using it cannot be crafted.
+#[derive(Serialize)]
+pub struct BrokenTag {
+    /// Tag id starting with `#`
+    pub tag: String,
+    /// Owner of the tag's namespace
+    pub mod_name: Option<String>,
+    /// No tag file was found, rather than one that resolves to nothing
+    pub missing: bool,
+    /// Recipes using it
+    pub consumer_count: i64,
+}
+
+/// Recipes of one mod that name items no scanned jar has.
+#[derive(Serialize)]
+pub struct DanglingReferences {
+    pub mod_id: i64,
+    pub mod_name: String,
+    pub references: Vec<DanglingReference>,
+}
+
+#[derive(Serialize)]
+pub struct DanglingReference {
+    /// Row id of the recipe
+    pub recipe: i64,
+    pub recipe_id: String,
+    pub item: String,
+    /// The item is the recipe's result rather than an ingredient
+    pub output: bool,
+    pub reason: DanglingReason,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DanglingReason {
+    /// No scanned jar owns the item's namespace
+    MissingMod,
+    /// The namespace's lang files name other items but not this one, often a
+    /// typo or an item the mod has since removed
+    UnknownItem,
+}
+
+/// Items some recipe makes, some loot table drops or some trade sells.
+/// `loot_table` is `items::LOOT_TABLE`.
+const OBTAINABLE_CTE: &str = "obtainable(item) AS (
+        SELECT result_item FROM recipes WHERE result_item IS NOT NULL
+        UNION SELECT item FROM item_refs WHERE source = 'loot_table'
+        UNION SELECT result_item FROM trades
+    )";
+
+/// An item id seen anywhere in the scanned data.
+#[derive(Serialize)]
+pub struct Item {
+    pub item: String,
+    pub namespace: String,
+    pub path: String,
+    /// English name from the lang files, when one was found
+    pub display_name: Option<String>,
+    /// Owner of the item's namespace
+    pub mod_id: Option<i64>,
+    pub mod_name: Option<String>,
+}
+
+const ITEM_SELECT: &str =
+    "SELECT i.item, i.namespace, i.path, i.display_name, i.mod_id, m.name
+     FROM items i
+     LEFT JOIN mods m ON i.mod_id = m.id";
+
+/// Items of `ITEM_SELECT` not owned by a hidden mod.
+const VISIBLE_ITEM: &str = "COALESCE(m.hidden, 0) = 0";
+
+/// Locale used for item display names.
+const DISPLAY_LOCALE: &str = "en_us";
+
+/// A data namespace and the mod that owns it.
+#[derive(Serialize)]
+pub struct NamespaceOwner {
+    pub namespace: String,
+    pub mod_id: i64,
+    pub mod_name: String,
+    /// Recipes in the namespace, including those shipped by other jars
+    pub recipe_count: i64,
+}
+
+/// An extracted jar and what its loader metadata declares, when it has any.
+#[derive(Serialize, Default)]
+pub struct ModInfo {
+    pub id: i64,
+    /// Jar file name
+    pub name: String,
+    pub path: String,
+    /// The jar this one was bundled in (Jar-in-Jar), if any
+    pub parent_mod_id: Option<i64>,
+    /// The mod id from mods.toml or fabric.mod.json, e.g. `create`
+    pub loader_mod_id: Option<String>,
+    pub loader: Option<String>,
+    pub display_name: Option<String>,
+    pub version: Option<String>,
+    pub environment: Option<String>,
+    /// Declared Minecraft version range, e.g. `[1.20.1,1.21)` or `>=1.21`
+    pub minecraft_versions: Option<String>,
+    /// Logo path inside the jar; `get_mod_icon` caches it to disk
+    pub icon: Option<String>,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub issue_tracker: Option<String>,
+    pub authors: Option<String>,
+    /// Hex digests of the jar file, shared by every mod it declares
+    pub sha1: Option<String>,
+    pub sha512: Option<String>,
+    /// Hidden mods are left out of recipe lists and searches
+    pub hidden: bool,
+    pub recipe_count: i64,
+    /// Errors from the jar's most recent extraction, if it has been extracted
+    pub error_count: Option<i64>,
+    pub dependencies: Vec<ModDependency>,
+}
+
+/// What one mod contributes, for its detail view.
+#[derive(Serialize)]
+pub struct ModDetail {
+    pub info: ModInfo,
+    pub recipe_count: i64,
+    /// Recipes per canonical type, most common first
+    pub recipe_types: Vec<CategoryCount>,
+    /// Data namespaces the mod owns
+    pub namespaces: Vec<String>,
+    /// Mods from jars bundled in this one
+    pub nested: Vec<ModInfo>,
+}
+
+/// Figures for one mod's report card.
+#[derive(Serialize)]
+pub struct ModStats {
+    pub mod_id: i64,
+    pub name: String,
+    /// Recipes per canonical type, most common first
+    pub recipe_types: Vec<CategoryCount>,
+    pub coverage: ParserCoverage,
+    /// Distinct items its recipes make in namespaces it owns
+    pub outputs_added: i64,
+    /// Distinct item tags it adds entries to
+    pub tags_contributed: i64,
+    /// Its recipes replacing one another jar ships, or a vanilla recipe
+    pub overrides: i64,
+    /// Errors in its last extraction run
+    pub error_count: Option<i64>,
+    /// Errors per data file attempted (recipes stored plus errors)
+    pub error_rate: Option<f64>,
+}
+
+/// Column list shared by every query that returns `ModInfo` rows.
+const MOD_SELECT: &str =
+    "SELECT id, name, path, parent_mod_id, loader_mod_id, loader, display_name, version, environment,
+            minecraft_versions, icon, license, homepage, issue_tracker, authors, sha1, sha512, hidden,
+            (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = mods.id),
+            (SELECT error_count FROM jar_runs j WHERE j.jar_path = mods.path ORDER BY j.id DESC LIMIT 1)
+     FROM mods";
+
+/// Food, stack size and durability an item is given by a data file.
+#[derive(Serialize)]
+pub struct ItemComponentEntry {
+    pub item: String,
+    pub mod_name: String,
+    pub path: String,
+    pub nutrition: Option<i32>,
+    pub saturation: Option<f64>,
+    pub max_stack_size: Option<i32>,
+    pub max_damage: Option<i32>,
+}
+
+/// A setting found while scanning the instance's config directory.
+#[derive(Serialize)]
+pub struct ConfigEntry {
+    pub file: String,
+    pub key: String,
+    pub value: String,
+}
+
+/// A recipe matched by full-text search, with the matching part of its JSON.
+#[derive(Serialize)]
+pub struct RecipeSearchHit {
+    pub recipe: Recipe,
+    /// Excerpt with matched terms wrapped in `**`
+    pub snippet: String,
+}
+
+/// A villager-style trade offer read from a data file.
+#[derive(Serialize)]
+pub struct Trade {
+    pub id: i64,
+    pub mod_name: String,
+    /// Data file the offer came from, e.g. `easy_villagers:trades/farmer`
+    pub source: String,
+    pub profession: Option<String>,
+    pub level: Option<i32>,
+    pub costs: Vec<IngredientCount>,
+    pub result_item: String,
+    pub result_count: i32,
+}
+
+/// Every known way to obtain an item.
+#[derive(Serialize)]
+pub struct ItemSources {
+    pub recipes: Vec<Recipe>,
+    pub trades: Vec<Trade>,
+    pub loot_tables: Vec<LootSource>,
+    /// No recipe, loot table or trade gives it, so a player can never get it
+    pub blocker: bool,
+}
+
+/// A loot table that can drop an item.
+#[derive(Serialize)]
+pub struct LootSource {
+    pub mod_name: String,
+    /// Path of the loot table in the jar; unknown for data extracted by
+    /// older versions
+    pub path: Option<String>,
+}
+
+/// A worldgen file (feature, structure, biome modifier, ...) and the ids it references.
+#[derive(Serialize)]
+pub struct WorldgenEntry {
+    pub id: i64,
+    pub mod_name: String,
+    pub kind: String,
+    pub entry_id: String,
+    pub references: Vec<String>,
+    pub raw_json: String,
+}
+
+/// Column list shared by every query that returns `Enchantment` rows.
+const ENCHANTMENT_SELECT: &str =
+    "SELECT e.enchantment_id, m.name, e.description, e.supported_items, e.max_level, e.weight,
+            e.anvil_cost, e.min_cost_base, e.min_cost_per_level, e.max_cost_base,
+            e.max_cost_per_level, e.effects, e.raw_json
+     FROM enchantments e
+     JOIN mods m ON e.mod_id = m.id AND m.hidden = 0";
+
+#[derive(Serialize)]
+pub struct Enchantment {
+    pub enchantment_id: String,
+    pub mod_name: String,
+    pub description: Option<String>,
+    pub supported_items: Vec<String>,
+    pub max_level: Option<i32>,
+    pub weight: Option<i32>,
+    pub anvil_cost: Option<i32>,
+    pub min_cost_base: Option<i32>,
+    pub min_cost_per_level: Option<i32>,
+    pub max_cost_base: Option<i32>,
+    pub max_cost_per_level: Option<i32>,
+    pub effects: Vec<String>,
     pub raw_json: String,
 }
 
+/// Column list shared by every query that returns `Trade` rows.
+const TRADE_SELECT: &str =
+    "SELECT t.id, m.name, t.source, t.profession, t.level, t.result_item, t.result_count
+     FROM trades t
+     JOIN mods m ON t.mod_id = m.id AND m.hidden = 0";
+
+/// Namespace of an item or tag id: `minecraft` for `minecraft:stick`,
+/// `forge` for `#forge:ingots`.
+fn id_namespace(id: &str) -> Option<&str> {
+    id.trim_start_matches('#').split_once(':').map(|(namespace, _)| namespace)
+}
+
+/// Pushes `values` onto `params` and returns their placeholders, for an
+/// `IN (...)` list.
+fn placeholders(values: &[String], params: &mut Vec<Value>) -> String {
+    values
+        .iter()
+        .map(|value| {
+            params.push(Value::from(value.clone()));
+            format!("?{}", params.len())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Condition for crafting table recipes that fit a `size` by `size` grid:
+/// shaped ones by pattern, shapeless ones by slots used.
+fn grid_condition(size: i64, params: &mut Vec<Value>) -> String {
+    params.push(Value::from(size));
+    let n = params.len();
+    format!(
+        "((r.canonical_type = 'minecraft:crafting_shaped' AND r.grid_width <= ?{n} AND r.grid_height <= ?{n})
+          OR (r.canonical_type = 'minecraft:crafting_shapeless'
+              AND (SELECT SUM(count) FROM recipe_ingredients i WHERE i.recipe_id = r.id) <= ?{n} * ?{n}))"
+    )
+}
+
+/// Every tag, written `#namespace:path`, holding an entry that matches
+/// `condition` (over `entry`) directly or through nested tags.
+fn tags_containing_sql(condition: &str) -> String {
+    format!(
+        "WITH RECURSIVE containing(tag) AS (
+             SELECT '#' || tag FROM item_tags WHERE {}
+             UNION
+             SELECT '#' || t.tag FROM item_tags t JOIN containing c ON t.entry = c.tag
+         )
+         SELECT tag FROM containing",
+        condition
+    )
+}
+
+/// Condition over `recipes r` for recipes using a matching ingredient. With
+/// `expand_tags`, tag ingredients that contain a matching item count too.
+fn ingredient_condition(item: &str, mode: MatchMode, expand_tags: bool, params: &mut Vec<Value>) -> String {
+    let mut condition = matching::condition("item", item, mode, params);
+    if expand_tags {
+        let entry = matching::condition("entry", item, mode, params);
+        condition = format!("{} OR item IN ({})", condition, tags_containing_sql(&entry));
+    }
+    format!("r.id IN (SELECT recipe_id FROM recipe_ingredients WHERE {})", condition)
+}
+
+/// How long a connection waits for a lock held by another before failing.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Searches kept in the history; older ones are dropped.
+const SEARCH_HISTORY_LIMIT: i64 = 500;
+
+/// Sessions whose snapshots are kept. Each holds every recipe's JSON, so
+/// older ones are dropped.
+const SNAPSHOT_LIMIT: i64 = 10;
+
+/// A jar taking longer than this to extract counts as a stalled run.
+const JAR_STALL_THRESHOLD: Duration = Duration::from_secs(120);
+
+/// Consecutive crashed or stalled runs before a jar is quarantined.
+const QUARANTINE_AFTER_FAILURES: i64 = 2;
+
+/// Recipes a mod contributed in one past extraction.
+#[derive(Serialize)]
+pub struct RecipeTrendPoint {
+    pub started_at: String,
+    pub jar_path: String,
+    pub recipe_count: i64,
+}
+
+/// One run of `extract_all_recipes`. `status` is `running`, `finished`,
+/// `failed` when the run stopped on an error, or `crashed` when the app
+/// closed before the run ended.
+#[derive(Serialize)]
+pub struct ExtractionSession {
+    pub id: i64,
+    pub started_at: String,
+    pub folder: Option<String>,
+    pub jar_count: i64,
+    pub duration_ms: Option<i64>,
+    pub error_count: Option<i64>,
+    pub recipe_count: Option<i64>,
+    pub status: String,
+}
+
+/// Size and contents of the database file, for the maintenance view.
+#[derive(Serialize)]
+pub struct DbStats {
+    /// Bytes on disk, including the write-ahead log
+    pub file_size: u64,
+    pub tables: Vec<TableStats>,
+    pub indexes: Vec<IndexStats>,
+    pub last_extraction: Option<ExtractionSession>,
+}
+
+#[derive(Serialize)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: i64,
+    pub bytes: i64,
+}
+
+#[derive(Serialize)]
+pub struct IndexStats {
+    pub name: String,
+    pub table: String,
+    pub bytes: i64,
+}
+
+#[derive(Serialize)]
+pub struct QuarantinedJar {
+    pub jar_path: String,
+    pub reason: String,
+    pub quarantined_at: String,
+}
+
+/// A data-driven machine definition and how many recipes target it.
+#[derive(Serialize)]
+pub struct Machine {
+    pub machine_id: String,
+    pub name: Option<String>,
+    pub mod_name: String,
+    pub recipe_count: i64,
+}
+
+/// A recipe that consumes or produces fluids/chemicals, linked to the
+/// recipes that consume what it produces.
+#[derive(Serialize)]
+pub struct FluidConversion {
+    pub recipe: Recipe,
+    pub fluid_inputs: Vec<FluidAmount>,
+    pub fluid_outputs: Vec<FluidAmount>,
+    pub feeds_into: Vec<i64>,
+}
+
 #[derive(Serialize)]
 pub struct ExtractionResult {
     pub mods_processed: usize,
     pub recipes_extracted: usize,
     pub errors: Vec<String>,
+    pub warnings: Vec<String>,
 }
 
 impl Database {
@@ -34,17 +1293,80 @@ impl Database {
             std::fs::create_dir_all(parent).ok();
         }
 
-        let conn = Connection::open(db_path)?;
-        let db = Database {
+        let conn = Connection::open(&db_path)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        // WAL lets read connections see committed data while a write is in progress
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        Self::migrate(&conn)?;
+        Ok(Database {
             conn: Mutex::new(conn),
-        };
-        db.init_schema()?;
-        Ok(db)
+            reader: Mutex::new(open_read_only(&db_path)?),
+            pinned_filter: Mutex::new(PinnedFilter::default()),
+            path: db_path,
+        })
     }
 
-    fn init_schema(&self) -> SqliteResult<()> {
-        let conn = self.conn.lock().unwrap();
+    /// Opens a separate read-only handle to the same database, carrying
+    /// over the pinned filter, so independent queries can run in parallel.
+    pub fn open_reader(&self) -> SqliteResult<Database> {
+        Ok(Database {
+            conn: Mutex::new(open_read_only(&self.path)?),
+            reader: Mutex::new(open_read_only(&self.path)?),
+            pinned_filter: Mutex::new(self.get_pinned_filter()),
+            path: self.path.clone(),
+        })
+    }
+
+    /// Brings the schema up to date and turns on foreign keys. They are off
+    /// while migrating, since rebuilding a parent table would otherwise
+    /// cascade into its children. Recursive triggers make rows deleted by
+    /// `INSERT OR REPLACE` fire delete triggers, which keeps the full-text
+    /// index in step with `recipes`.
+    fn migrate(conn: &Connection) -> SqliteResult<()> {
+        conn.create_scalar_function(
+            "jar_mod_key",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| Ok(scanner::mod_key(&ctx.get::<String>(0)?)),
+        )?;
+        conn.create_scalar_function(
+            "shrunk_pattern",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| {
+                let value: serde_json::Value = serde_json::from_str(&ctx.get::<String>(0)?).unwrap_or_default();
+                Ok(serde_json::Value::from(recipe_parser::extract_pattern(&value)).to_string())
+            },
+        )?;
+        conn.pragma_update(None, "foreign_keys", false)?;
+        Self::init_schema(conn)?;
+        conn.pragma_update(None, "recursive_triggers", true)?;
+        conn.pragma_update(None, "foreign_keys", true)
+    }
+
+    /// Copies the database to `path` with SQLite's online backup API, which
+    /// gives a consistent copy even while an extraction is writing.
+    pub fn backup_to(&self, path: &Path) -> SqliteResult<()> {
+        let conn = self.reader.lock().unwrap();
+        conn.backup(DatabaseName::Main, path, None)
+    }
+
+    /// Whether SQLite finds the database file intact, e.g. after copying it.
+    pub fn quick_check(&self) -> SqliteResult<bool> {
+        let conn = self.reader.lock().unwrap();
+        let result: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        Ok(result == "ok")
+    }
+
+    /// Replaces the contents with a backup made by `backup_to`, upgrading its
+    /// schema if it came from an older version.
+    pub fn restore_from(&self, path: &Path) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        Self::migrate(&conn)
+    }
 
+    fn init_schema(conn: &Connection) -> SqliteResult<()> {
         conn.execute_batch(
             "
             CREATE TABLE IF NOT EXISTS mods (
@@ -78,160 +1400,3023 @@ impl Database {
             "
         )?;
 
+        let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            conn.execute_batch(migration)?;
+            conn.pragma_update(None, "user_version", version + 1)?;
+        }
+
         Ok(())
     }
 
-    pub fn clear_all(&self) -> SqliteResult<()> {
-        let conn = self.conn.lock().unwrap();
-        conn.execute_batch(
-            "
-            DELETE FROM recipe_ingredients;
-            DELETE FROM recipes;
-            DELETE FROM mods;
-            "
+    /// Where a full extraction builds its data before `replace_extracted`
+    /// swaps it in.
+    pub fn staging_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.staging", self.path.display()))
+    }
+
+    /// Replaces everything extracted with the contents of the database at
+    /// `staging`, in one transaction, so readers see either the old data or
+    /// the new and never a half-filled database. Notes, collections, settings
+    /// and session history are kept, and hidden mods and recipes stay hidden.
+    pub fn replace_extracted(&self, staging: &Path) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        conn.execute("ATTACH DATABASE ?1 AS staging", [staging.to_string_lossy()])?;
+        let result = conn.transaction().and_then(|tx| {
+            for table in EXTRACTED_TABLES {
+                tx.execute(&format!("DELETE FROM main.{}", table), [])?;
+            }
+            for table in EXTRACTED_TABLES.iter().rev() {
+                tx.execute(&format!("INSERT INTO main.{0} SELECT * FROM staging.{0}", table), [])?;
+            }
+            tx.execute_batch(APPLY_HIDDEN)?;
+            tx.commit()
+        });
+        conn.execute("DETACH DATABASE staging", [])?;
+        result
+    }
+
+    /// Deletes a mod and, through cascading foreign keys, everything
+    /// extracted from it and from jars bundled inside it. Errors recorded for
+    /// its jar go too once no mod from the jar is left. Returns false if
+    /// there was no such mod.
+    pub fn remove_mod(&self, mod_id: i64) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let path: Option<String> = tx
+            .query_row("SELECT path FROM mods WHERE id = ?1", [mod_id], |row| row.get(0))
+            .optional()?;
+        let Some(path) = path else {
+            return Ok(false);
+        };
+        tx.execute("DELETE FROM mods WHERE id = ?1", [mod_id])?;
+        tx.execute(
+            "DELETE FROM extraction_errors WHERE path = ?1 AND NOT EXISTS (SELECT 1 FROM mods WHERE path = ?1)",
+            [&path],
         )?;
-        Ok(())
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Deletes the mods extracted from the jar at `path`, and those from jars
+    /// nested in it, so the jar can be extracted again without duplicating
+    /// its rows. Returns false if the jar was not indexed.
+    pub fn remove_mods_at(&self, path: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM mods WHERE path = ?1", [path])? > 0)
     }
 
-    pub fn insert_mod(&self, name: &str, path: &str) -> SqliteResult<i64> {
+    pub fn insert_mod(&self, name: &str, path: &str, parent_mod_id: Option<i64>) -> SqliteResult<i64> {
         let conn = self.conn.lock().unwrap();
         let now = chrono_lite_now();
 
         conn.execute(
-            "INSERT OR REPLACE INTO mods (name, path, scanned_at) VALUES (?1, ?2, ?3)",
-            [name, path, &now],
+            "INSERT OR REPLACE INTO mods (name, path, scanned_at, parent_mod_id, mod_key) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![name, path, now, parent_mod_id, scanner::mod_key(name)],
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
-    pub fn insert_recipe(
-        &self,
-        mod_id: i64,
-        path: &str,
-        recipe_type: &str,
-        result_item: Option<&str>,
-        result_count: Option<i32>,
-        raw_json: &str,
-        ingredients: &[String],
-    ) -> SqliteResult<i64> {
+    /// Records the digests on every mod row extracted from `path`.
+    pub fn set_jar_hashes(&self, path: &str, hashes: &JarHashes) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
-
         conn.execute(
-            "INSERT OR REPLACE INTO recipes (mod_id, path, recipe_type, result_item, result_count, raw_json)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            rusqlite::params![mod_id, path, recipe_type, result_item, result_count, raw_json],
+            "UPDATE mods SET sha1 = ?2, sha512 = ?3 WHERE path = ?1",
+            rusqlite::params![path, hashes.sha1, hashes.sha512],
         )?;
+        Ok(())
+    }
 
-        let recipe_id = conn.last_insert_rowid();
-
-        // Clear existing ingredients for this recipe (in case of replace)
-        conn.execute(
-            "DELETE FROM recipe_ingredients WHERE recipe_id = ?1",
-            [recipe_id],
+    pub fn set_mod_metadata(&self, mod_id: i64, metadata: &ModMetadata) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE mods SET loader_mod_id = ?2, loader = ?3, display_name = ?4, version = ?5, environment = ?6,
+                 minecraft_versions = ?7, icon = ?8, license = ?9, homepage = ?10, issue_tracker = ?11,
+                 authors = ?12
+             WHERE id = ?1",
+            rusqlite::params![
+                mod_id,
+                metadata.mod_id,
+                metadata.loader,
+                metadata.display_name,
+                metadata.version,
+                metadata.environment,
+                metadata.minecraft_versions(),
+                metadata.icon,
+                metadata.license,
+                metadata.homepage,
+                metadata.issue_tracker,
+                metadata.authors
+            ],
         )?;
-
+        tx.execute("DELETE FROM mod_dependencies WHERE mod_id = ?1", [mod_id])?;
+        for dep in &metadata.dependencies {
+            tx.execute(
+                "INSERT INTO mod_dependencies (mod_id, dependency, version_range, required) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![mod_id, dep.mod_id, dep.version_range, dep.required],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Lists extracted mods, optionally only those for one loader.
+    pub fn list_mods(&self, loader: Option<&str>, include_hidden: bool) -> SqliteResult<Vec<ModInfo>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE (?1 IS NULL OR loader = ?1) AND (?2 OR hidden = 0)
+             ORDER BY COALESCE(display_name, name) COLLATE NOCASE",
+            MOD_SELECT
+        ))?;
+        Self::collect_mods(&conn, &mut stmt, &[&loader, &include_hidden])
+    }
+
+    /// Hides or shows a mod, and every other version of it, now and after
+    /// later extractions. Returns false if there is no such mod.
+    pub fn set_mod_hidden(&self, mod_id: i64, hidden: bool) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let key: Option<String> = tx
+            .query_row(&format!("SELECT {} FROM mods m WHERE id = ?1", HIDE_KEY), [mod_id], |row| row.get(0))
+            .optional()?;
+        let Some(key) = key else {
+            return Ok(false);
+        };
+        match hidden {
+            true => tx.execute("INSERT OR IGNORE INTO hidden_mods (mod_key) VALUES (?1)", [&key])?,
+            false => tx.execute("DELETE FROM hidden_mods WHERE mod_key = ?1", [&key])?,
+        };
+        tx.execute(
+            &format!("UPDATE mods SET hidden = ?2 WHERE id IN (SELECT id FROM mods m WHERE {} = ?1)", HIDE_KEY),
+            rusqlite::params![key, hidden],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Hides or shows a recipe, now and after later extractions. Returns
+    /// false if there is no such recipe.
+    pub fn set_recipe_hidden(&self, recipe_id: i64, hidden: bool) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let ids: Option<(String, String)> = tx
+            .query_row(
+                &format!("SELECT {}, r.recipe_id FROM recipes r JOIN mods m ON r.mod_id = m.id WHERE r.id = ?1", HIDE_KEY),
+                [recipe_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((key, id)) = ids else {
+            return Ok(false);
+        };
+        // Recipes without an id can't be told apart once re-extracted
+        match (hidden, id.is_empty()) {
+            (_, true) => 0,
+            (true, false) => tx.execute("INSERT OR IGNORE INTO hidden_recipes VALUES (?1, ?2)", [&key, &id])?,
+            (false, false) => tx.execute("DELETE FROM hidden_recipes WHERE mod_key = ?1 AND recipe_id = ?2", [&key, &id])?,
+        };
+        tx.execute("UPDATE recipes SET hidden = ?2 WHERE id = ?1", rusqlite::params![recipe_id, hidden])?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// The key a mod keeps across versions and workspaces: its loader mod id,
+    /// or its jar name without the version.
+    pub fn mod_key_of(&self, mod_id: i64) -> SqliteResult<Option<String>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row(&format!("SELECT {} FROM mods m WHERE id = ?1", HIDE_KEY), [mod_id], |row| row.get(0))
+            .optional()
+    }
+
+    /// The mod with this key, as `mod_key_of` gives it.
+    pub fn find_mod_by_key(&self, key: &str) -> SqliteResult<Option<i64>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row(
+            &format!("SELECT id FROM mods m WHERE {} = ?1 ORDER BY parent_mod_id IS NOT NULL, id LIMIT 1", HIDE_KEY),
+            [key],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Re-applies hidden mods and recipes to rows extracted since they were
+    /// hidden.
+    pub fn apply_hidden(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(APPLY_HIDDEN)
+    }
+
+    /// Mods from the jar with this SHA-1 or SHA-512 digest.
+    pub fn find_mods_by_hash(&self, hash: &str) -> SqliteResult<Vec<ModInfo>> {
+        let conn = self.reader.lock().unwrap();
+        let hash = hash.trim().to_lowercase();
+        let mut stmt = conn.prepare(&format!("{} WHERE sha1 = ?1 OR sha512 = ?1 ORDER BY id", MOD_SELECT))?;
+        Self::collect_mods(&conn, &mut stmt, &[&hash])
+    }
+
+    pub fn get_mod(&self, mod_id: i64) -> SqliteResult<Option<ModInfo>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} WHERE id = ?1", MOD_SELECT))?;
+        Ok(Self::collect_mods(&conn, &mut stmt, &[&mod_id])?.pop())
+    }
+
+    pub fn get_mod_detail(&self, mod_id: i64) -> SqliteResult<Option<ModDetail>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} WHERE id = ?1", MOD_SELECT))?;
+        let Some(info) = Self::collect_mods(&conn, &mut stmt, &[&mod_id])?.pop() else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT canonical_type, COUNT(*) FROM recipes WHERE mod_id = ?1
+             GROUP BY canonical_type ORDER BY COUNT(*) DESC, canonical_type",
+        )?;
+        let recipe_types = stmt
+            .query_map([mod_id], |row| Ok(CategoryCount { name: row.get(0)?, count: row.get(1)? }))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut stmt = conn.prepare("SELECT namespace FROM namespaces WHERE mod_id = ?1 ORDER BY namespace")?;
+        let namespaces = stmt.query_map([mod_id], |row| row.get(0))?.collect::<SqliteResult<_>>()?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE parent_mod_id = ?1 ORDER BY COALESCE(display_name, name) COLLATE NOCASE",
+            MOD_SELECT
+        ))?;
+        let nested = Self::collect_mods(&conn, &mut stmt, &[&mod_id])?;
+
+        Ok(Some(ModDetail {
+            info,
+            recipe_count: recipe_types.iter().map(|t| t.count).sum(),
+            recipe_types,
+            namespaces,
+            nested,
+        }))
+    }
+
+    /// Report card figures for one mod, or for every top-level and nested
+    /// mod when `mod_id` is `None`, by name.
+    pub fn mod_stats(&self, mod_id: Option<i64>) -> SqliteResult<Vec<ModStats>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT canonical_type, COUNT(*) FROM recipes WHERE mod_id = ?1
+             GROUP BY canonical_type ORDER BY COUNT(*) DESC, canonical_type",
+        )?;
+        let mut type_counts = |mod_id: i64| {
+            stmt.query_map([mod_id], |row| Ok(CategoryCount { name: row.get(0)?, count: row.get(1)? }))?
+                .collect::<SqliteResult<Vec<_>>>()
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.name,
+                    (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = m.id),
+                    (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = m.id AND r.result_item IS NOT NULL),
+                    (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = m.id
+                       AND EXISTS (SELECT 1 FROM recipe_ingredients i WHERE i.recipe_id = r.id)),
+                    (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = m.id
+                       AND EXISTS (SELECT 1 FROM recipe_fluids f WHERE f.recipe_id = r.id)),
+                    (SELECT COUNT(DISTINCT r.result_item) FROM recipes r
+                     JOIN namespaces ns ON ns.namespace = r.result_namespace AND ns.mod_id = m.id
+                     WHERE r.mod_id = m.id),
+                    (SELECT COUNT(DISTINCT tag) FROM item_tags t WHERE t.mod_id = m.id),
+                    (SELECT COUNT(*) FROM recipes r WHERE r.mod_id = m.id
+                       AND r.namespace NOT IN (SELECT namespace FROM namespaces WHERE mod_id = m.id)
+                       AND (r.namespace = 'minecraft'
+                            OR EXISTS (SELECT 1 FROM recipes o
+                                       WHERE o.recipe_id = r.recipe_id AND r.recipe_id != '' AND o.mod_id != m.id))),
+                    (SELECT error_count FROM jar_runs j WHERE j.jar_path = m.path ORDER BY j.id DESC LIMIT 1)
+             FROM mods m
+             WHERE ?1 IS NULL OR m.id = ?1
+             ORDER BY m.name COLLATE NOCASE",
+        )?;
+        let rows = stmt.query_map([mod_id], |row| {
+            let recipes: i64 = row.get(2)?;
+            let error_count: Option<i64> = row.get(9)?;
+            Ok(ModStats {
+                mod_id: row.get(0)?,
+                name: row.get(1)?,
+                recipe_types: Vec::new(),
+                coverage: ParserCoverage {
+                    recipes,
+                    with_result: row.get(3)?,
+                    with_ingredients: row.get(4)?,
+                    with_fluids: row.get(5)?,
+                },
+                outputs_added: row.get(6)?,
+                tags_contributed: row.get(7)?,
+                overrides: row.get(8)?,
+                error_count,
+                error_rate: error_count.filter(|n| *n + recipes > 0).map(|n| n as f64 / (n + recipes) as f64),
+            })
+        })?;
+        let mut stats = rows.collect::<SqliteResult<Vec<_>>>()?;
+        for entry in &mut stats {
+            entry.recipe_types = type_counts(entry.mod_id)?;
+        }
+        Ok(stats)
+    }
+
+    fn collect_mods(
+        conn: &Connection,
+        stmt: &mut rusqlite::Statement,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> SqliteResult<Vec<ModInfo>> {
+        let mut mods = stmt
+            .query_map(params, |row| {
+                Ok(ModInfo {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    path: row.get(2)?,
+                    parent_mod_id: row.get(3)?,
+                    loader_mod_id: row.get(4)?,
+                    loader: row.get(5)?,
+                    display_name: row.get(6)?,
+                    version: row.get(7)?,
+                    environment: row.get(8)?,
+                    minecraft_versions: row.get(9)?,
+                    icon: row.get(10)?,
+                    license: row.get(11)?,
+                    homepage: row.get(12)?,
+                    issue_tracker: row.get(13)?,
+                    authors: row.get(14)?,
+                    sha1: row.get(15)?,
+                    sha512: row.get(16)?,
+                    hidden: row.get(17)?,
+                    recipe_count: row.get(18)?,
+                    error_count: row.get(19)?,
+                    dependencies: Vec::new(),
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut deps_stmt = conn.prepare(
+            "SELECT dependency, version_range, required FROM mod_dependencies WHERE mod_id = ?1 ORDER BY id"
+        )?;
+        for info in &mut mods {
+            info.dependencies = deps_stmt
+                .query_map([info.id], |row| {
+                    Ok(ModDependency {
+                        mod_id: row.get(0)?,
+                        version_range: row.get(1)?,
+                        required: row.get(2)?,
+                    })
+                })?
+                .collect::<SqliteResult<_>>()?;
+        }
+        Ok(mods)
+    }
+
+    /// The jar path and in-jar logo path for a mod, if it declares a logo.
+    pub fn get_mod_icon_source(&self, mod_id: i64) -> SqliteResult<Option<(String, String)>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row(
+            "SELECT path, icon FROM mods WHERE id = ?1 AND icon IS NOT NULL",
+            [mod_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    }
+
+    pub fn start_session(&self, folder: Option<&str>, jar_count: usize) -> SqliteResult<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO extraction_sessions (started_at, folder, jar_count, status) VALUES (?1, ?2, ?3, 'running')",
+            rusqlite::params![chrono_lite_now(), folder, jar_count as i64],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Closes a session that stopped on an error.
+    pub fn fail_session(&self, session_id: i64, elapsed: Duration) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE extraction_sessions SET duration_ms = ?2, status = 'failed' WHERE id = ?1",
+            rusqlite::params![session_id, elapsed.as_millis() as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Closes the session, tags the recipes extracted since it started and
+    /// stores a snapshot of everything indexed for `session_snapshot`,
+    /// dropping snapshots past `SNAPSHOT_LIMIT`.
+    pub fn finish_session(
+        &self,
+        session_id: i64,
+        elapsed: Duration,
+        error_count: usize,
+        recipe_count: usize,
+    ) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE extraction_sessions SET duration_ms = ?2, error_count = ?3, recipe_count = ?4, status = 'finished'
+             WHERE id = ?1",
+            rusqlite::params![session_id, elapsed.as_millis() as i64, error_count as i64, recipe_count as i64],
+        )?;
+        tx.execute("UPDATE recipes SET session_id = ?1 WHERE session_id IS NULL", [session_id])?;
+
+        let mods: Vec<(i64, String, Option<String>, Option<String>)> = tx
+            .prepare("SELECT id, name, display_name, version FROM mods")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+            .collect::<SqliteResult<_>>()?;
+        for (mod_id, name, display_name, version) in mods {
+            tx.execute(
+                "INSERT INTO session_mods (session_id, mod_id, mod_key, name, display_name, version)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![session_id, mod_id, scanner::mod_key(&name), name, display_name, version],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO session_recipes (session_id, mod_id, recipe_id, raw_json)
+             SELECT ?1, mod_id, recipe_id, raw_json FROM recipes",
+            [session_id],
+        )?;
+        tx.execute(
+            "INSERT INTO session_tags (session_id, tag, entry) SELECT DISTINCT ?1, tag, entry FROM item_tags",
+            [session_id],
+        )?;
+        let oldest_kept: Option<i64> = tx.query_row(
+            "SELECT MIN(session_id) FROM (SELECT DISTINCT session_id FROM session_mods ORDER BY session_id DESC LIMIT ?1)",
+            [SNAPSHOT_LIMIT],
+            |row| row.get(0),
+        )?;
+        if let Some(oldest_kept) = oldest_kept {
+            for table in ["session_recipes", "session_tags", "session_mods"] {
+                tx.execute(&format!("DELETE FROM {} WHERE session_id < ?1", table), [oldest_kept])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// What a session indexed, or `None` if it has no snapshot because it
+    /// did not finish, ran before snapshots were kept or is past
+    /// `SNAPSHOT_LIMIT`.
+    pub fn session_snapshot(&self, session_id: i64) -> SqliteResult<Option<SessionSnapshot>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT mod_key, name, display_name, version FROM session_mods WHERE session_id = ?1")?;
+        let mods = stmt
+            .query_map([session_id], |row| {
+                Ok(SessionMod { mod_key: row.get(0)?, name: row.get(1)?, display_name: row.get(2)?, version: row.get(3)? })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        if mods.is_empty() {
+            return Ok(None);
+        }
+        let mut stmt = conn.prepare(
+            "SELECT m.mod_key, r.recipe_id, r.raw_json
+             FROM session_recipes r
+             JOIN session_mods m ON m.session_id = r.session_id AND m.mod_id = r.mod_id
+             WHERE r.session_id = ?1",
+        )?;
+        let recipes = stmt
+            .query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut stmt = conn.prepare("SELECT tag, entry FROM session_tags WHERE session_id = ?1")?;
+        let tags = stmt
+            .query_map([session_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(Some(SessionSnapshot { mods, recipes, tags }))
+    }
+
+    /// How the recipe with row id `id` changed over the snapshotted
+    /// sessions, matched by its mod key and recipe id.
+    pub fn recipe_history(&self, id: i64) -> SqliteResult<Option<RecipeHistory>> {
+        let conn = self.reader.lock().unwrap();
+        let recipe: Option<(String, String)> = conn
+            .query_row(
+                "SELECT r.recipe_id, m.name FROM recipes r JOIN mods m ON r.mod_id = m.id WHERE r.id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((recipe_id, mod_name)) = recipe else {
+            return Ok(None);
+        };
+        let mod_key = scanner::mod_key(&mod_name);
+        let mut stmt = conn.prepare(
+            "SELECT r.session_id, r.raw_json
+             FROM session_recipes r
+             JOIN session_mods m ON m.session_id = r.session_id AND m.mod_id = r.mod_id
+             WHERE r.recipe_id = ?1 AND m.mod_key = ?2",
+        )?;
+        let copies = stmt
+            .query_map(rusqlite::params![recipe_id, mod_key], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<SqliteResult<HashMap<i64, String>>>()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, started_at FROM extraction_sessions
+             WHERE id IN (SELECT session_id FROM session_mods)
+             ORDER BY id",
+        )?;
+        let sessions = stmt
+            .query_map([], |row| {
+                let session_id: i64 = row.get(0)?;
+                Ok((session_id, row.get(1)?, copies.get(&session_id).cloned()))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        Ok(Some(session_diff::recipe_history(&mod_key, &recipe_id, &sessions)))
+    }
+
+    /// How many recipes use each of `tags` (ids without `#`) as an
+    /// ingredient. Tags no recipe uses are left out.
+    pub fn tag_consumer_counts(&self, tags: &[String]) -> SqliteResult<HashMap<String, i64>> {
+        if tags.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let conn = self.reader.lock().unwrap();
+        let prefixed: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+        let mut params = Vec::new();
+        let list = placeholders(&prefixed, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT substr(item, 2), COUNT(DISTINCT recipe_id) FROM recipe_ingredients WHERE item IN ({}) GROUP BY item",
+            list
+        ))?;
+        let counts = stmt.query_map(params_from_iter(params), |row| Ok((row.get(0)?, row.get(1)?)))?;
+        counts.collect()
+    }
+
+    /// What the database holds now, in the shape of a session snapshot.
+    pub fn current_snapshot(&self) -> SqliteResult<SessionSnapshot> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, name, display_name, version FROM mods")?;
+        let rows = stmt
+            .query_map([], |row| {
+                let name: String = row.get(1)?;
+                let session_mod =
+                    SessionMod { mod_key: scanner::mod_key(&name), name, display_name: row.get(2)?, version: row.get(3)? };
+                Ok((row.get::<_, i64>(0)?, session_mod))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let keys: HashMap<i64, String> = rows.iter().map(|(id, m)| (*id, m.mod_key.clone())).collect();
+        let mods = rows.into_iter().map(|(_, m)| m).collect();
+        let mut stmt = conn.prepare("SELECT mod_id, recipe_id, raw_json FROM recipes")?;
+        let recipes = stmt
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get(1)?, row.get(2)?)))?
+            .filter_map(|row| row.map(|(mod_id, id, json)| keys.get(&mod_id).map(|key| (key.clone(), id, json))).transpose())
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT tag, entry FROM item_tags")?;
+        let tags = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<SqliteResult<Vec<_>>>()?;
+        Ok(SessionSnapshot { mods, recipes, tags })
+    }
+
+    /// Datapack overrides and "remove" notes aimed at any of `recipe_ids`.
+    /// Copies in jars with the mod key `updated` are the recipes themselves
+    /// rather than overrides.
+    pub fn stale_targets(&self, recipe_ids: &[String], updated: &str) -> SqliteResult<Vec<StaleTarget>> {
+        if recipe_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let list = placeholders(recipe_ids, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.recipe_id, m.name, m.loader, m.loader_mod_id
+             FROM recipes r JOIN mods m ON r.mod_id = m.id
+             WHERE r.recipe_id IN ({})
+             ORDER BY r.recipe_id, m.name",
+            list
+        ))?;
+        let mut targets = stmt
+            .query_map(params_from_iter(&params), |row| {
+                let recipe_id: String = row.get(0)?;
+                let name: String = row.get(1)?;
+                let loader: Option<String> = row.get(2)?;
+                let loader_mod_id: Option<String> = row.get(3)?;
+                let kind = load_order::provider_kind(&name, loader.as_deref(), loader_mod_id.as_deref());
+                Ok((kind == ProviderKind::Datapack && scanner::mod_key(&name) != updated).then_some(StaleTarget {
+                    recipe_id,
+                    kind: StaleKind::Override,
+                    source: Some(name),
+                }))
+            })?
+            .filter_map(|row| row.transpose())
+            .collect::<SqliteResult<Vec<_>>>()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT recipe_id FROM recipe_notes WHERE LOWER(status) = 'remove' AND recipe_id IN ({}) ORDER BY recipe_id",
+            list
+        ))?;
+        let removals = stmt.query_map(params_from_iter(&params), |row| {
+            Ok(StaleTarget { recipe_id: row.get(0)?, kind: StaleKind::Removal, source: None })
+        })?;
+        for removal in removals {
+            targets.push(removal?);
+        }
+        Ok(targets)
+    }
+
+    pub fn db_stats(&self) -> SqliteResult<DbStats> {
+        let last_extraction = self.list_sessions()?.into_iter().next();
+        let file_size = ["", "-wal"]
+            .iter()
+            .filter_map(|suffix| std::fs::metadata(format!("{}{}", self.path.display(), suffix)).ok())
+            .map(|meta| meta.len())
+            .sum();
+
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.type, s.name, s.tbl_name, COALESCE(SUM(d.pgsize), 0)
+             FROM sqlite_master s
+             LEFT JOIN dbstat d ON d.name = s.name
+             WHERE s.type IN ('table', 'index') AND s.name NOT LIKE 'sqlite_%'
+             GROUP BY s.name
+             ORDER BY s.name"
+        )?;
+        let objects = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get(3)?))
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut tables = Vec::new();
+        let mut indexes = Vec::new();
+        for (kind, name, table, bytes) in objects {
+            if kind == "index" {
+                indexes.push(IndexStats { name, table, bytes });
+                continue;
+            }
+            let rows = conn.query_row(&format!("SELECT COUNT(*) FROM \"{}\"", name), [], |row| row.get(0))?;
+            tables.push(TableStats { name, rows, bytes });
+        }
+
+        Ok(DbStats { file_size, tables, indexes, last_extraction })
+    }
+
+    /// Refreshes query planner statistics, rebuilds the file to reclaim
+    /// space left by deleted rows and truncates the write-ahead log.
+    pub fn run_maintenance(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(
+            "ANALYZE;
+             VACUUM;
+             PRAGMA wal_checkpoint(TRUNCATE);"
+        )
+    }
+
+    /// Past extractions, newest first.
+    pub fn list_sessions(&self) -> SqliteResult<Vec<ExtractionSession>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, started_at, folder, jar_count, duration_ms, error_count, recipe_count, status
+             FROM extraction_sessions
+             ORDER BY id DESC"
+        )?;
+        let sessions = stmt.query_map([], |row| {
+            Ok(ExtractionSession {
+                id: row.get(0)?,
+                started_at: row.get(1)?,
+                folder: row.get(2)?,
+                jar_count: row.get(3)?,
+                duration_ms: row.get(4)?,
+                error_count: row.get(5)?,
+                recipe_count: row.get(6)?,
+                status: row.get(7)?,
+            })
+        })?;
+        sessions.collect()
+    }
+
+    pub fn start_jar_run(&self, jar_path: &str, mod_key: &str) -> SqliteResult<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO jar_runs (jar_path, mod_key, started_at, status) VALUES (?1, ?2, ?3, 'running')",
+            [jar_path, mod_key, &chrono_lite_now()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Records how long a jar took and how many recipes it yielded. Returns
+    /// true if the run stalled and the jar has now failed often enough to be
+    /// quarantined.
+    pub fn finish_jar_run(
+        &self,
+        run_id: i64,
+        jar_path: &str,
+        elapsed: Duration,
+        recipe_count: usize,
+        error_count: usize,
+    ) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let status = if elapsed > JAR_STALL_THRESHOLD { "stalled" } else { "ok" };
+        conn.execute(
+            "UPDATE jar_runs SET duration_ms = ?1, status = ?2, recipe_count = ?3, error_count = ?4 WHERE id = ?5",
+            rusqlite::params![elapsed.as_millis() as i64, status, recipe_count as i64, error_count as i64, run_id],
+        )?;
+
+        if status == "ok" {
+            return Ok(false);
+        }
+        Self::quarantine_if_failing(&conn, jar_path)
+    }
+
+    /// Marks runs and sessions left over from an extraction that never finished as crashed,
+    /// quarantining jars that keep crashing.
+    pub fn fail_interrupted_runs(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let crashed: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT DISTINCT jar_path FROM jar_runs WHERE status = 'running'")?;
+            let paths = stmt.query_map([], |row| row.get(0))?;
+            paths.collect::<SqliteResult<_>>()?
+        };
+        conn.execute("UPDATE jar_runs SET status = 'crashed' WHERE status = 'running'", [])?;
+        conn.execute("UPDATE extraction_sessions SET status = 'crashed' WHERE status = 'running'", [])?;
+
+        for jar_path in crashed {
+            Self::quarantine_if_failing(&conn, &jar_path)?;
+        }
+        Ok(())
+    }
+
+    fn quarantine_if_failing(conn: &Connection, jar_path: &str) -> SqliteResult<bool> {
+        // Failures since the jar's last successful run
+        let failures: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM jar_runs
+             WHERE jar_path = ?1 AND status IN ('crashed', 'stalled')
+               AND id > COALESCE((SELECT MAX(id) FROM jar_runs WHERE jar_path = ?1 AND status = 'ok'), 0)",
+            [jar_path],
+            |row| row.get(0),
+        )?;
+        if failures < QUARANTINE_AFTER_FAILURES {
+            return Ok(false);
+        }
+
+        let reason = format!("Crashed or stalled extraction {} times in a row", failures);
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO quarantined_jars (jar_path, reason, quarantined_at) VALUES (?1, ?2, ?3)",
+            [jar_path, &reason, &chrono_lite_now()],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Recipe counts from every finished extraction of the given mod,
+    /// matched across jar versions by mod key, oldest first.
+    pub fn get_mod_recipe_trend(&self, mod_id: i64) -> SqliteResult<Vec<RecipeTrendPoint>> {
+        let conn = self.reader.lock().unwrap();
+        let mod_name: String = conn.query_row("SELECT name FROM mods WHERE id = ?1", [mod_id], |row| row.get(0))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT started_at, jar_path, recipe_count FROM jar_runs
+             WHERE mod_key = ?1 AND recipe_count IS NOT NULL
+             ORDER BY id"
+        )?;
+        let points = stmt.query_map([scanner::mod_key(&mod_name)], |row| {
+            Ok(RecipeTrendPoint {
+                started_at: row.get(0)?,
+                jar_path: row.get(1)?,
+                recipe_count: row.get(2)?,
+            })
+        })?;
+        points.collect()
+    }
+
+    pub fn quarantined_paths(&self) -> SqliteResult<HashSet<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT jar_path FROM quarantined_jars")?;
+        let paths = stmt.query_map([], |row| row.get(0))?;
+        paths.collect()
+    }
+
+    pub fn list_quarantined_jars(&self) -> SqliteResult<Vec<QuarantinedJar>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT jar_path, reason, quarantined_at FROM quarantined_jars ORDER BY jar_path"
+        )?;
+        let jars = stmt.query_map([], |row| {
+            Ok(QuarantinedJar {
+                jar_path: row.get(0)?,
+                reason: row.get(1)?,
+                quarantined_at: row.get(2)?,
+            })
+        })?;
+        jars.collect()
+    }
+
+    pub fn quarantine_jar(&self, jar_path: &str, reason: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO quarantined_jars (jar_path, reason, quarantined_at) VALUES (?1, ?2, ?3)",
+            [jar_path, reason, &chrono_lite_now()],
+        )?;
+        Ok(())
+    }
+
+    /// Lets a quarantined jar be extracted again. Its failure history is
+    /// kept, so one more crash or stall quarantines it again.
+    pub fn release_quarantined_jar(&self, jar_path: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM quarantined_jars WHERE jar_path = ?1", [jar_path])?;
+        Ok(())
+    }
+
+    pub fn insert_recipe(
+        &self,
+        mod_id: i64,
+        path: &str,
+        recipe_id: &str,
+        raw_json: &str,
+        recipe: &ParsedRecipe,
+    ) -> SqliteResult<i64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT OR REPLACE INTO recipes (mod_id, path, recipe_id, namespace, recipe_type, canonical_type,
+                 result_item, result_namespace, result_count, raw_json, machine, pattern, grid_width, grid_height)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            rusqlite::params![
+                mod_id,
+                path,
+                recipe_id,
+                recipe_id.split_once(':').map_or("", |(namespace, _)| namespace),
+                recipe.recipe_type,
+                recipe_parser::canonical_recipe_type(&recipe.recipe_type),
+                recipe.result_item,
+                recipe.result_item.as_deref().and_then(id_namespace),
+                recipe.result_count,
+                raw_json,
+                recipe.machine,
+                (!recipe.pattern.is_empty()).then(|| recipe.pattern.join("/")),
+                recipe.pattern.iter().map(|row| row.chars().count()).max(),
+                (!recipe.pattern.is_empty()).then_some(recipe.pattern.len()),
+            ],
+        )?;
+
+        let recipe_id = conn.last_insert_rowid();
+
+        // Clear existing ingredients for this recipe (in case of replace)
+        conn.execute(
+            "DELETE FROM recipe_ingredients WHERE recipe_id = ?1",
+            [recipe_id],
+        )?;
+        conn.execute(
+            "DELETE FROM recipe_fluids WHERE recipe_id = ?1",
+            [recipe_id],
+        )?;
+
         // Insert ingredients
-        for item in ingredients {
+        for ingredient in &recipe.ingredients {
             conn.execute(
-                "INSERT INTO recipe_ingredients (recipe_id, item) VALUES (?1, ?2)",
-                rusqlite::params![recipe_id, item],
+                "INSERT INTO recipe_ingredients (recipe_id, item, namespace, count) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![
+                    recipe_id,
+                    ingredient.item,
+                    id_namespace(&ingredient.item).unwrap_or(""),
+                    ingredient.count
+                ],
             )?;
         }
 
-        Ok(recipe_id)
+        for fluid in &recipe.fluids {
+            conn.execute(
+                "INSERT INTO recipe_fluids (recipe_id, fluid, amount, is_output) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![recipe_id, fluid.fluid, fluid.amount, fluid.is_output],
+            )?;
+        }
+
+        Ok(recipe_id)
+    }
+
+    pub fn insert_item_names(&self, mod_id: i64, locale: &str, names: &[(String, String)]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO item_names (item_id, locale, name, mod_id) VALUES (?1, ?2, ?3, ?4)"
+            )?;
+            for (item_id, name) in names {
+                stmt.execute(rusqlite::params![item_id, locale, name, mod_id])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Display names for the given item ids in one locale. Items without a
+    /// translation are left out of the map.
+    pub fn get_item_names(&self, items: &[String], locale: &str) -> SqliteResult<HashMap<String, String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name FROM item_names WHERE item_id = ?1 AND locale = ?2"
+        )?;
+
+        let mut names = HashMap::new();
+        for item in items {
+            let name: Option<String> = stmt
+                .query_row([item.as_str(), locale], |row| row.get(0))
+                .optional()?;
+            if let Some(name) = name {
+                names.insert(item.clone(), name);
+            }
+        }
+        Ok(names)
+    }
+
+    pub fn insert_machine(&self, mod_id: i64, machine_id: &str, name: Option<&str>, raw_json: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO machines (mod_id, machine_id, name, raw_json) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![mod_id, machine_id, name, raw_json],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_machines(&self) -> SqliteResult<Vec<Machine>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT ma.machine_id, ma.name, m.name,
+                    (SELECT COUNT(*) FROM recipes r WHERE r.machine = ma.machine_id AND {})
+             FROM machines ma
+             JOIN mods m ON ma.mod_id = m.id AND m.hidden = 0
+             ORDER BY ma.machine_id",
+            VISIBLE_RECIPE
+        ))?;
+
+        let machines = stmt.query_map([], |row| {
+            Ok(Machine {
+                machine_id: row.get(0)?,
+                name: row.get(1)?,
+                mod_name: row.get(2)?,
+                recipe_count: row.get(3)?,
+            })
+        })?;
+        machines.collect()
+    }
+
+    pub fn list_machine_recipes(&self, machine_id: &str) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = vec![Value::from(machine_id.to_string())];
+        let where_clause = self.recipe_where(vec!["r.machine = ?1".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY r.result_item, r.path",
+            RECIPE_SELECT, where_clause
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    pub fn insert_enchantment(
+        &self,
+        mod_id: i64,
+        enchantment_id: &str,
+        raw_json: &str,
+        enchantment: &ParsedEnchantment,
+    ) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO enchantments (mod_id, enchantment_id, description, supported_items,
+                 max_level, weight, anvil_cost, min_cost_base, min_cost_per_level, max_cost_base,
+                 max_cost_per_level, effects, raw_json)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            rusqlite::params![
+                mod_id,
+                enchantment_id,
+                enchantment.description,
+                enchantment.supported_items.join(","),
+                enchantment.max_level,
+                enchantment.weight,
+                enchantment.anvil_cost,
+                enchantment.min_cost.base,
+                enchantment.min_cost.per_level,
+                enchantment.max_cost.base,
+                enchantment.max_cost.per_level,
+                enchantment.effects.join(","),
+                raw_json
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_enchantments(&self) -> SqliteResult<Vec<Enchantment>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY e.enchantment_id", ENCHANTMENT_SELECT))?;
+        Self::collect_enchantments(&mut stmt, &[])
+    }
+
+    /// Matches the enchantment id, description or effect types.
+    pub fn search_enchantments(&self, query: &str) -> SqliteResult<Vec<Enchantment>> {
+        let conn = self.reader.lock().unwrap();
+        let search_term = format!("%{}%", query);
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE e.enchantment_id LIKE ?1 OR e.description LIKE ?1 OR e.effects LIKE ?1
+             ORDER BY e.enchantment_id",
+            ENCHANTMENT_SELECT
+        ))?;
+        Self::collect_enchantments(&mut stmt, &[&search_term])
+    }
+
+    /// Matches the items or tags an enchantment can be applied to.
+    pub fn search_enchantments_by_item(&self, item: &str) -> SqliteResult<Vec<Enchantment>> {
+        let conn = self.reader.lock().unwrap();
+        let search_term = format!("%{}%", item);
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE e.supported_items LIKE ?1 ORDER BY e.enchantment_id",
+            ENCHANTMENT_SELECT
+        ))?;
+        Self::collect_enchantments(&mut stmt, &[&search_term])
+    }
+
+    fn collect_enchantments(
+        stmt: &mut rusqlite::Statement,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> SqliteResult<Vec<Enchantment>> {
+        let enchantments = stmt.query_map(params, |row| {
+            Ok(Enchantment {
+                enchantment_id: row.get(0)?,
+                mod_name: row.get(1)?,
+                description: row.get(2)?,
+                supported_items: split_list(&row.get::<_, String>(3)?),
+                max_level: row.get(4)?,
+                weight: row.get(5)?,
+                anvil_cost: row.get(6)?,
+                min_cost_base: row.get(7)?,
+                min_cost_per_level: row.get(8)?,
+                max_cost_base: row.get(9)?,
+                max_cost_per_level: row.get(10)?,
+                effects: split_list(&row.get::<_, String>(11)?),
+                raw_json: row.get(12)?,
+            })
+        })?;
+        enchantments.collect()
+    }
+
+    pub fn insert_worldgen(
+        &self,
+        mod_id: i64,
+        kind: &str,
+        entry_id: &str,
+        raw_json: &str,
+        references: &[String],
+    ) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO worldgen (mod_id, kind, entry_id, raw_json) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![mod_id, kind, entry_id, raw_json],
+        )?;
+        let worldgen_id = tx.last_insert_rowid();
+        tx.execute("DELETE FROM worldgen_refs WHERE worldgen_id = ?1", [worldgen_id])?;
+        for reference in references {
+            tx.execute(
+                "INSERT INTO worldgen_refs (worldgen_id, ref) VALUES (?1, ?2)",
+                rusqlite::params![worldgen_id, reference],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Lists worldgen entries, optionally only one kind (e.g. `placed_feature`).
+    pub fn list_worldgen(&self, kind: Option<&str>) -> SqliteResult<Vec<WorldgenEntry>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT w.id, m.name, w.kind, w.entry_id, w.raw_json
+             FROM worldgen w
+             JOIN mods m ON w.mod_id = m.id AND m.hidden = 0
+             WHERE ?1 IS NULL OR w.kind = ?1
+             ORDER BY w.kind, w.entry_id"
+        )?;
+        Self::collect_worldgen(&conn, &mut stmt, &[&kind])
+    }
+
+    /// Finds worldgen entries referencing a block, item, feature or tag.
+    pub fn search_worldgen(&self, reference: &str) -> SqliteResult<Vec<WorldgenEntry>> {
+        let conn = self.reader.lock().unwrap();
+        let search_term = format!("%{}%", reference);
+        let mut stmt = conn.prepare(
+            "SELECT w.id, m.name, w.kind, w.entry_id, w.raw_json
+             FROM worldgen w
+             JOIN mods m ON w.mod_id = m.id AND m.hidden = 0
+             WHERE w.id IN (SELECT worldgen_id FROM worldgen_refs WHERE ref LIKE ?1)
+             ORDER BY w.kind, w.entry_id"
+        )?;
+        Self::collect_worldgen(&conn, &mut stmt, &[&search_term])
+    }
+
+    fn collect_worldgen(
+        conn: &Connection,
+        stmt: &mut rusqlite::Statement,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> SqliteResult<Vec<WorldgenEntry>> {
+        let mut entries = stmt
+            .query_map(params, |row| {
+                Ok(WorldgenEntry {
+                    id: row.get(0)?,
+                    mod_name: row.get(1)?,
+                    kind: row.get(2)?,
+                    entry_id: row.get(3)?,
+                    references: Vec::new(),
+                    raw_json: row.get(4)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut refs_stmt = conn.prepare("SELECT ref FROM worldgen_refs WHERE worldgen_id = ?1 ORDER BY ref")?;
+        for entry in &mut entries {
+            entry.references = refs_stmt
+                .query_map([entry.id], |row| row.get(0))?
+                .collect::<SqliteResult<_>>()?;
+        }
+        Ok(entries)
+    }
+
+    pub fn insert_item_components(&self, mod_id: i64, path: &str, components: &[ItemComponents]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for c in components {
+            tx.execute(
+                "INSERT INTO item_components (mod_id, path, item, nutrition, saturation, max_stack_size, max_damage)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                rusqlite::params![mod_id, path, c.item, c.nutrition, c.saturation, c.max_stack_size, c.max_damage],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Records item ids found in a tag or loot table at `path`.
+    pub fn insert_item_refs(&self, mod_id: i64, path: &str, source: &str, items: &[String]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for item in items {
+            tx.execute(
+                "INSERT INTO item_refs (mod_id, item, source, path) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![mod_id, item, source, path],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Records what an item tag file lists. Several jars may add to the same
+    /// tag; their entries are merged.
+    pub fn insert_tag_entries(&self, mod_id: i64, tag: &str, entries: &[String]) -> SqliteResult<()> {
+        self.insert_tag_rows("item_tags", mod_id, tag, entries)
+    }
+
+    /// Records what a fluid tag file lists, as `insert_tag_entries` does for items.
+    pub fn insert_fluid_tag_entries(&self, mod_id: i64, tag: &str, entries: &[String]) -> SqliteResult<()> {
+        self.insert_tag_rows("fluid_tags", mod_id, tag, entries)
+    }
+
+    /// Records that a jar's tag file replaces the tag rather than adding to
+    /// it. `kind` is `item` or `fluid`.
+    pub fn record_tag_replace(&self, mod_id: i64, kind: &str, tag: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO tag_replacements (mod_id, kind, tag) VALUES (?1, ?2, ?3)",
+            rusqlite::params![mod_id, kind, tag],
+        )?;
+        Ok(())
+    }
+
+    /// Drops the entries that jars loading before a replacing tag file added
+    /// to its tag, as the game does. Jars load as in `load_order::winner`
+    /// with no explicit order. Dropped entries come back only when their jar
+    /// is extracted again.
+    pub fn apply_tag_replacements(&self) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let provider = |row: &rusqlite::Row, from: usize| -> SqliteResult<(i64, String, Option<String>, Option<String>)> {
+            Ok((row.get(from)?, row.get(from + 1)?, row.get(from + 2)?, row.get(from + 3)?))
+        };
+        let load_key = |(_, name, loader, loader_mod_id): &(i64, String, Option<String>, Option<String>)| {
+            let kind = load_order::provider_kind(name, loader.as_deref(), loader_mod_id.as_deref());
+            load_order::load_key(&LoadEntry { kind, jar_name: name, sort_name: loader_mod_id.as_deref().unwrap_or(name) }, &[])
+        };
+        let replacements = tx
+            .prepare(
+                "SELECT t.kind, t.tag, m.id, m.name, m.loader, m.loader_mod_id
+                 FROM tag_replacements t JOIN mods m ON m.id = t.mod_id",
+            )?
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, provider(row, 2)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+        for (kind, tag, replacer) in replacements {
+            let table = format!("{}_tags", kind);
+            let providers = tx
+                .prepare(&format!(
+                    "SELECT DISTINCT m.id, m.name, m.loader, m.loader_mod_id
+                     FROM {} t JOIN mods m ON m.id = t.mod_id WHERE t.tag = ?1",
+                    table
+                ))?
+                .query_map([&tag], |row| provider(row, 0))?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            let replacer_key = load_key(&replacer);
+            for earlier in providers.iter().filter(|p| p.0 != replacer.0 && load_key(p) < replacer_key) {
+                tx.execute(
+                    &format!("DELETE FROM {} WHERE tag = ?1 AND mod_id = ?2", table),
+                    rusqlite::params![tag, earlier.0],
+                )?;
+            }
+        }
+        tx.commit()
+    }
+
+    fn insert_tag_rows(&self, table: &str, mod_id: i64, tag: &str, entries: &[String]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for entry in entries {
+            tx.execute(
+                &format!("INSERT INTO {} (mod_id, tag, entry) VALUES (?1, ?2, ?3)", table),
+                rusqlite::params![mod_id, tag, entry],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Items in a tag, following nested tags.
+    pub fn tag_members(&self, tag: &str) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE members(entry) AS (
+                 SELECT entry FROM item_tags WHERE tag = ?1
+                 UNION
+                 SELECT t.entry FROM item_tags t JOIN members m ON m.entry = '#' || t.tag
+             )
+             SELECT entry FROM members WHERE entry NOT LIKE '#%' ORDER BY entry",
+        )?;
+        let items = stmt.query_map([tag.trim_start_matches('#')], |row| row.get(0))?;
+        items.collect()
+    }
+
+    /// Every (input, recipe, output) step of the recipe graph, with tag
+    /// ingredients expanded to their items. Respects the pinned filter.
+    pub fn recipe_edges(&self) -> SqliteResult<Vec<RecipeEdge>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(vec!["r.result_item IS NOT NULL".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE members(tag, item) AS (
+                 SELECT '#' || tag, entry FROM item_tags
+                 UNION
+                 SELECT m.tag, t.entry FROM members m JOIN item_tags t ON m.item = '#' || t.tag
+             )
+             SELECT r.id, r.recipe_id, COALESCE(mb.item, i.item), i.count,
+                    (SELECT COALESCE(SUM(o.count), 0) FROM recipe_ingredients o WHERE o.recipe_id = r.id AND o.id != i.id),
+                    r.result_item, COALESCE(r.result_count, 1)
+             FROM recipes r
+             JOIN recipe_ingredients i ON i.recipe_id = r.id
+             LEFT JOIN members mb ON mb.tag = i.item
+             {}",
+            where_clause
+        ))?;
+        let edges = stmt.query_map(params_from_iter(params), |row| {
+            Ok(RecipeEdge {
+                recipe: row.get(0)?,
+                recipe_id: row.get(1)?,
+                input: row.get(2)?,
+                input_count: row.get(3)?,
+                other_input_count: row.get(4)?,
+                output: row.get(5)?,
+                output_count: row.get(6)?,
+            })
+        })?;
+        // Nested tags are followed by `members`; their own rows are not items
+        edges.filter(|edge| !matches!(edge, Ok(edge) if edge.input.starts_with('#'))).collect()
+    }
+
+    /// Every item a recipe makes, with how many recipes make it. Respects the
+    /// pinned filter.
+    pub fn output_items(&self) -> SqliteResult<Vec<OutputItem>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(vec!["r.result_item IS NOT NULL".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.result_item, i.display_name, m.name, COUNT(*)
+             FROM recipes r
+             LEFT JOIN items i ON i.item = r.result_item
+             LEFT JOIN mods m ON i.mod_id = m.id
+             {} GROUP BY r.result_item ORDER BY r.result_item",
+            where_clause
+        ))?;
+        let items = stmt.query_map(params_from_iter(params), |row| {
+            Ok(OutputItem {
+                item: row.get(0)?,
+                display_name: row.get(1)?,
+                mod_name: row.get(2)?,
+                recipe_count: row.get(3)?,
+            })
+        })?;
+        items.collect()
+    }
+
+    /// Every (tag, entry) pair, merged across jars. Nested tags appear as
+    /// `#namespace:path` entries.
+    pub fn tag_entries(&self) -> SqliteResult<Vec<(String, String)>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT tag, entry FROM item_tags ORDER BY tag, entry")?;
+        let entries = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        entries.collect()
+    }
+
+    /// Tags holding items from several namespaces, with the recipes making
+    /// and using each item and those using the tag. Recipes respect the
+    /// pinned filter.
+    pub fn tag_unification(&self) -> SqliteResult<Vec<TagUnification>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE members(tag, item) AS (
+                 SELECT tag, entry FROM item_tags
+                 UNION
+                 SELECT m.tag, t.entry FROM members m JOIN item_tags t ON m.item = '#' || t.tag
+             )
+             SELECT tag, item FROM members WHERE item NOT LIKE '#%'",
+        )?;
+        let members = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        // ?1 is the item or tag; the pinned filter's params follow it
+        let mut params = vec![Value::Null];
+        let producer_where = self.recipe_where(vec!["r.result_item = ?1".to_string()], &mut params);
+        let mut consumer_params = vec![Value::Null];
+        let consumer_where = self.recipe_where(
+            vec!["r.id IN (SELECT recipe_id FROM recipe_ingredients WHERE item = ?1)".to_string()],
+            &mut consumer_params,
+        );
+        let mut producers = conn.prepare(&format!("SELECT r.recipe_id FROM recipes r {} ORDER BY r.recipe_id", producer_where))?;
+        let mut consumers = conn.prepare(&format!("SELECT r.recipe_id FROM recipes r {} ORDER BY r.recipe_id", consumer_where))?;
+        let mut owner = conn.prepare("SELECT m.name FROM items i JOIN mods m ON i.mod_id = m.id WHERE i.item = ?1")?;
+        let recipe_ids = |stmt: &mut rusqlite::Statement, params: &mut Vec<Value>, key: &str| -> SqliteResult<Vec<String>> {
+            params[0] = Value::from(key.to_string());
+            let ids = stmt.query_map(params_from_iter(params.iter()), |row| row.get(0))?;
+            ids.collect()
+        };
+
+        let mut report = Vec::new();
+        for (tag, items) in unification::multi_namespace_tags(&members) {
+            let tag = format!("#{}", tag);
+            let mut variants = Vec::new();
+            for item in items {
+                variants.push(TagVariant {
+                    item: item.to_string(),
+                    mod_name: owner.query_row([item], |row| row.get(0)).optional()?,
+                    producers: recipe_ids(&mut producers, &mut params, item)?,
+                    consumers: recipe_ids(&mut consumers, &mut consumer_params, item)?,
+                });
+            }
+            let tag_consumers = recipe_ids(&mut consumers, &mut consumer_params, &tag)?;
+            report.push(TagUnification { tag, variants, tag_consumers });
+        }
+        Ok(report)
+    }
+
+    /// Ingredients nothing produces, most used first. Items from jars that
+    /// were not scanned, often the game's own, show up too. Tags count only
+    /// when their contents were extracted. Respects the pinned filter.
+    pub fn unobtainable_items(&self) -> SqliteResult<Vec<UnobtainableItem>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(
+            vec!["(i.item NOT LIKE '#%' AND i.item NOT IN (SELECT item FROM obtainable)
+                  OR i.item IN (SELECT '#' || tag FROM item_tags)
+                     AND NOT EXISTS (SELECT 1 FROM members mb JOIN obtainable o ON o.item = mb.item
+                                     WHERE '#' || mb.tag = i.item))"
+                .to_string()],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE {},
+             members(tag, item) AS (
+                 SELECT tag, entry FROM item_tags
+                 UNION
+                 SELECT m.tag, t.entry FROM members m JOIN item_tags t ON m.item = '#' || t.tag
+             )
+             SELECT i.item, owner.name, COUNT(DISTINCT r.id)
+             FROM recipes r
+             JOIN recipe_ingredients i ON i.recipe_id = r.id
+             LEFT JOIN namespaces ns ON ns.namespace = substr(ltrim(i.item, '#'), 1, instr(ltrim(i.item, '#'), ':') - 1)
+             LEFT JOIN mods owner ON owner.id = ns.mod_id
+             {}
+             GROUP BY i.item
+             ORDER BY 3 DESC, i.item",
+            OBTAINABLE_CTE, where_clause
+        ))?;
+        let items = stmt.query_map(params_from_iter(params), |row| {
+            Ok(UnobtainableItem {
+                item: row.get(0)?,
+                mod_name: row.get(1)?,
+                consumer_count: row.get(2)?,
+            })
+        })?;
+        items.collect()
+    }
+
+    /// Outputs nothing uses, most produced first. Tools, armor and other
+    /// finished goods are often tagged and so drop out; `endpoints` lists
+    /// more items known to be useful on their own. Respects the pinned filter.
+    pub fn dead_end_outputs(&self, endpoints: &[String]) -> SqliteResult<Vec<DeadEndOutput>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let mut conditions = vec![
+            "r.result_item IS NOT NULL".to_string(),
+            "r.result_item NOT IN (SELECT item FROM recipe_ingredients)".to_string(),
+            "r.result_item NOT IN (SELECT entry FROM item_tags)".to_string(),
+            "r.result_item NOT IN (SELECT item FROM trade_costs)".to_string(),
+        ];
+        if !endpoints.is_empty() {
+            conditions.push(format!("r.result_item NOT IN ({})", placeholders(endpoints, &mut params)));
+        }
+        let where_clause = self.recipe_where(conditions, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.result_item, owner.name, COUNT(*)
+             FROM recipes r
+             LEFT JOIN namespaces ns ON ns.namespace = r.result_namespace
+             LEFT JOIN mods owner ON owner.id = ns.mod_id
+             {}
+             GROUP BY r.result_item
+             ORDER BY 3 DESC, r.result_item",
+            where_clause
+        ))?;
+        let items = stmt.query_map(params_from_iter(params), |row| {
+            Ok(DeadEndOutput {
+                item: row.get(0)?,
+                mod_name: row.get(1)?,
+                producer_count: row.get(2)?,
+            })
+        })?;
+        items.collect()
+    }
+
+    /// The `limit` ingredients most recipes use, tags included as they are
+    /// written rather than expanded. Respects the pinned filter.
+    pub fn ingredient_usage(&self, limit: usize) -> SqliteResult<Vec<IngredientUsage>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(Vec::new(), &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT i.item, r.mod_id, m.name, COUNT(DISTINCT r.id), SUM(i.count)
+             FROM recipes r
+             JOIN recipe_ingredients i ON i.recipe_id = r.id
+             JOIN mods m ON m.id = r.mod_id
+             {}
+             GROUP BY i.item, r.mod_id
+             ORDER BY i.item, 4 DESC, m.name",
+            where_clause
+        ))?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            let facet = ModFacet { mod_id: row.get(1)?, name: row.get(2)?, count: row.get(3)? };
+            Ok((row.get::<_, String>(0)?, facet, row.get::<_, i64>(4)?))
+        })?;
+
+        let mut usage: Vec<IngredientUsage> = Vec::new();
+        for row in rows {
+            let (item, facet, total) = row?;
+            match usage.last_mut() {
+                Some(last) if last.item == item => {
+                    last.recipe_count += facet.count;
+                    last.total_count += total;
+                    last.mods.push(facet);
+                }
+                _ => usage.push(IngredientUsage { item, recipe_count: facet.count, total_count: total, mods: vec![facet] }),
+            }
+        }
+        usage.sort_by(|a, b| b.recipe_count.cmp(&a.recipe_count).then_with(|| a.item.cmp(&b.item)));
+        usage.truncate(limit);
+        Ok(usage)
+    }
+
+    /// Ingredient tags that are undefined or empty, most used first. A tag
+    /// item counts if it is vanilla or a scanned jar owns its namespace.
+    /// Undefined `minecraft` tags are only reported once the game jar has
+    /// been scanned. Respects the pinned filter.
+    pub fn broken_tags(&self) -> SqliteResult<Vec<BrokenTag>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(
+            vec!["i.item LIKE '#%'
+                  AND (defined.tag IS NOT NULL OR i.namespace != 'minecraft'
+                       OR EXISTS (SELECT 1 FROM mods WHERE loader_mod_id = 'minecraft'))
+                  AND NOT EXISTS (SELECT 1 FROM members mb
+                                  WHERE '#' || mb.tag = i.item AND mb.item NOT LIKE '#%'
+                                    AND (substr(mb.item, 1, instr(mb.item, ':') - 1) IN (SELECT namespace FROM namespaces)
+                                         OR mb.item LIKE 'minecraft:%'))"
+                .to_string()],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE members(tag, item) AS (
+                 SELECT tag, entry FROM item_tags
+                 UNION
+                 SELECT m.tag, t.entry FROM members m JOIN item_tags t ON m.item = '#' || t.tag
+             )
+             SELECT i.item, owner.name, defined.tag IS NULL, COUNT(DISTINCT r.id)
+             FROM recipes r
+             JOIN recipe_ingredients i ON i.recipe_id = r.id
+             LEFT JOIN (SELECT DISTINCT '#' || tag AS tag FROM item_tags) defined ON defined.tag = i.item
+             LEFT JOIN namespaces ns ON ns.namespace = i.namespace
+             LEFT JOIN mods owner ON owner.id = ns.mod_id
+             {}
+             GROUP BY i.item
+             ORDER BY 4 DESC, i.item",
+            where_clause
+        ))?;
+        let tags = stmt.query_map(params_from_iter(params), |row| {
+            Ok(BrokenTag {
+                tag: row.get(0)?,
+                mod_name: row.get(1)?,
+                missing: row.get(2)?,
+                consumer_count: row.get(3)?,
+            })
+        })?;
+        tags.collect()
+    }
+
+    /// Recipe ingredients and results naming items outside the observed
+    /// registry, grouped by the mod shipping the recipe. An item exists if a
+    /// lang file names it; namespaces with no lang names at all cannot be
+    /// checked, and `minecraft` counts as present even when the game jar was
+    /// not scanned. Respects the pinned filter.
+    pub fn dangling_references(&self) -> SqliteResult<Vec<DanglingReferences>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(
+            vec!["x.item NOT IN (SELECT item_id FROM item_names)
+                  AND (ns.namespace IS NULL AND x.namespace != 'minecraft' OR x.namespace IN (SELECT namespace FROM named))"
+                .to_string()],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!(
+            "WITH named(namespace) AS (
+                 SELECT DISTINCT substr(item_id, 1, instr(item_id, ':') - 1) FROM item_names
+             ),
+             refs(recipe, item, namespace, output) AS (
+                 SELECT recipe_id, item, namespace, 0 FROM recipe_ingredients
+                 WHERE item NOT LIKE '#%' AND namespace != ''
+                 UNION
+                 SELECT id, result_item, result_namespace, 1 FROM recipes
+                 WHERE result_item IS NOT NULL AND result_namespace != ''
+             )
+             SELECT r.mod_id, m.name, r.id, r.recipe_id, x.item, x.output,
+                    ns.namespace IS NULL AND x.namespace NOT IN (SELECT namespace FROM named)
+             FROM refs x
+             JOIN recipes r ON r.id = x.recipe
+             JOIN mods m ON m.id = r.mod_id
+             LEFT JOIN namespaces ns ON ns.namespace = x.namespace
+             {}
+             ORDER BY m.name, r.mod_id, r.recipe_id, x.output, x.item",
+            where_clause
+        ))?;
+        let rows = stmt.query_map(params_from_iter(params), |row| {
+            let reference = DanglingReference {
+                recipe: row.get(2)?,
+                recipe_id: row.get(3)?,
+                item: row.get(4)?,
+                output: row.get(5)?,
+                reason: if row.get(6)? { DanglingReason::MissingMod } else { DanglingReason::UnknownItem },
+            };
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, reference))
+        })?;
+
+        let mut groups: Vec<DanglingReferences> = Vec::new();
+        for row in rows {
+            let (mod_id, mod_name, reference) = row?;
+            match groups.last_mut() {
+                Some(last) if last.mod_id == mod_id => last.references.push(reference),
+                _ => groups.push(DanglingReferences { mod_id, mod_name, references: vec![reference] }),
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Which mods' recipes use items of which other mods, heaviest first.
+    /// Respects the pinned filter.
+    pub fn recipe_dependencies(&self) -> SqliteResult<Vec<RecipeDependency>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(vec!["ns.mod_id != r.mod_id".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.mod_id, consumer.name, ns.mod_id, owner.name, COUNT(DISTINCT r.id), COUNT(DISTINCT i.item)
+             FROM recipes r
+             JOIN recipe_ingredients i ON i.recipe_id = r.id
+             JOIN namespaces ns ON ns.namespace = i.namespace
+             JOIN mods consumer ON consumer.id = r.mod_id
+             JOIN mods owner ON owner.id = ns.mod_id
+             {}
+             GROUP BY r.mod_id, ns.mod_id
+             ORDER BY 5 DESC, consumer.name, owner.name",
+            where_clause
+        ))?;
+        let edges = stmt.query_map(params_from_iter(params), |row| {
+            Ok(RecipeDependency {
+                from_mod_id: row.get(0)?,
+                from: row.get(1)?,
+                to_mod_id: row.get(2)?,
+                to: row.get(3)?,
+                recipe_count: row.get(4)?,
+                item_count: row.get(5)?,
+            })
+        })?;
+        edges.collect()
+    }
+
+    /// Shaped and shapeless crafting recipes, for conflict detection.
+    /// Respects the pinned filter.
+    pub fn crafting_recipes(&self) -> SqliteResult<Vec<CraftingRecipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(
+            vec!["r.canonical_type IN ('minecraft:crafting_shaped', 'minecraft:crafting_shapeless')".to_string()],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, r.recipe_id, m.name, r.recipe_type, r.result_item, r.raw_json
+             FROM recipes r JOIN mods m ON r.mod_id = m.id
+             {} ORDER BY r.id",
+            where_clause
+        ))?;
+        let recipes = stmt.query_map(params_from_iter(params), |row| {
+            Ok(CraftingRecipe {
+                id: row.get(0)?,
+                recipe_id: row.get(1)?,
+                mod_name: row.get(2)?,
+                recipe_type: row.get(3)?,
+                result_item: row.get(4)?,
+                raw_json: row.get(5)?,
+            })
+        })?;
+        recipes.collect()
+    }
+
+    /// Recipes with an item result, for complexity scoring and progression.
+    /// Respects the pinned filter.
+    pub fn complexity_recipes(&self) -> SqliteResult<Vec<ComplexityRecipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(vec!["r.result_item IS NOT NULL".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, r.result_item, r.canonical_type,
+                    (SELECT json_group_array(item) FROM recipe_ingredients WHERE recipe_id = r.id)
+             FROM recipes r
+             {} ORDER BY r.id",
+            where_clause
+        ))?;
+        let recipes = stmt.query_map(params_from_iter(params), |row| {
+            let ingredients: String = row.get(3)?;
+            Ok(ComplexityRecipe {
+                id: row.get(0)?,
+                output: row.get(1)?,
+                recipe_type: row.get(2)?,
+                ingredients: serde_json::from_str(&ingredients).unwrap_or_default(),
+            })
+        })?;
+        recipes.collect()
+    }
+
+    /// Items a loot table drops or a trade sells, which stay obtainable
+    /// whatever happens to recipes.
+    pub fn non_recipe_sources(&self) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT item FROM item_refs WHERE source = ?1 UNION SELECT result_item FROM trades ORDER BY 1",
+        )?;
+        let items = stmt.query_map([items::LOOT_TABLE], |row| row.get(0))?;
+        items.collect()
+    }
+
+    /// Recipes with an item result and their amounts, for value
+    /// propagation. Respects the pinned filter.
+    pub fn value_recipes(&self) -> SqliteResult<Vec<ValueRecipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(vec!["r.result_item IS NOT NULL".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, r.recipe_id, r.result_item, COALESCE(r.result_count, 1),
+                    (SELECT json_group_array(json_array(item, count)) FROM recipe_ingredients WHERE recipe_id = r.id)
+             FROM recipes r
+             {} ORDER BY r.id",
+            where_clause
+        ))?;
+        let recipes = stmt.query_map(params_from_iter(params), |row| {
+            let ingredients: String = row.get(4)?;
+            Ok(ValueRecipe {
+                id: row.get(0)?,
+                recipe_id: row.get(1)?,
+                output: row.get(2)?,
+                output_count: row.get(3)?,
+                ingredients: serde_json::from_str(&ingredients).unwrap_or_default(),
+            })
+        })?;
+        recipes.collect()
+    }
+
+    /// Every item in the registry built by `rebuild_items`.
+    pub fn item_ids(&self) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT item FROM items ORDER BY item")?;
+        let items = stmt.query_map([], |row| row.get(0))?;
+        items.collect()
+    }
+
+    /// Tags (as `#namespace:path`) holding an item, directly or through
+    /// nested tags.
+    pub fn tags_containing(&self, item: &str) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} ORDER BY tag", tags_containing_sql("entry = ?1")))?;
+        let tags = stmt.query_map([item], |row| row.get(0))?;
+        tags.collect()
+    }
+
+    /// Rebuilds the item registry from every item id seen in recipes, tags,
+    /// loot tables and lang files. Run after `rebuild_namespace_owners`,
+    /// which decides each item's mod.
+    pub fn rebuild_items(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM items", [])?;
+        conn.execute(
+            "INSERT INTO items (item, namespace, path, display_name, mod_id)
+             SELECT ids.item,
+                    substr(ids.item, 1, instr(ids.item, ':') - 1),
+                    substr(ids.item, instr(ids.item, ':') + 1),
+                    (SELECT name FROM item_names WHERE item_id = ids.item AND locale = ?1),
+                    ns.mod_id
+             FROM (
+                 SELECT result_item AS item FROM recipes WHERE result_item IS NOT NULL
+                 UNION SELECT item FROM recipe_ingredients
+                 UNION SELECT item FROM item_refs
+                 UNION SELECT item_id FROM item_names
+             ) ids
+             LEFT JOIN namespaces ns ON ns.namespace = substr(ids.item, 1, instr(ids.item, ':') - 1)
+             WHERE instr(ids.item, ':') > 1 AND ids.item NOT LIKE '#%'",
+            [DISPLAY_LOCALE],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_items(&self, namespace: Option<&str>) -> SqliteResult<Vec<Item>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE {} AND (?1 IS NULL OR i.namespace = ?1) ORDER BY i.item",
+            ITEM_SELECT, VISIBLE_ITEM
+        ))?;
+        let items = stmt.query_map([namespace], Self::item_from_row)?;
+        items.collect()
+    }
+
+    /// Items whose id or display name matches `query`, for autocomplete.
+    pub fn search_items(&self, query: &str, limit: i64, mode: MatchMode) -> SqliteResult<Vec<Item>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let by_id = matching::condition("i.item", query, mode, &mut params);
+        let by_name = matching::condition("i.display_name", query, mode, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE {} AND ({} OR {}) ORDER BY i.item LIMIT ?{}",
+            ITEM_SELECT,
+            VISIBLE_ITEM,
+            by_id,
+            by_name,
+            params.len() + 1
+        ))?;
+        params.push(Value::from(limit));
+        let items = stmt.query_map(params_from_iter(params), Self::item_from_row)?;
+        items.collect()
+    }
+
+    /// Item suggestions for a search box: exact ids first, then ids, paths
+    /// and display names starting with `prefix`, then ones merely containing
+    /// it. Ties go to items used by more recipes.
+    pub fn autocomplete_items(&self, prefix: &str, limit: i64) -> SqliteResult<Vec<Item>> {
+        let conn = self.reader.lock().unwrap();
+        let escaped = matching::escape_like(prefix);
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE {} AND (i.item LIKE ?3 ESCAPE '\\' OR i.display_name LIKE ?3 ESCAPE '\\')
+             ORDER BY CASE
+                     WHEN i.item = ?1 THEN 0
+                     WHEN i.item LIKE ?2 ESCAPE '\\' OR i.path LIKE ?2 ESCAPE '\\' THEN 1
+                     WHEN i.display_name LIKE ?2 ESCAPE '\\' THEN 2
+                     ELSE 3
+                 END,
+                 (SELECT COUNT(*) FROM recipe_ingredients ri WHERE ri.item = i.item) DESC,
+                 length(i.item), i.item
+             LIMIT ?4",
+            ITEM_SELECT, VISIBLE_ITEM
+        ))?;
+        let params = rusqlite::params![prefix, format!("{}%", escaped), format!("%{}%", escaped), limit];
+        let items = stmt.query_map(params, Self::item_from_row)?;
+        items.collect()
+    }
+
+    fn item_from_row(row: &rusqlite::Row) -> SqliteResult<Item> {
+        Ok(Item {
+            item: row.get(0)?,
+            namespace: row.get(1)?,
+            path: row.get(2)?,
+            display_name: row.get(3)?,
+            mod_id: row.get(4)?,
+            mod_name: row.get(5)?,
+        })
+    }
+
+    /// Lists component defaults, optionally only those for one item.
+    pub fn list_item_components(&self, item: Option<&str>) -> SqliteResult<Vec<ItemComponentEntry>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT c.item, m.name, c.path, c.nutrition, c.saturation, c.max_stack_size, c.max_damage
+             FROM item_components c
+             JOIN mods m ON c.mod_id = m.id
+             WHERE ?1 IS NULL OR c.item = ?1
+             ORDER BY c.item, m.name, c.path"
+        )?;
+        let entries = stmt.query_map([item], |row| {
+            Ok(ItemComponentEntry {
+                item: row.get(0)?,
+                mod_name: row.get(1)?,
+                path: row.get(2)?,
+                nutrition: row.get(3)?,
+                saturation: row.get(4)?,
+                max_stack_size: row.get(5)?,
+                max_damage: row.get(6)?,
+            })
+        })?;
+        entries.collect()
+    }
+
+    /// Stores every offer found in one trade file.
+    pub fn insert_trades(&self, mod_id: i64, source: &str, trades: &[ParsedTrade]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for trade in trades {
+            tx.execute(
+                "INSERT INTO trades (mod_id, source, profession, level, result_item, result_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                rusqlite::params![
+                    mod_id,
+                    source,
+                    trade.profession,
+                    trade.level,
+                    trade.result.item,
+                    trade.result.count
+                ],
+            )?;
+            let trade_id = tx.last_insert_rowid();
+            for cost in &trade.costs {
+                tx.execute(
+                    "INSERT INTO trade_costs (trade_id, item, count) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![trade_id, cost.item, cost.count],
+                )?;
+            }
+        }
+        tx.commit()
+    }
+
+    pub fn list_trades(&self) -> SqliteResult<Vec<Trade>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} ORDER BY t.profession, t.level, t.result_item",
+            TRADE_SELECT
+        ))?;
+        Self::collect_trades(&conn, &mut stmt, &[])
+    }
+
+    /// Recipes and trades that produce exactly `item`.
+    pub fn get_item_sources(&self, item: &str) -> SqliteResult<ItemSources> {
+        let conn = self.reader.lock().unwrap();
+
+        let mut params = vec![Value::from(item.to_string())];
+        let where_clause = self.recipe_where(vec!["r.result_item = ?1".to_string()], &mut params);
+        let mut stmt = conn.prepare(&format!("{} {} ORDER BY m.name, r.path", RECIPE_SELECT, where_clause))?;
+        let recipes = self.collect_recipes(&conn, &mut stmt, params_from_iter(params))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE t.result_item = ?1 ORDER BY t.profession, t.level",
+            TRADE_SELECT
+        ))?;
+        let trades = Self::collect_trades(&conn, &mut stmt, &[&item])?;
+
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT m.name, x.path FROM item_refs x JOIN mods m ON x.mod_id = m.id
+             WHERE x.item = ?1 AND x.source = ?2
+             ORDER BY m.name, x.path",
+        )?;
+        let loot_tables = stmt
+            .query_map([item, items::LOOT_TABLE], |row| Ok(LootSource { mod_name: row.get(0)?, path: row.get(1)? }))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let blocker = recipes.is_empty() && trades.is_empty() && loot_tables.is_empty();
+        Ok(ItemSources { recipes, trades, loot_tables, blocker })
+    }
+
+    fn collect_trades(
+        conn: &Connection,
+        stmt: &mut rusqlite::Statement,
+        params: &[&dyn rusqlite::ToSql],
+    ) -> SqliteResult<Vec<Trade>> {
+        let mut trades = stmt
+            .query_map(params, |row| {
+                Ok(Trade {
+                    id: row.get(0)?,
+                    mod_name: row.get(1)?,
+                    source: row.get(2)?,
+                    profession: row.get(3)?,
+                    level: row.get(4)?,
+                    costs: Vec::new(),
+                    result_item: row.get(5)?,
+                    result_count: row.get(6)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut costs_stmt = conn.prepare("SELECT item, count FROM trade_costs WHERE trade_id = ?1 ORDER BY id")?;
+        for trade in &mut trades {
+            trade.costs = costs_stmt
+                .query_map([trade.id], |row| {
+                    Ok(IngredientCount {
+                        item: row.get(0)?,
+                        count: row.get(1)?,
+                    })
+                })?
+                .collect::<SqliteResult<_>>()?;
+        }
+        Ok(trades)
+    }
+
+    /// Replaces the config index with a fresh scan.
+    pub fn replace_config_entries(&self, files: &[(String, Vec<ConfigValue>)]) -> SqliteResult<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM config_entries", [])?;
+        let mut count = 0;
+        for (file, values) in files {
+            for value in values {
+                tx.execute(
+                    "INSERT INTO config_entries (file, key, value) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![file, value.key, value.value],
+                )?;
+                count += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Full-text search over config file names, keys and values. Every word
+    /// must match; punctuation such as `:` is treated as a word break.
+    pub fn search_configs(&self, query: &str, limit: i64) -> SqliteResult<Vec<ConfigEntry>> {
+        let match_query = fts_match_query(query);
+        if match_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT file, key, value FROM config_entries
+             WHERE config_entries MATCH ?1
+             ORDER BY rank
+             LIMIT ?2"
+        )?;
+        let entries = stmt.query_map(rusqlite::params![match_query, limit], |row| {
+            Ok(ConfigEntry {
+                file: row.get(0)?,
+                key: row.get(1)?,
+                value: row.get(2)?,
+            })
+        })?;
+        entries.collect()
+    }
+
+    /// Full-text search over recipe paths and raw JSON, so any field or key
+    /// can be found. Uses the same word matching as `search_configs` and
+    /// respects the pinned filter. Hits are ordered by relevance.
+    /// Best matches first, unless `sort` is given.
+    pub fn search_recipes_fulltext(
+        &self,
+        query: &str,
+        limit: i64,
+        sort: Option<RecipeSort>,
+    ) -> SqliteResult<Vec<RecipeSearchHit>> {
+        let match_query = fts_match_query(query);
+        if match_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.reader.lock().unwrap();
+        let mut params = vec![Value::from(match_query)];
+        let where_clause = self.recipe_where(vec!["recipes_fts MATCH ?1".to_string()], &mut params);
+        params.push(Value::from(limit));
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.id, snippet(recipes_fts, 1, '**', '**', '...', 16)
+             FROM recipes_fts
+             JOIN recipes r ON r.id = recipes_fts.rowid
+             JOIN mods m ON m.id = r.mod_id
+             {} ORDER BY {} LIMIT ?{}",
+            where_clause,
+            sort.map_or("rank".to_string(), |sort| sort.order_clause()),
+            params.len()
+        ))?;
+        let matches = stmt
+            .query_map(params_from_iter(params), |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut recipe_stmt = conn.prepare(&format!("{} WHERE r.id = ?1", RECIPE_SELECT))?;
+        let mut hits = Vec::new();
+        for (id, snippet) in matches {
+            if let Some(recipe) = self.collect_recipes(&conn, &mut recipe_stmt, [id])?.pop() {
+                hits.push(RecipeSearchHit { recipe, snippet });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// A recipe by row id. Unlike lists and searches this ignores the pinned
+    /// filter, so a recipe found earlier can always be fetched again.
+    pub fn get_recipe(&self, id: i64) -> SqliteResult<Option<Recipe>> {
+        Ok(self.get_recipes(&[id])?.pop())
+    }
+
+    /// Recipes by row id, in the order asked for. Ids with no recipe are
+    /// left out.
+    pub fn get_recipes(&self, ids: &[i64]) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} WHERE r.id = ?1", RECIPE_SELECT))?;
+        let mut recipes = Vec::new();
+        for id in ids {
+            recipes.extend(self.collect_recipes(&conn, &mut stmt, [id])?);
+        }
+        Ok(recipes)
+    }
+
+    /// Recipes sharing an ingredient or the result with recipe `id`, scored
+    /// by `recipe_diff::similarity`, best first. Respects the pinned filter.
+    pub fn similar_recipes(&self, id: i64, min_score: f64, limit: usize) -> SqliteResult<Vec<SimilarRecipe>> {
+        let Some(target) = self.get_recipe(id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = self.reader.lock().unwrap();
+        let mut params = vec![Value::from(id), Value::from(target.result_item.clone())];
+        let where_clause = self.recipe_where(
+            vec![
+                "r.id != ?1".to_string(),
+                "(r.result_item = ?2 OR r.id IN (SELECT recipe_id FROM recipe_ingredients
+                     WHERE item IN (SELECT item FROM recipe_ingredients WHERE recipe_id = ?1)))"
+                    .to_string(),
+            ],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!("{} {}", RECIPE_SELECT, where_clause))?;
+        let candidates = self.collect_recipes(&conn, &mut stmt, params_from_iter(params))?;
+
+        let mut similar: Vec<SimilarRecipe> = candidates
+            .into_iter()
+            .map(|recipe| {
+                let (score, ingredient_similarity) = recipe_diff::similarity(&target, &recipe);
+                SimilarRecipe {
+                    same_output: target.result_item.is_some() && recipe.result_item == target.result_item,
+                    recipe,
+                    score,
+                    ingredient_similarity,
+                }
+            })
+            .filter(|s| s.score >= min_score)
+            .collect();
+        similar.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.recipe.recipe_id.cmp(&b.recipe.recipe_id)));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+
+    /// Every visible copy of the recipe with this id, one per jar providing
+    /// it.
+    pub fn get_recipe_providers(&self, recipe_id: &str) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE r.recipe_id = ?1 AND {} ORDER BY r.mod_id",
+            RECIPE_SELECT, VISIBLE_RECIPE
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, [recipe_id])
+    }
+
+    /// The copy of a recipe id from the game jar, if that was scanned.
+    pub fn vanilla_copy(&self, recipe_id: &str) -> SqliteResult<Option<Recipe>> {
+        let vanilla = {
+            let conn = self.reader.lock().unwrap();
+            let mut stmt = conn.prepare(
+                "SELECT r.id, m.name, m.loader, m.loader_mod_id FROM recipes r JOIN mods m ON r.mod_id = m.id
+                 WHERE r.recipe_id = ?1 ORDER BY r.id",
+            )?;
+            let rows: Vec<(i64, String, Option<String>, Option<String>)> = stmt
+                .query_map([recipe_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+                .collect::<SqliteResult<_>>()?;
+            rows.into_iter()
+                .find(|(_, name, loader, mod_id)| {
+                    load_order::provider_kind(name, loader.as_deref(), mod_id.as_deref()) == ProviderKind::Vanilla
+                })
+                .map(|(id, ..)| id)
+        };
+        match vanilla {
+            Some(id) => self.get_recipe(id),
+            None => Ok(None),
+        }
+    }
+
+    /// Recipe ids shipped by several jars, with the copy that wins when jars
+    /// load in `load_order` (jar names; see `load_order::winner`). Hidden
+    /// copies are left out.
+    pub fn list_recipe_overrides(&self, load_order: &[String]) -> SqliteResult<Vec<RecipeOverride>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.recipe_id, r.id, r.mod_id, m.name, r.path, m.loader, m.loader_mod_id
+             FROM recipes r
+             JOIN mods m ON r.mod_id = m.id
+             WHERE {visible} AND r.recipe_id IN (
+                 SELECT r.recipe_id FROM recipes r WHERE r.recipe_id != '' AND {visible}
+                 GROUP BY r.recipe_id HAVING COUNT(*) > 1
+             )
+             ORDER BY r.recipe_id, r.mod_id",
+            visible = VISIBLE_RECIPE
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            let mod_name: String = row.get(3)?;
+            let loader: Option<String> = row.get(5)?;
+            let loader_mod_id: Option<String> = row.get(6)?;
+            let kind = load_order::provider_kind(&mod_name, loader.as_deref(), loader_mod_id.as_deref());
+            let provider = RecipeProvider { id: row.get(1)?, mod_id: row.get(2)?, mod_name, path: row.get(4)?, kind };
+            Ok((row.get::<_, String>(0)?, provider, loader_mod_id))
+        })?;
+
+        let mut overrides: Vec<RecipeOverride> = Vec::new();
+        let mut sort_names: Vec<Vec<Option<String>>> = Vec::new();
+        for row in rows {
+            let (recipe_id, provider, loader_mod_id) = row?;
+            match overrides.last_mut() {
+                Some(last) if last.recipe_id == recipe_id => {
+                    last.providers.push(provider);
+                    sort_names.last_mut().unwrap().push(loader_mod_id);
+                }
+                _ => {
+                    overrides.push(RecipeOverride { recipe_id, providers: vec![provider], winner: None });
+                    sort_names.push(vec![loader_mod_id]);
+                }
+            }
+        }
+        for (entry, names) in overrides.iter_mut().zip(&sort_names) {
+            let entries: Vec<LoadEntry> = entry
+                .providers
+                .iter()
+                .zip(names)
+                .map(|(p, name)| LoadEntry {
+                    kind: p.kind,
+                    jar_name: &p.mod_name,
+                    sort_name: name.as_deref().unwrap_or(&p.mod_name),
+                })
+                .collect();
+            entry.winner = load_order::winner(&entries, load_order).map(|i| entry.providers[i].id);
+        }
+        Ok(overrides)
+    }
+
+    pub fn search_by_output(&self, item: &str, mode: MatchMode, sort: RecipeSort) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let condition = matching::condition("r.result_item", item, mode, &mut params);
+        let where_clause = self.recipe_where(vec![condition], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {}",
+            RECIPE_SELECT,
+            where_clause,
+            sort.order_clause()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    /// Visible recipes making exactly `item`, by recipe id. Unlike
+    /// `search_by_output` this ignores the pinned filter, since what an item
+    /// takes to make does not depend on what the user is browsing.
+    pub fn recipes_making(&self, item: &str) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE r.result_item = ?1 AND {} ORDER BY {}",
+            RECIPE_SELECT,
+            VISIBLE_RECIPE,
+            RecipeSort::by(SortKey::RecipeId).order_clause()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, [item])
+    }
+
+    /// Recipes using a matching ingredient. With `expand_tags`, recipes whose
+    /// tag ingredients contain a matching item are included.
+    pub fn search_by_ingredient(
+        &self,
+        item: &str,
+        mode: MatchMode,
+        expand_tags: bool,
+        sort: RecipeSort,
+    ) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let condition = ingredient_condition(item, mode, expand_tags, &mut params);
+        let where_clause = self.recipe_where(vec![condition], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {}",
+            RECIPE_SELECT,
+            where_clause,
+            sort.order_clause()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    /// Recipes whose every item and fluid input is in `items`, or in a tag
+    /// holding one of them. Amounts are not checked.
+    pub fn craftable_recipes(&self, items: &[String], sort: RecipeSort) -> SqliteResult<Vec<Recipe>> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.reader.lock().unwrap();
+        let mut params: Vec<Value> = items.iter().map(|item| Value::from(item.clone())).collect();
+        let values = (1..=items.len()).map(|n| format!("(?{})", n)).collect::<Vec<_>>().join(", ");
+        let conditions = vec![
+            "EXISTS (SELECT 1 FROM recipe_ingredients i WHERE i.recipe_id = r.id)".to_string(),
+            "NOT EXISTS (SELECT 1 FROM recipe_ingredients i
+                         WHERE i.recipe_id = r.id AND i.item NOT IN (SELECT item FROM have))".to_string(),
+            "NOT EXISTS (SELECT 1 FROM recipe_fluids f
+                         WHERE f.recipe_id = r.id AND f.is_output = 0 AND f.fluid NOT IN (SELECT item FROM have))".to_string(),
+        ];
+        let where_clause = self.recipe_where(conditions, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE have(item) AS (
+                 VALUES {}
+                 UNION
+                 SELECT '#' || t.tag FROM item_tags t JOIN have h ON t.entry = h.item
+             )
+             {} {} ORDER BY {}",
+            values,
+            RECIPE_SELECT,
+            where_clause,
+            sort.order_clause()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    /// Up to `limit` recipes with row ids above `after_id`, in id order,
+    /// hidden ones included and the pinned filter ignored. For walking the
+    /// whole table in batches.
+    pub fn recipes_after(&self, after_id: i64, limit: i64) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!("{} WHERE r.id > ?1 ORDER BY r.id LIMIT ?2", RECIPE_SELECT))?;
+        self.collect_recipes(&conn, &mut stmt, [after_id, limit])
+    }
+
+    pub fn search_recipes(&self, search: &RecipeSearch) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let conditions = search.conditions(&mut params);
+        let where_clause = self.recipe_where_including(conditions, &mut params, search.include_hidden);
+
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {} LIMIT ?{} OFFSET ?{}",
+            RECIPE_SELECT,
+            where_clause,
+            search.sort.order_clause(),
+            params.len() + 1,
+            params.len() + 2
+        ))?;
+        params.push(Value::from(search.limit.unwrap_or(200)));
+        params.push(Value::from(search.offset));
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    /// A page of recipes matching `search` in its sort order, starting after
+    /// `cursor`. Unlike offsets, cursors keep their place when recipes are
+    /// added or removed mid-scroll. The search's offset is ignored.
+    pub fn search_recipes_page(&self, search: &RecipeSearch, cursor: Option<&RecipeCursor>) -> SqliteResult<RecipePage> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let mut conditions = search.conditions(&mut params);
+        let key = search.sort.keyset_key();
+        let (direction, after) = if search.sort.descending { ("DESC", "<") } else { ("ASC", ">") };
+        if let Some(cursor) = cursor {
+            params.push(cursor.key_param());
+            params.push(Value::from(cursor.id));
+            conditions.push(format!("({}, r.id) {} (?{}, ?{})", key, after, params.len() - 1, params.len()));
+        }
+        let where_clause = self.recipe_where_including(conditions, &mut params, search.include_hidden);
+        let limit = search.limit.unwrap_or(200).max(1);
+        // One extra row tells whether another page follows
+        params.push(Value::from(limit + 1));
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {} {dir}, r.id {dir} LIMIT ?{}",
+            RECIPE_SELECT,
+            where_clause,
+            key,
+            params.len(),
+            dir = direction
+        ))?;
+        let mut recipes = self.collect_recipes(&conn, &mut stmt, params_from_iter(params))?;
+        let next_cursor = match recipes.len() as i64 > limit {
+            true => {
+                recipes.truncate(limit as usize);
+                recipes.last().map(|last| RecipeCursor { key: search.sort.key_of(last), id: last.id }.encode())
+            }
+            false => None,
+        };
+        Ok(RecipePage { recipes, next_cursor })
+    }
+
+    /// Counts of every recipe matching `search`, by type, mod and namespace;
+    /// its sort, offset and limit are ignored.
+    pub fn recipe_facets(&self, search: &RecipeSearch) -> SqliteResult<SearchFacets> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let conditions = search.conditions(&mut params);
+        let where_clause = self.recipe_where_including(conditions, &mut params, search.include_hidden);
+
+        let counts = |column: &str| -> SqliteResult<Vec<CategoryCount>> {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT {column}, COUNT(*) FROM recipes r {where_clause} GROUP BY 1 ORDER BY 2 DESC, 1"
+            ))?;
+            let rows = stmt.query_map(params_from_iter(&params), |row| {
+                Ok(CategoryCount { name: row.get(0)?, count: row.get(1)? })
+            })?;
+            rows.collect()
+        };
+        let recipe_types = counts("r.canonical_type")?;
+        let namespaces = counts("r.namespace")?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.mod_id, m.name, COUNT(*) FROM recipes r JOIN mods m ON r.mod_id = m.id
+             {where_clause} GROUP BY r.mod_id ORDER BY 3 DESC, m.name"
+        ))?;
+        let mods = stmt
+            .query_map(params_from_iter(&params), |row| {
+                Ok(ModFacet { mod_id: row.get(0)?, name: row.get(1)?, count: row.get(2)? })
+            })?
+            .collect::<SqliteResult<_>>()?;
+
+        Ok(SearchFacets { recipe_types, mods, namespaces })
     }
 
-    pub fn search_by_output(&self, item: &str) -> SqliteResult<Vec<Recipe>> {
-        let conn = self.conn.lock().unwrap();
-        let search_term = format!("%{}%", item);
-        let mut stmt = conn.prepare(
-            "SELECT r.id, m.name, r.path, r.recipe_type, r.result_item, r.result_count, r.raw_json
-             FROM recipes r
-             JOIN mods m ON r.mod_id = m.id
-             WHERE r.result_item LIKE ?1
-             ORDER BY r.result_item, m.name"
-        )?;
-        self.collect_recipes(&conn, &mut stmt, &[&search_term])
+    /// Up to `count` recipes picked at random from those matching `search`;
+    /// its sort, offset and limit are ignored.
+    pub fn random_recipes(&self, search: &RecipeSearch, count: i64) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let conditions = search.conditions(&mut params);
+        let where_clause = self.recipe_where_including(conditions, &mut params, search.include_hidden);
+        params.push(Value::from(count));
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY random() LIMIT ?{}",
+            RECIPE_SELECT,
+            where_clause,
+            params.len()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
     }
 
-    pub fn search_by_ingredient(&self, item: &str) -> SqliteResult<Vec<Recipe>> {
-        let conn = self.conn.lock().unwrap();
-        let search_term = format!("%{}%", item);
+    /// Recipes matching a parsed query-language expression, on top of the
+    /// pinned filter.
+    pub fn query_recipes(&self, query: &Query, offset: i64, limit: i64, sort: RecipeSort) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let condition = query.to_sql(&mut params);
+        let where_clause = self.recipe_where(vec![condition], &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {} LIMIT ?{} OFFSET ?{}",
+            RECIPE_SELECT,
+            where_clause,
+            sort.order_clause(),
+            params.len() + 1,
+            params.len() + 2
+        ))?;
+        params.push(Value::from(limit));
+        params.push(Value::from(offset));
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    /// Every recipe the mod's jar ships, hidden ones included and without
+    /// the pinned filter, so the mod's page shows all it contributes.
+    pub fn list_recipes_by_mod(
+        &self,
+        mod_id: i64,
+        offset: i64,
+        limit: i64,
+        sort: RecipeSort,
+    ) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE r.mod_id = ?1 ORDER BY {} LIMIT ?2 OFFSET ?3",
+            RECIPE_SELECT,
+            sort.order_clause()
+        ))?;
+        self.collect_recipes(&conn, &mut stmt, rusqlite::params![mod_id, limit, offset])
+    }
+
+    pub fn list_recipes(&self, offset: i64, limit: i64, sort: RecipeSort) -> SqliteResult<Vec<Recipe>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(Vec::new(), &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY {} LIMIT ?{} OFFSET ?{}",
+            RECIPE_SELECT,
+            where_clause,
+            sort.order_clause(),
+            params.len() + 1,
+            params.len() + 2
+        ))?;
+        params.push(Value::from(limit));
+        params.push(Value::from(offset));
+        self.collect_recipes(&conn, &mut stmt, params_from_iter(params))
+    }
+
+    pub fn browse_fluid_conversions(&self) -> SqliteResult<Vec<FluidConversion>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(
+            vec!["r.id IN (SELECT recipe_id FROM recipe_fluids)".to_string()],
+            &mut params,
+        );
+        let mut stmt = conn.prepare(&format!(
+            "{} {} ORDER BY r.canonical_type, r.path",
+            RECIPE_SELECT, where_clause
+        ))?;
+        let recipes = self.collect_recipes(&conn, &mut stmt, params_from_iter(params))?;
+
+        let mut conversions = Vec::new();
+        for recipe in recipes {
+            let (fluid_outputs, fluid_inputs) = self
+                .get_fluids_for_recipe(&conn, recipe.id)?
+                .into_iter()
+                .partition(|f| f.is_output);
+            conversions.push(FluidConversion {
+                recipe,
+                fluid_inputs,
+                fluid_outputs,
+                feeds_into: Vec::new(),
+            });
+        }
+
+        // Link each producer to the recipes consuming any fluid it outputs,
+        // directly or through a fluid tag such as `#c:water`
+        let tag_fluids = Self::fluid_tag_members(&conn)?;
+        let mut consumers: HashMap<String, Vec<i64>> = HashMap::new();
+        for conversion in &conversions {
+            for input in &conversion.fluid_inputs {
+                let members = tag_fluids.get(&input.fluid).into_iter().flatten();
+                for fluid in std::iter::once(&input.fluid).chain(members) {
+                    consumers.entry(fluid.clone()).or_default().push(conversion.recipe.id);
+                }
+            }
+        }
+        for conversion in &mut conversions {
+            let mut ids: Vec<i64> = conversion.fluid_outputs
+                .iter()
+                .filter_map(|o| consumers.get(&o.fluid))
+                .flatten()
+                .copied()
+                .collect();
+            ids.sort();
+            ids.dedup();
+            conversion.feeds_into = ids;
+        }
+
+        Ok(conversions)
+    }
+
+    /// Fluids in each fluid tag, keyed `#namespace:path` and following
+    /// nested tags.
+    fn fluid_tag_members(conn: &Connection) -> SqliteResult<HashMap<String, Vec<String>>> {
         let mut stmt = conn.prepare(
-            "SELECT DISTINCT r.id, m.name, r.path, r.recipe_type, r.result_item, r.result_count, r.raw_json
-             FROM recipes r
-             JOIN mods m ON r.mod_id = m.id
-             JOIN recipe_ingredients ri ON r.id = ri.recipe_id
-             WHERE ri.item LIKE ?1
-             ORDER BY r.result_item, m.name"
+            "WITH RECURSIVE members(tag, fluid) AS (
+                 SELECT '#' || tag, entry FROM fluid_tags
+                 UNION
+                 SELECT m.tag, t.entry FROM members m JOIN fluid_tags t ON m.fluid = '#' || t.tag
+             )
+             SELECT tag, fluid FROM members WHERE fluid NOT LIKE '#%'",
         )?;
-        self.collect_recipes(&conn, &mut stmt, &[&search_term])
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        let mut members: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (tag, fluid) = row?;
+            members.entry(tag).or_default().push(fluid);
+        }
+        Ok(members)
     }
 
-    pub fn list_recipes(&self, offset: i64, limit: i64) -> SqliteResult<Vec<Recipe>> {
-        let conn = self.conn.lock().unwrap();
+    fn get_fluids_for_recipe(&self, conn: &Connection, recipe_id: i64) -> SqliteResult<Vec<FluidAmount>> {
         let mut stmt = conn.prepare(
-            "SELECT r.id, m.name, r.path, r.recipe_type, r.result_item, r.result_count, r.raw_json
-             FROM recipes r
-             JOIN mods m ON r.mod_id = m.id
-             ORDER BY m.name, r.path
-             LIMIT ?1 OFFSET ?2"
+            "SELECT fluid, amount, is_output FROM recipe_fluids WHERE recipe_id = ?1 ORDER BY fluid"
         )?;
-        self.collect_recipes(&conn, &mut stmt, &[&limit, &offset])
+
+        let fluids = stmt.query_map([recipe_id], |row| {
+            Ok(FluidAmount {
+                fluid: row.get(0)?,
+                amount: row.get(1)?,
+                is_output: row.get(2)?,
+            })
+        })?;
+        fluids.collect()
     }
 
     fn collect_recipes(
         &self,
         conn: &Connection,
         stmt: &mut rusqlite::Statement,
-        params: &[&dyn rusqlite::ToSql],
+        params: impl rusqlite::Params,
     ) -> SqliteResult<Vec<Recipe>> {
-        let recipe_rows = stmt.query_map(params, |row| {
-            Ok((
-                row.get::<_, i64>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, Option<String>>(4)?,
-                row.get::<_, Option<i32>>(5)?,
-                row.get::<_, String>(6)?,
-            ))
-        })?;
+        let mut recipes = stmt
+            .query_map(params, |row| {
+                Ok(Recipe {
+                    id: row.get(0)?,
+                    mod_id: row.get(1)?,
+                    mod_name: row.get(2)?,
+                    path: row.get(3)?,
+                    recipe_id: row.get(4)?,
+                    recipe_type: row.get(5)?,
+                    result_item: row.get(6)?,
+                    result_count: row.get(7)?,
+                    ingredients: Vec::new(),
+                    raw_json: row.get(8)?,
+                    machine: row.get(9)?,
+                    owner_mod_id: row.get(10)?,
+                    owner_mod_name: row.get(11)?,
+                    canonical_type: row.get(12)?,
+                    provider_count: row.get(13)?,
+                    hidden: row.get(14)?,
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
 
-        let mut recipes = Vec::new();
-        for row in recipe_rows {
-            let (id, mod_name, path, recipe_type, result_item, result_count, raw_json) = row?;
-            let ingredients = self.get_ingredients_for_recipe(conn, id)?;
-            recipes.push(Recipe {
-                id,
-                mod_name,
-                path,
-                recipe_type,
-                result_item,
-                result_count,
-                ingredients,
-                raw_json,
-            });
+        for recipe in &mut recipes {
+            recipe.ingredients = self.get_ingredients_for_recipe(conn, recipe.id)?;
         }
         Ok(recipes)
     }
 
+    /// Keeps only the category of each error from the latest extraction of
+    /// the jar or data pack at `path`, replacing what its earlier extraction
+    /// recorded.
+    pub fn record_extraction_errors(&self, path: &str, errors: &[String]) -> SqliteResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM extraction_errors WHERE path = ?1", [path])?;
+        for error in errors {
+            tx.execute(
+                "INSERT INTO extraction_errors (path, category) VALUES (?1, ?2)",
+                rusqlite::params![path, stats::error_category(error)],
+            )?;
+        }
+        tx.commit()
+    }
+
+    pub fn get_mod_count(&self) -> SqliteResult<i64> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM mods", [], |row| row.get(0))
+    }
+
+    /// Recipes per canonical type, under the pinned filter.
+    pub fn recipe_type_counts(&self) -> SqliteResult<Vec<CategoryCount>> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(Vec::new(), &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT r.canonical_type, COUNT(*) FROM recipes r {}
+             GROUP BY r.canonical_type ORDER BY COUNT(*) DESC, r.canonical_type",
+            where_clause
+        ))?;
+        Self::collect_category_counts(&mut stmt, params)
+    }
+
+    pub fn error_category_counts(&self) -> SqliteResult<Vec<CategoryCount>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT category, COUNT(*) FROM extraction_errors GROUP BY category ORDER BY COUNT(*) DESC, category"
+        )?;
+        Self::collect_category_counts(&mut stmt, Vec::new())
+    }
+
+    fn collect_category_counts(stmt: &mut rusqlite::Statement, params: Vec<Value>) -> SqliteResult<Vec<CategoryCount>> {
+        let counts = stmt.query_map(params_from_iter(params), |row| {
+            Ok(CategoryCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+        counts.collect()
+    }
+
+    pub fn parser_coverage(&self) -> SqliteResult<ParserCoverage> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*),
+                    COUNT(result_item),
+                    SUM(EXISTS (SELECT 1 FROM recipe_ingredients i WHERE i.recipe_id = r.id)),
+                    SUM(EXISTS (SELECT 1 FROM recipe_fluids f WHERE f.recipe_id = r.id))
+             FROM recipes r",
+            [],
+            |row| {
+                Ok(ParserCoverage {
+                    recipes: row.get(0)?,
+                    with_result: row.get(1)?,
+                    with_ingredients: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                    with_fluids: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                })
+            },
+        )
+    }
+
+    /// Assigns each namespace to the mod declaring that mod id, or failing
+    /// that, the mod shipping the most recipes in it.
+    pub fn rebuild_namespace_owners(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch(
+            "DELETE FROM namespaces;
+             INSERT INTO namespaces (namespace, mod_id)
+             SELECT namespace, mod_id FROM (
+                 SELECT namespace, mod_id,
+                        ROW_NUMBER() OVER (PARTITION BY namespace ORDER BY declared DESC, files DESC, mod_id) AS pick
+                 FROM (
+                     SELECT namespace, mod_id, 0 AS declared, COUNT(*) AS files
+                     FROM recipes
+                     WHERE namespace != ''
+                     GROUP BY namespace, mod_id
+                     UNION ALL
+                     SELECT loader_mod_id, id, 1, 0 FROM mods WHERE loader_mod_id IS NOT NULL
+                 )
+             )
+             WHERE pick = 1;"
+        )
+    }
+
+    pub fn list_namespaces(&self) -> SqliteResult<Vec<NamespaceOwner>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT ns.namespace, ns.mod_id, m.name,
+                    (SELECT COUNT(*) FROM recipes r WHERE r.namespace = ns.namespace AND {})
+             FROM namespaces ns
+             JOIN mods m ON ns.mod_id = m.id AND m.hidden = 0
+             ORDER BY ns.namespace",
+            VISIBLE_RECIPE
+        ))?;
+        let owners = stmt.query_map([], |row| {
+            Ok(NamespaceOwner {
+                namespace: row.get(0)?,
+                mod_id: row.get(1)?,
+                mod_name: row.get(2)?,
+                recipe_count: row.get(3)?,
+            })
+        })?;
+        owners.collect()
+    }
+
     pub fn get_recipe_count(&self) -> SqliteResult<i64> {
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let where_clause = self.recipe_where(Vec::new(), &mut params);
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM recipes r {}", where_clause),
+            params_from_iter(params),
+            |row| row.get(0),
+        )
+    }
+
+    /// Recipe ids starting with `prefix`, for collision checks.
+    pub fn recipe_ids_starting_with(&self, prefix: &str) -> SqliteResult<HashSet<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT recipe_id FROM recipes WHERE substr(recipe_id, 1, length(?1)) = ?1"
+        )?;
+        let ids = stmt.query_map([prefix], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    pub fn get_setting(&self, key: &str) -> SqliteResult<Option<String>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    /// The type of each recipe id, from its first copy. Ids no recipe has
+    /// are left out.
+    pub fn recipe_types_by_id(&self, recipe_ids: &[String]) -> SqliteResult<HashMap<String, String>> {
+        if recipe_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let conn = self.reader.lock().unwrap();
+        let mut params = Vec::new();
+        let list = placeholders(recipe_ids, &mut params);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT recipe_id, canonical_type FROM recipes WHERE recipe_id IN ({}) ORDER BY id DESC",
+            list
+        ))?;
+        let types = stmt.query_map(params_from_iter(params), |row| Ok((row.get(0)?, row.get(1)?)))?;
+        types.collect()
+    }
+
+    /// Recipe ids whose note has `status`, ignoring case.
+    pub fn recipe_ids_with_status(&self, status: &str) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT recipe_id FROM recipe_notes WHERE LOWER(status) = LOWER(?1) ORDER BY recipe_id")?;
+        let ids = stmt.query_map([status], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    pub fn get_recipe_note(&self, recipe_id: &str) -> SqliteResult<Option<RecipeNote>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row(
+            "SELECT recipe_id, note, status, updated_at FROM recipe_notes WHERE recipe_id = ?1",
+            [recipe_id],
+            |row| {
+                Ok(RecipeNote {
+                    recipe_id: row.get(0)?,
+                    note: row.get(1)?,
+                    status: row.get(2)?,
+                    updated_at: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Saves the note for a recipe id, or removes it when both the note and
+    /// the status are empty.
+    pub fn set_recipe_note(&self, recipe_id: &str, note: &str, status: Option<&str>) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let status = status.map(str::trim).filter(|s| !s.is_empty());
+        if note.trim().is_empty() && status.is_none() {
+            conn.execute("DELETE FROM recipe_notes WHERE recipe_id = ?1", [recipe_id])?;
+        } else {
+            conn.execute(
+                "INSERT OR REPLACE INTO recipe_notes (recipe_id, note, status, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![recipe_id, note, status, chrono_lite_now()],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn create_collection(&self, name: &str) -> SqliteResult<i64> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO collections (name, created_at) VALUES (?1, ?2)",
+            [name, &chrono_lite_now()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Deletes a collection and its bookmarks. Returns false if there was no
+    /// such collection.
+    pub fn delete_collection(&self, collection_id: i64) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM collections WHERE id = ?1", [collection_id])? > 0)
+    }
+
+    pub fn list_collections(&self) -> SqliteResult<Vec<Collection>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.name, COUNT(e.target), c.created_at
+             FROM collections c
+             LEFT JOIN collection_entries e ON e.collection_id = c.id
+             GROUP BY c.id
+             ORDER BY c.name COLLATE NOCASE"
+        )?;
+        let collections = stmt.query_map([], Self::collection_from_row)?;
+        collections.collect()
+    }
+
+    fn collection_from_row(row: &rusqlite::Row) -> SqliteResult<Collection> {
+        Ok(Collection {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            entry_count: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+
+    /// Bookmarks a recipe or item id. `kind` is `BOOKMARK_RECIPE` or
+    /// `BOOKMARK_ITEM`; adding the same bookmark twice is a no-op.
+    pub fn add_to_collection(&self, collection_id: i64, kind: &str, target: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO collection_entries (collection_id, kind, target, added_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![collection_id, kind, target, chrono_lite_now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_from_collection(&self, collection_id: i64, kind: &str, target: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        let removed = conn.execute(
+            "DELETE FROM collection_entries WHERE collection_id = ?1 AND kind = ?2 AND target = ?3",
+            rusqlite::params![collection_id, kind, target],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// Recipe ids bookmarked in a collection, including ones the current
+    /// extraction lacks.
+    pub fn collection_recipe_ids(&self, collection_id: i64) -> SqliteResult<Vec<String>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT target FROM collection_entries WHERE collection_id = ?1 AND kind = ?2 ORDER BY target",
+        )?;
+        let ids = stmt.query_map(rusqlite::params![collection_id, BOOKMARK_RECIPE], |row| row.get(0))?;
+        ids.collect()
+    }
+
+    /// A collection with its bookmarks resolved against the current
+    /// extraction, or None if there is no such collection.
+    pub fn get_collection(&self, collection_id: i64) -> SqliteResult<Option<CollectionContents>> {
+        let conn = self.reader.lock().unwrap();
+        let collection = conn
+            .query_row(
+                "SELECT c.id, c.name, (SELECT COUNT(*) FROM collection_entries WHERE collection_id = c.id), c.created_at
+                 FROM collections c WHERE c.id = ?1",
+                [collection_id],
+                Self::collection_from_row,
+            )
+            .optional()?;
+        let Some(collection) = collection else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE r.recipe_id IN (
+                 SELECT target FROM collection_entries WHERE collection_id = ?1 AND kind = 'recipe'
+             )
+             ORDER BY r.recipe_id, r.mod_id",
+            RECIPE_SELECT
+        ))?;
+        let recipes = self.collect_recipes(&conn, &mut stmt, [collection_id])?;
+
+        let mut stmt = conn.prepare(&format!(
+            "{} WHERE i.item IN (
+                 SELECT target FROM collection_entries WHERE collection_id = ?1 AND kind = 'item'
+             )
+             ORDER BY i.item",
+            ITEM_SELECT
+        ))?;
+        let items = stmt.query_map([collection_id], Self::item_from_row)?.collect::<SqliteResult<_>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT target FROM collection_entries e
+             WHERE collection_id = ?1 AND CASE kind
+                 WHEN 'recipe' THEN NOT EXISTS (SELECT 1 FROM recipes WHERE recipe_id = e.target)
+                 ELSE NOT EXISTS (SELECT 1 FROM items WHERE item = e.target)
+             END
+             ORDER BY target"
+        )?;
+        let missing = stmt.query_map([collection_id], |row| row.get(0))?.collect::<SqliteResult<_>>()?;
+
+        Ok(Some(CollectionContents { collection, recipes, items, missing }))
+    }
+
+    /// Stores a saved search definition, replacing one with the same name.
+    pub fn save_search(&self, name: &str, definition: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO saved_searches (name, definition, updated_at) VALUES (?1, ?2, ?3)",
+            [name, definition, &chrono_lite_now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_saved_search(&self, name: &str) -> SqliteResult<Option<String>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row("SELECT definition FROM saved_searches WHERE name = ?1", [name], |row| row.get(0))
+            .optional()
+    }
+
+    /// Names and definitions of the saved searches, by name.
+    pub fn list_saved_searches(&self) -> SqliteResult<Vec<(String, String)>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT name, definition FROM saved_searches ORDER BY name")?;
+        let definitions = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        definitions.collect()
+    }
+
+    pub fn delete_saved_search(&self, name: &str) -> SqliteResult<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM saved_searches WHERE name = ?1", [name])? > 0)
+    }
+
+    /// Appends a search to the history, dropping the oldest entries past
+    /// `SEARCH_HISTORY_LIMIT`.
+    pub fn record_search(&self, definition: &str, result_count: i64) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO search_history (searched_at, definition, result_count) VALUES (?1, ?2, ?3)",
+            rusqlite::params![chrono_lite_now(), definition, result_count],
+        )?;
+        conn.execute(
+            "DELETE FROM search_history WHERE id <= (SELECT MAX(id) FROM search_history) - ?1",
+            [SEARCH_HISTORY_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_search_history(&self, limit: i64) -> SqliteResult<Vec<SearchHistoryRow>> {
+        let conn = self.reader.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, searched_at, definition, result_count FROM search_history ORDER BY id DESC LIMIT ?1"
+        )?;
+        let rows = stmt.query_map([limit], |row| {
+            Ok(SearchHistoryRow {
+                id: row.get(0)?,
+                searched_at: row.get(1)?,
+                definition: row.get(2)?,
+                result_count: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_search_history_entry(&self, id: i64) -> SqliteResult<Option<String>> {
+        let conn = self.reader.lock().unwrap();
+        conn.query_row("SELECT definition FROM search_history WHERE id = ?1", [id], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn clear_search_history(&self) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
-        conn.query_row("SELECT COUNT(*) FROM recipes", [], |row| row.get(0))
+        conn.execute("DELETE FROM search_history", [])?;
+        Ok(())
+    }
+
+    pub fn get_pinned_filter(&self) -> PinnedFilter {
+        self.pinned_filter.lock().unwrap().clone()
+    }
+
+    pub fn set_pinned_filter(&self, filter: PinnedFilter) {
+        *self.pinned_filter.lock().unwrap() = filter;
+    }
+
+    /// Combines a query's own conditions on `recipes r` with the pinned
+    /// filter's, leaving out hidden recipes. Filter values are appended to
+    /// `params` and numbered after the ones already there.
+    fn recipe_where(&self, conditions: Vec<String>, params: &mut Vec<Value>) -> String {
+        self.recipe_where_including(conditions, params, false)
     }
 
-    fn get_ingredients_for_recipe(&self, conn: &Connection, recipe_id: i64) -> SqliteResult<Vec<String>> {
+    /// `recipe_where`, keeping hidden recipes when `include_hidden` is set.
+    fn recipe_where_including(&self, mut conditions: Vec<String>, params: &mut Vec<Value>, include_hidden: bool) -> String {
+        let filter = self.get_pinned_filter();
+
+        if let Some(namespace) = filter.namespace {
+            params.push(Value::from(namespace));
+            conditions.push(format!("r.namespace = ?{}", params.len()));
+        }
+        if let Some(recipe_type) = filter.recipe_type {
+            params.push(Value::from(recipe_parser::canonical_recipe_type(&recipe_type)));
+            conditions.push(format!("r.canonical_type = ?{}", params.len()));
+        }
+        if let Some(mod_id) = filter.mod_id {
+            params.push(Value::from(mod_id));
+            conditions.push(format!("r.mod_id = ?{}", params.len()));
+        }
+        if !include_hidden {
+            conditions.push(VISIBLE_RECIPE.to_string());
+        }
+
+        if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        }
+    }
+
+    fn get_ingredients_for_recipe(&self, conn: &Connection, recipe_id: i64) -> SqliteResult<Vec<IngredientCount>> {
         let mut stmt = conn.prepare(
-            "SELECT DISTINCT item FROM recipe_ingredients WHERE recipe_id = ?1 ORDER BY item"
+            "SELECT item, count FROM recipe_ingredients WHERE recipe_id = ?1 ORDER BY item"
         )?;
 
-        let items = stmt.query_map([recipe_id], |row| row.get(0))?;
+        let items = stmt.query_map([recipe_id], |row| {
+            Ok(IngredientCount {
+                item: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
         items.collect()
     }
 }
 
+/// Splits a comma-joined list column back into its entries.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Turns user input into an FTS5 query where every word must match. Words
+/// are quoted so punctuation such as `:` is a word break, not syntax.
+fn fts_match_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn open_read_only(path: &Path) -> SqliteResult<Connection> {
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    matching::register_functions(&conn)?;
+    Ok(conn)
+}
+
 // Simple timestamp without external dependency
 fn chrono_lite_now() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -240,3 +4425,377 @@ fn chrono_lite_now() -> String {
         .unwrap_or_default();
     format!("{}", duration.as_secs())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{add_shapeless, temp_db, STICK};
+
+    fn add_recipe(db: &Database, mod_id: i64, recipe_id: &str) {
+        let path = format!("data/{}.json", recipe_id.replace(':', "/recipes/"));
+        db.insert_recipe(mod_id, &path, recipe_id, STICK, &recipe_parser::parse_recipe(STICK).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_record_extraction_errors_per_jar() {
+        let db = temp_db();
+        db.record_extraction_errors("a.jar", &["a.jar:data/a/x.json: Invalid JSON: eof".to_string()]).unwrap();
+        db.record_extraction_errors("b.jar", &["b.jar:data/b/y.json: Missing result".to_string()]).unwrap();
+        db.record_extraction_errors("a.jar", &[]).unwrap();
+        let counts = db.error_category_counts().unwrap();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].name, "Missing result");
+    }
+
+    #[test]
+    fn test_replace_extracted() {
+        let db = temp_db();
+        let old = db.insert_mod("old.jar", "/mods/old.jar", None).unwrap();
+        add_recipe(&db, old, "old:stick");
+        db.set_setting("kept", "yes").unwrap();
+
+        let staging = temp_db();
+        let new = staging.insert_mod("new.jar", "/mods/new.jar", None).unwrap();
+        add_recipe(&staging, new, "new:stick");
+        let staging_path = staging.path.clone();
+        drop(staging);
+
+        db.replace_extracted(&staging_path).unwrap();
+        assert_eq!(db.get_recipe_count().unwrap(), 1);
+        assert!(db.recipe_ids_starting_with("new:").unwrap().contains("new:stick"));
+        assert_eq!(db.search_recipes_fulltext("old", 10, None).unwrap().len(), 0);
+        assert_eq!(db.search_recipes_fulltext("new", 10, None).unwrap().len(), 1);
+        assert_eq!(db.get_setting("kept").unwrap().as_deref(), Some("yes"));
+    }
+
+    #[test]
+    fn test_remove_mod_drops_its_items() {
+        let db = temp_db();
+        let kept = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let removed = db.insert_mod("thermal.jar", "/mods/thermal.jar", None).unwrap();
+        db.insert_item_names(kept, "en_us", &[("create:cog".to_string(), "Cogwheel".to_string())]).unwrap();
+        db.insert_item_names(removed, "en_us", &[("thermal:gear".to_string(), "Gear".to_string())]).unwrap();
+        db.record_extraction_errors("/mods/thermal.jar", &["thermal.jar:x.json: Invalid JSON: eof".to_string()]).unwrap();
+        db.rebuild_items().unwrap();
+        assert_eq!(db.list_items(None).unwrap().len(), 2);
+
+        assert!(db.remove_mod(removed).unwrap());
+        db.rebuild_items().unwrap();
+        let items: Vec<String> = db.list_items(None).unwrap().into_iter().map(|i| i.item).collect();
+        assert_eq!(items, vec!["create:cog".to_string()]);
+        assert!(db.error_category_counts().unwrap().is_empty());
+        assert!(!db.remove_mod(removed).unwrap());
+    }
+
+    #[test]
+    fn test_full_text_index_follows_recipes() {
+        let db = temp_db();
+        let fts_rows = |db: &Database| -> i64 {
+            db.conn.lock().unwrap().query_row("SELECT COUNT(*) FROM recipes_fts", [], |row| row.get(0)).unwrap()
+        };
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        add_recipe(&db, create, "create:stick");
+        add_recipe(&db, create, "create:stick");
+        assert_eq!(fts_rows(&db), 1);
+
+        let other = db.insert_mod("other.jar", "/mods/other.jar", None).unwrap();
+        add_recipe(&db, other, "other:stick");
+        db.remove_mod(create).unwrap();
+        assert_eq!(fts_rows(&db), 1);
+        db.remove_mods_at("/mods/other.jar").unwrap();
+        assert_eq!(fts_rows(&db), 0);
+    }
+
+    #[test]
+    fn test_old_snapshots_are_dropped() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        add_recipe(&db, create, "create:cog");
+        let sessions: Vec<i64> = (0..SNAPSHOT_LIMIT + 2)
+            .map(|_| {
+                let session = db.start_session(None, 1).unwrap();
+                db.finish_session(session, Duration::ZERO, 0, 1).unwrap();
+                session
+            })
+            .collect();
+        assert!(db.session_snapshot(sessions[1]).unwrap().is_none());
+        assert!(db.session_snapshot(sessions[2]).unwrap().is_some());
+        assert_eq!(db.session_snapshot(sessions[sessions.len() - 1]).unwrap().unwrap().recipes.len(), 1);
+    }
+
+    #[test]
+    fn test_query_output_tag() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        add_recipe(&db, create, "create:stick");
+        db.insert_tag_entries(create, "c:wooden_rods", &["minecraft:stick".to_string()]).unwrap();
+        db.insert_tag_entries(create, "c:rods", &["#c:wooden_rods".to_string()]).unwrap();
+        let found = |text: &str| {
+            let query = crate::recipe_query::parse(text).unwrap();
+            db.query_recipes(&query, 0, 10, RecipeSort::default()).unwrap().len()
+        };
+        assert_eq!(found("output:#c:rods"), 1);
+        assert_eq!(found("output:#c:ingots"), 0);
+    }
+
+    #[test]
+    fn test_stale_targets() {
+        let db = temp_db();
+        let create = db.insert_mod("create-1.0.jar", "/mods/create-1.0.jar", None).unwrap();
+        add_recipe(&db, create, "create:cog");
+        let tweaks = db.insert_mod("tweaks.zip", "/datapacks/tweaks.zip", None).unwrap();
+        add_recipe(&db, tweaks, "create:cog");
+        db.set_recipe_note("create:shaft", "", Some("remove")).unwrap();
+
+        let dropped = ["create:cog".to_string(), "create:shaft".to_string(), "create:belt".to_string()];
+        let targets = db.stale_targets(&dropped, "create").unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!((targets[0].recipe_id.as_str(), targets[0].kind), ("create:cog", StaleKind::Override));
+        assert_eq!(targets[0].source.as_deref(), Some("tweaks.zip"));
+        assert_eq!((targets[1].recipe_id.as_str(), targets[1].kind), ("create:shaft", StaleKind::Removal));
+        assert!(db.stale_targets(&[], "create").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pinned_namespace_is_literal() {
+        let db = temp_db();
+        let pack = db.insert_mod("pack.zip", "/datapacks/pack.zip", None).unwrap();
+        add_recipe(&db, pack, "my_pack:stick");
+        add_recipe(&db, pack, "myxpack:stick");
+        db.set_pinned_filter(PinnedFilter { namespace: Some("my_pack".to_string()), ..Default::default() });
+        assert_eq!(db.get_recipe_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_search_namespace_is_literal() {
+        let db = temp_db();
+        let pack = db.insert_mod("pack.zip", "/datapacks/pack.zip", None).unwrap();
+        add_recipe(&db, pack, "my_pack:stick");
+        add_recipe(&db, pack, "myxpack:stick");
+        let search = RecipeSearch { namespace: Some("my_pack".to_string()), ..Default::default() };
+        assert_eq!(db.search_recipes(&search).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_hidden_survives_reextraction() {
+        let extract = |db: &Database, create_jar: &str| {
+            let create = db.insert_mod(create_jar, &format!("/mods/{}", create_jar), None).unwrap();
+            add_recipe(db, create, "create:cog");
+            add_recipe(db, create, "create:shaft");
+            let thermal = db.insert_mod("thermal.jar", "/mods/thermal.jar", None).unwrap();
+            add_recipe(db, thermal, "thermal:gear");
+            thermal
+        };
+        let db = temp_db();
+        let thermal = extract(&db, "create-1.0.jar");
+        let cog = db.search_recipes(&RecipeSearch::default()).unwrap().into_iter().find(|r| r.recipe_id == "create:cog");
+        assert!(db.set_recipe_hidden(cog.unwrap().id, true).unwrap());
+        assert!(db.set_mod_hidden(thermal, true).unwrap());
+
+        let staging = temp_db();
+        extract(&staging, "create-1.1.jar");
+        let staging_path = staging.path.clone();
+        drop(staging);
+        db.replace_extracted(&staging_path).unwrap();
+
+        let visible: Vec<String> =
+            db.search_recipes(&RecipeSearch::default()).unwrap().into_iter().map(|r| r.recipe_id).collect();
+        assert_eq!(visible, vec!["create:shaft".to_string()]);
+        let all = RecipeSearch { include_hidden: true, ..Default::default() };
+        assert_eq!(db.search_recipes(&all).unwrap().len(), 3);
+        assert_eq!(db.recipe_type_counts().unwrap()[0].count, 1);
+        assert_eq!(db.list_mods(None, false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_tag_replacements_follow_load_order() {
+        let db = temp_db();
+        for (jar, entry, replace) in [("base.zip", "a:plate", false), ("tweaks.zip", "b:plate", true), ("zzz.zip", "c:plate", false)] {
+            let pack = db.insert_mod(jar, &format!("/datapacks/{}", jar), None).unwrap();
+            db.insert_tag_entries(pack, "c:plates", &[entry.to_string()]).unwrap();
+            if replace {
+                db.record_tag_replace(pack, "item", "c:plates").unwrap();
+            }
+        }
+        db.apply_tag_replacements().unwrap();
+        assert_eq!(db.tag_members("#c:plates").unwrap(), vec!["b:plate", "c:plate"]);
+    }
+
+    #[test]
+    fn test_similar_recipes() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        add_shapeless(&db, create, "create:gear", "create:gear", &["create:zinc_ingot", "minecraft:stick"]);
+        add_shapeless(&db, create, "create:gear_alt", "create:gear", &["create:zinc_ingot", "minecraft:stick"]);
+        add_shapeless(&db, create, "create:rod", "create:rod", &["minecraft:stick"]);
+        add_shapeless(&db, create, "create:plate", "create:plate", &["create:iron_ingot"]);
+        let recipes = db.search_recipes(&RecipeSearch::default()).unwrap();
+        let gear = recipes.iter().find(|r| r.recipe_id == "create:gear").unwrap().id;
+
+        let similar = db.similar_recipes(gear, 0.0, 10).unwrap();
+        let scores: Vec<(&str, f64)> = similar.iter().map(|s| (s.recipe.recipe_id.as_str(), s.score)).collect();
+        assert_eq!(scores, vec![("create:gear_alt", 1.0), ("create:rod", 0.25)]);
+        assert!(similar[0].same_output);
+        assert_eq!(db.similar_recipes(gear, 0.5, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_vanilla_copy() {
+        let db = temp_db();
+        let game = db.insert_mod("1.20.1.jar", "/versions/1.20.1.jar", None).unwrap();
+        let pack = db.insert_mod("tweaks.zip", "/datapacks/tweaks.zip", None).unwrap();
+        add_recipe(&db, pack, "minecraft:stick");
+        add_recipe(&db, game, "minecraft:stick");
+        let vanilla = db.vanilla_copy("minecraft:stick").unwrap().unwrap();
+        assert_eq!(vanilla.mod_name, "1.20.1.jar");
+        assert!(db.vanilla_copy("minecraft:torch").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_tag_unification() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        db.insert_tag_entries(create, "c:ingots/zinc", &["create:zinc_ingot".to_string(), "#c:ingots/other_zinc".to_string()])
+            .unwrap();
+        db.insert_tag_entries(create, "c:ingots/other_zinc", &["mekanism:zinc_ingot".to_string()]).unwrap();
+        add_shapeless(&db, create, "create:zinc_ingot", "create:zinc_ingot", &["create:zinc_nugget"]);
+        add_shapeless(&db, create, "create:zinc_block", "create:zinc_block", &["#c:ingots/zinc"]);
+        add_shapeless(&db, create, "mekanism:zinc_block", "mekanism:zinc_block", &["mekanism:zinc_ingot"]);
+
+        let report = db.tag_unification().unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].tag, "#c:ingots/zinc");
+        assert_eq!(report[0].tag_consumers, vec!["create:zinc_block"]);
+        let variants: Vec<(&str, &[String], &[String])> = report[0]
+            .variants
+            .iter()
+            .map(|v| (v.item.as_str(), v.producers.as_slice(), v.consumers.as_slice()))
+            .collect();
+        assert_eq!(variants, vec![
+            ("create:zinc_ingot", &["create:zinc_ingot".to_string()][..], &[][..]),
+            ("mekanism:zinc_ingot", &[][..], &["mekanism:zinc_block".to_string()][..]),
+        ]);
+    }
+
+    #[test]
+    fn test_cursor_pages_round_trip() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        // STICK recipes all make the same item from the same ingredients, so
+        // those sorts are all ties, broken by row id in the sort's direction
+        for recipe_id in ["create:a", "create:b", "create:c", "create:d", "create:e"] {
+            add_recipe(&db, create, recipe_id);
+        }
+        let by_id = RecipeSearch { sort: RecipeSort::by(SortKey::Recent), ..Default::default() };
+        let ids: Vec<i64> = db.search_recipes(&by_id).unwrap().into_iter().map(|r| r.id).collect();
+        for by in [SortKey::ResultItem, SortKey::RecipeId, SortKey::IngredientCount] {
+            for descending in [false, true] {
+                let search = RecipeSearch { sort: RecipeSort { by, descending }, limit: Some(2), ..Default::default() };
+                let mut paged = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let page = db.search_recipes_page(&search, cursor.as_ref()).unwrap();
+                    assert!(page.recipes.len() <= 2);
+                    paged.extend(page.recipes.into_iter().map(|r| r.id));
+                    match page.next_cursor {
+                        Some(next) => cursor = Some(RecipeCursor::decode(&next).unwrap()),
+                        None => break,
+                    }
+                }
+                let expected: Vec<i64> = match descending {
+                    true => ids.iter().rev().copied().collect(),
+                    false => ids.clone(),
+                };
+                assert_eq!(paged, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_padded_patterns_fit_their_cells() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let json = r##"{"type": "minecraft:crafting_shaped", "pattern": ["  ", " #"],
+                        "key": {"#": {"item": "minecraft:stick"}}, "result": {"item": "create:handle"}}"##;
+        db.insert_recipe(create, "data/create/recipe/handle.json", "create:handle", json, &recipe_parser::parse_recipe(json).unwrap())
+            .unwrap();
+        assert_eq!(db.search_recipes(&RecipeSearch { fits_grid: Some(1), ..Default::default() }).unwrap().len(), 1);
+        assert_eq!(db.search_recipes(&RecipeSearch { slots: vec![0], ..Default::default() }).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_fluid_conversions_follow_fluid_tags() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let melting = r#"{"type": "create:mixing", "ingredients": [{"item": "minecraft:ice"}],
+                          "results": [{"fluid": "minecraft:water", "amount": 250}]}"#;
+        let washing = r#"{"type": "create:mixing", "ingredients": [{"fluidTag": "c:water", "amount": 250}],
+                          "results": [{"item": "minecraft:clay"}]}"#;
+        for (path, recipe_id, json) in [("data/create/recipe/melting.json", "create:melting", melting),
+                                        ("data/create/recipe/washing.json", "create:washing", washing)] {
+            db.insert_recipe(create, path, recipe_id, json, &recipe_parser::parse_recipe(json).unwrap()).unwrap();
+        }
+        db.insert_fluid_tag_entries(create, "c:water", &["#minecraft:water".to_string()]).unwrap();
+        db.insert_fluid_tag_entries(create, "minecraft:water", &["minecraft:water".to_string()]).unwrap();
+
+        let conversions = db.browse_fluid_conversions().unwrap();
+        let melting = conversions.iter().find(|c| c.recipe.recipe_id == "create:melting").unwrap();
+        let washing = conversions.iter().find(|c| c.recipe.recipe_id == "create:washing").unwrap();
+        assert_eq!(melting.feeds_into, vec![washing.recipe.id]);
+    }
+
+    #[test]
+    fn test_type_counts_follow_pinned_filter() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        add_recipe(&db, create, "create:stick");
+        add_recipe(&db, create, "thermal:stick");
+        db.set_pinned_filter(PinnedFilter { namespace: Some("create".to_string()), ..Default::default() });
+        assert_eq!(db.recipe_type_counts().unwrap()[0].count, 1);
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let db = temp_db();
+        let alpha = db.insert_mod("alpha.jar", "/mods/alpha.jar", None).unwrap();
+        let beta = db.insert_mod("beta.jar", "/mods/beta.jar", None).unwrap();
+        add_shapeless(&db, alpha, "alpha:widget", "alpha:widget", &["alpha:known", "minecraft:stick"]);
+        add_shapeless(&db, alpha, "alpha:gadget", "alpha:gadget", &["alpha:missing"]);
+        add_shapeless(&db, beta, "beta:thing", "beta:thing", &["gone:part"]);
+        let names: Vec<(String, String)> = ["alpha:widget", "alpha:gadget", "alpha:known", "beta:thing"]
+            .iter()
+            .map(|item| (item.to_string(), item.to_string()))
+            .collect();
+        db.insert_item_names(alpha, "en_us", &names).unwrap();
+        db.rebuild_namespace_owners().unwrap();
+
+        let groups = db.dangling_references().unwrap();
+        let mods: Vec<&str> = groups.iter().map(|group| group.mod_name.as_str()).collect();
+        assert_eq!(mods, vec!["alpha.jar", "beta.jar"]);
+        let found = |group: &DanglingReferences| -> Vec<(String, String, DanglingReason)> {
+            group.references.iter().map(|r| (r.recipe_id.clone(), r.item.clone(), r.reason)).collect()
+        };
+        assert_eq!(
+            found(&groups[0]),
+            vec![("alpha:gadget".to_string(), "alpha:missing".to_string(), DanglingReason::UnknownItem)]
+        );
+        assert_eq!(found(&groups[1]), vec![("beta:thing".to_string(), "gone:part".to_string(), DanglingReason::MissingMod)]);
+    }
+
+    #[test]
+    fn test_mod_stats_overrides_skip_legacy_ids() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let thermal = db.insert_mod("thermal.jar", "/mods/thermal.jar", None).unwrap();
+        let parsed = recipe_parser::parse_recipe(STICK).unwrap();
+        db.insert_recipe(create, "data/create/recipes/a.json", "", STICK, &parsed).unwrap();
+        db.insert_recipe(thermal, "data/thermal/recipes/b.json", "", STICK, &parsed).unwrap();
+        add_recipe(&db, create, "create:shared");
+        add_recipe(&db, thermal, "create:shared");
+        db.rebuild_namespace_owners().unwrap();
+
+        let stats = db.mod_stats(None).unwrap();
+        let overrides: Vec<(&str, i64)> = stats.iter().map(|s| (s.name.as_str(), s.overrides)).collect();
+        assert_eq!(overrides, vec![("create.jar", 0), ("thermal.jar", 1)]);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::mcversion;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Which recipes to remove, for the datapack and KubeJS exporters.
+#[derive(Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum RemovalSource {
+    /// Recipe ids, e.g. from search results
+    RecipeIds { recipe_ids: Vec<String> },
+    Collection { collection_id: i64 },
+    /// Recipes whose note has the status "remove"
+    Marked,
+}
+
+/// Recipe file that every loader skips: Forge and NeoForge through a false
+/// condition, Fabric through a condition that can't hold. Vanilla fails to
+/// parse it and drops the recipe all the same.
+fn removal_recipe() -> serde_json::Value {
+    json!({
+        "conditions": [{ "type": "forge:false" }],
+        "neoforge:conditions": [{ "type": "neoforge:false" }],
+        "fabric:load_conditions": [{
+            "condition": "fabric:not",
+            "value": { "condition": "fabric:all_mods_loaded", "values": [] }
+        }]
+    })
+}
+
+/// Files of a datapack disabling `recipe_ids` on `minecraft_version`, as
+/// paths inside the pack and their contents.
+pub fn removal_files(recipe_ids: &[String], minecraft_version: &str, description: &str) -> Result<Vec<(String, String)>, String> {
+    let pack_format = mcversion::pack_format(minecraft_version)
+        .ok_or_else(|| format!("No known data pack format for Minecraft {}", minecraft_version))?;
+    let mcmeta = json!({ "pack": { "pack_format": pack_format, "description": description } });
+    let mut files = vec![("pack.mcmeta".to_string(), pretty(&mcmeta))];
+
+    let folder = mcversion::recipe_folder(minecraft_version);
+    let contents = pretty(&removal_recipe());
+    let ids: BTreeSet<&String> = recipe_ids.iter().collect();
+    for id in ids {
+        let (namespace, path) = id.split_once(':').unwrap_or(("minecraft", id));
+        if namespace.is_empty() || path.is_empty() {
+            return Err(format!("Invalid recipe id {}", id));
+        }
+        files.push((format!("data/{}/{}/{}.json", namespace, folder, path), contents.clone()));
+    }
+    Ok(files)
+}
+
+/// Writes the files as a zip that can go straight into a world's
+/// `datapacks` folder.
+pub fn write_zip(files: &[(String, String)], dest: &Path) -> Result<(), String> {
+    let file = File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (path, contents) in files {
+        zip.start_file(path.as_str(), options).map_err(|e| e.to_string())?;
+        zip.write_all(contents.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn pretty(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removal_files() {
+        let ids = vec!["create:crushing/ore".to_string(), "create:crushing/ore".to_string(), "stick".to_string()];
+        let files = removal_files(&ids, "1.20.1", "Removals").unwrap();
+        let paths: Vec<&str> = files.iter().map(|(p, _)| p.as_str()).collect();
+        assert_eq!(paths, vec!["pack.mcmeta", "data/create/recipes/crushing/ore.json", "data/minecraft/recipes/stick.json"]);
+        assert!(files[0].1.contains("\"pack_format\": 15"));
+        assert!(removal_files(&ids, "1.12.2", "Removals").is_err());
+    }
+}
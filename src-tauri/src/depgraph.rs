@@ -0,0 +1,109 @@
+use crate::database::ModInfo;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Dependency ids provided by the game or loader rather than by a jar.
+const PLATFORM_IDS: &[&str] = &["minecraft", "java", "forge", "neoforge", "fabricloader", "quilt_loader"];
+
+#[derive(Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+    /// Required dependencies that no scanned jar provides
+    pub missing: Vec<DependencyEdge>,
+}
+
+/// A mod with declared metadata, keyed by its loader mod id.
+#[derive(Serialize)]
+pub struct DependencyNode {
+    pub mod_id: String,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+    pub required: bool,
+    pub version_range: Option<String>,
+}
+
+/// Recipes in one mod's jar using items or tags whose namespace another mod
+/// owns: gameplay coupling that metadata does not declare.
+#[derive(Serialize)]
+pub struct RecipeDependency {
+    pub from_mod_id: i64,
+    pub from: String,
+    pub to_mod_id: i64,
+    pub to: String,
+    /// Recipes in `from` using something of `to`'s
+    pub recipe_count: i64,
+    /// Distinct items and tags of `to`'s those recipes use
+    pub item_count: i64,
+}
+
+/// Builds the graph from declared metadata. Mods without a metadata file
+/// are left out; platform dependencies (game, loader, Java) are skipped.
+pub fn build_dependency_graph(mods: &[ModInfo]) -> DependencyGraph {
+    let present: HashSet<&str> = mods.iter().filter_map(|m| m.loader_mod_id.as_deref()).collect();
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for info in mods {
+        let Some(mod_id) = &info.loader_mod_id else {
+            continue;
+        };
+        nodes.push(DependencyNode {
+            mod_id: mod_id.clone(),
+            name: info.display_name.clone().unwrap_or_else(|| info.name.clone()),
+            version: info.version.clone(),
+        });
+        edges.extend(
+            info.dependencies
+                .iter()
+                .filter(|dep| !PLATFORM_IDS.contains(&dep.mod_id.as_str()))
+                .map(|dep| DependencyEdge {
+                    from: mod_id.clone(),
+                    to: dep.mod_id.clone(),
+                    required: dep.required,
+                    version_range: dep.version_range.clone(),
+                }),
+        );
+    }
+
+    let missing = edges
+        .iter()
+        .filter(|edge| edge.required && !present.contains(edge.to.as_str()))
+        .cloned()
+        .collect();
+
+    DependencyGraph { nodes, edges, missing }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modmeta::ModDependency;
+    use crate::test_support::mod_info;
+
+    fn dep(mod_id: &str, required: bool) -> ModDependency {
+        ModDependency { mod_id: mod_id.to_string(), version_range: None, required }
+    }
+
+    #[test]
+    fn test_missing_dependencies() {
+        let mods = vec![
+            ModInfo {
+                dependencies: vec![dep("minecraft", true), dep("flywheel", true), dep("jei", false)],
+                ..mod_info("create")
+            },
+            ModInfo { dependencies: vec![dep("create", true)], ..mod_info("createaddition") },
+        ];
+        let graph = build_dependency_graph(&mods);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(graph.missing.len(), 1);
+        assert_eq!(graph.missing[0].to, "flywheel");
+    }
+}
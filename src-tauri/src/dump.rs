@@ -0,0 +1,75 @@
+use crate::database::{Database, ModInfo, Recipe};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Recipes read from the database at a time.
+const BATCH_SIZE: i64 = 500;
+
+/// One line of the dump: the recipe with its jar's metadata.
+#[derive(Serialize)]
+struct DumpLine<'a> {
+    #[serde(flatten)]
+    recipe: &'a Recipe,
+    #[serde(rename = "mod")]
+    mod_info: Option<&'a ModInfo>,
+}
+
+/// Writes every recipe as one JSON object per line (NDJSON), in batches so
+/// large packs never sit in memory whole. Returns how many were written.
+pub fn write_recipes(db: &Database, out: &mut impl Write) -> Result<usize, String> {
+    let mods: HashMap<i64, ModInfo> = db
+        .list_mods(None, true)
+        .map_err(|e| format!("Failed to load mods: {}", e))?
+        .into_iter()
+        .map(|m| (m.id, m))
+        .collect();
+    let mut written = 0;
+    let mut after_id = 0;
+    loop {
+        let recipes = db
+            .recipes_after(after_id, BATCH_SIZE)
+            .map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let Some(last) = recipes.last() else {
+            break;
+        };
+        after_id = last.id;
+        for recipe in &recipes {
+            let line = DumpLine { recipe, mod_info: mods.get(&recipe.mod_id) };
+            serde_json::to_writer(&mut *out, &line).map_err(|e| e.to_string())?;
+            out.write_all(b"\n").map_err(|e| e.to_string())?;
+        }
+        written += recipes.len();
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe_parser;
+    use crate::test_support::{temp_db, STICK};
+
+    #[test]
+    fn test_write_recipes_across_batches() {
+        let db = temp_db();
+        let create = db.insert_mod("create.jar", "/mods/create.jar", None).unwrap();
+        let parsed = recipe_parser::parse_recipe(STICK).unwrap();
+        let total = BATCH_SIZE as usize + 3;
+        for n in 0..total {
+            db.insert_recipe(create, &format!("data/create/recipe/r{}.json", n), &format!("create:r{}", n), STICK, &parsed)
+                .unwrap();
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(write_recipes(&db, &mut out).unwrap(), total);
+        let lines: Vec<serde_json::Value> =
+            String::from_utf8(out).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), total);
+        let ids: Vec<i64> = lines.iter().map(|line| line["id"].as_i64().unwrap()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(lines[total - 1]["recipe_id"], format!("create:r{}", total - 1).as_str());
+        assert_eq!(lines[0]["mod"]["name"], "create.jar");
+    }
+}
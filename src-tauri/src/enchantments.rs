@@ -0,0 +1,102 @@
+use serde_json::Value;
+
+pub struct ParsedEnchantment {
+    pub description: Option<String>,
+    pub supported_items: Vec<String>,
+    pub max_level: Option<i32>,
+    pub weight: Option<i32>,
+    pub anvil_cost: Option<i32>,
+    pub min_cost: LevelCost,
+    pub max_cost: LevelCost,
+    pub effects: Vec<String>,
+}
+
+/// Enchanting table cost: `base` at level 1 plus `per_level` for each level above.
+#[derive(Default)]
+pub struct LevelCost {
+    pub base: Option<i32>,
+    pub per_level: Option<i32>,
+}
+
+/// Parses a 1.21 data-driven enchantment (`data/<namespace>/enchantment/`).
+pub fn parse_enchantment(json_str: &str) -> Result<ParsedEnchantment, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    // Description is a text component, usually a translation key
+    let description = match value.get("description") {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(Value::Object(obj)) => obj.get("translate")
+            .or(obj.get("text"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        _ => None,
+    };
+
+    // A single item, a tag, or a list of items
+    let supported_items = match value.get("supported_items") {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(arr)) => arr.iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let effects = value.get("effects")
+        .and_then(|e| e.as_object())
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+
+    Ok(ParsedEnchantment {
+        description,
+        supported_items,
+        max_level: get_i32(&value, "max_level"),
+        weight: get_i32(&value, "weight"),
+        anvil_cost: get_i32(&value, "anvil_cost"),
+        min_cost: parse_cost(value.get("min_cost")),
+        max_cost: parse_cost(value.get("max_cost")),
+        effects,
+    })
+}
+
+fn parse_cost(value: Option<&Value>) -> LevelCost {
+    match value {
+        Some(cost) => LevelCost {
+            base: get_i32(cost, "base"),
+            per_level: get_i32(cost, "per_level_above_first"),
+        },
+        None => LevelCost::default(),
+    }
+}
+
+fn get_i32(value: &Value, key: &str) -> Option<i32> {
+    value.get(key).and_then(|v| v.as_i64()).map(|v| v as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_enchantment() {
+        let json = r##"{
+            "description": {"translate": "enchantment.minecraft.sharpness"},
+            "supported_items": "#minecraft:enchantable/sharp_weapon",
+            "weight": 10,
+            "max_level": 5,
+            "min_cost": {"base": 1, "per_level_above_first": 11},
+            "max_cost": {"base": 21, "per_level_above_first": 11},
+            "anvil_cost": 1,
+            "slots": ["mainhand"],
+            "effects": {"minecraft:damage": []}
+        }"##;
+
+        let parsed = parse_enchantment(json).unwrap();
+        assert_eq!(parsed.description, Some("enchantment.minecraft.sharpness".to_string()));
+        assert_eq!(parsed.supported_items, vec!["#minecraft:enchantable/sharp_weapon".to_string()]);
+        assert_eq!(parsed.max_level, Some(5));
+        assert_eq!(parsed.min_cost.per_level, Some(11));
+        assert_eq!(parsed.effects, vec!["minecraft:damage".to_string()]);
+    }
+}
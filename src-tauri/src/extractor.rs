@@ -0,0 +1,393 @@
+use crate::database::Database;
+use crate::hashing::{self, JarHashes};
+use crate::session_diff::SessionSnapshot;
+use crate::{components, enchantments, items, lang, machines, modmeta, recipe_parser, trades, worldgen};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Folders Forge JarJar and Fabric use for bundled jars.
+const NESTED_JAR_FOLDERS: &[&str] = &["META-INF/jars/", "META-INF/jarjar/"];
+
+/// How many levels of jars bundled inside bundled jars are followed.
+const MAX_NESTING: usize = 3;
+
+/// Extracts mod metadata, recipes, lang files, machine definitions,
+/// enchantments, worldgen data, trades, item tags, loot table drops and item
+/// component defaults from one jar into the database. Bundled jars are
+/// extracted as child mods of it. Problems with individual entries are pushed
+/// to `errors`; `on_recipe` is called after each stored recipe for progress
+/// reporting. Returns an error if the jar itself could not be read.
+/// `jar_path` may also be a zipped or folder data pack.
+pub fn extract_jar(
+    db: &Database,
+    jar_path: &str,
+    mod_name: &str,
+    errors: &mut Vec<String>,
+    mut on_recipe: impl FnMut(),
+) -> Result<(), String> {
+    if Path::new(jar_path).is_dir() {
+        let archive = folder_archive(Path::new(jar_path))?;
+        return extract_archive(db, archive, jar_path, mod_name, None, errors, &mut on_recipe);
+    }
+    let mut file = File::open(jar_path).map_err(|e| e.to_string())?;
+    let hashes = hashing::hash_reader(&mut file).map_err(|e| e.to_string())?;
+    file.rewind().map_err(|e| e.to_string())?;
+    let archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    extract_archive(db, archive, jar_path, mod_name, None, errors, &mut on_recipe)?;
+    store_hashes(db, jar_path, &hashes)
+}
+
+/// Extracts one jar on its own into a scratch database at `scratch`, which
+/// is deleted afterwards, and returns what it holds.
+pub fn snapshot_jar(jar_path: &str, scratch: &Path, errors: &mut Vec<String>) -> Result<SessionSnapshot, String> {
+    let remove_scratch = || {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", scratch.display(), suffix));
+        }
+    };
+    remove_scratch();
+    let mod_name = Path::new(jar_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| jar_path.to_string());
+    let result = Database::new(scratch.to_path_buf())
+        .map_err(|e| format!("Failed to open scratch database: {}", e))
+        .and_then(|db| {
+            extract_jar(&db, jar_path, &mod_name, errors, || {})?;
+            db.current_snapshot().map_err(|e| format!("Failed to read {}: {}", mod_name, e))
+        });
+    remove_scratch();
+    result
+}
+
+/// Extracts one opened jar. Nested jars get paths like
+/// `outer.jar!/META-INF/jars/inner.jar`, which also tracks the nesting depth.
+fn extract_archive<R: Read + Seek>(
+    db: &Database,
+    mut archive: ZipArchive<R>,
+    jar_path: &str,
+    mod_name: &str,
+    parent_mod_id: Option<i64>,
+    errors: &mut Vec<String>,
+    on_recipe: &mut dyn FnMut(),
+) -> Result<(), String> {
+    let mod_id = db
+        .insert_mod(mod_name, jar_path, parent_mod_id)
+        .map_err(|e| format!("Failed to insert mod: {}", e))?;
+
+    // Mods the jar declares, by mod id. Data under a declared mod's namespace
+    // is attributed to that mod rather than the jar's main mod.
+    let mut jar_mods: HashMap<String, i64> = HashMap::new();
+    let manifest = read_entry(&mut archive, modmeta::MANIFEST_PATH);
+    if let Some(path) = modmeta::METADATA_PATHS.iter().find(|p| archive.by_name(p).is_ok()) {
+        process_entry(&mut archive, path, mod_name, errors, |contents| {
+            let declared = modmeta::parse_metadata(path, contents, manifest.as_deref())?;
+            for (index, metadata) in declared.iter().enumerate() {
+                let id = if index == 0 {
+                    mod_id
+                } else {
+                    db.insert_mod(mod_name, jar_path, parent_mod_id).map_err(|e| e.to_string())?
+                };
+                db.set_mod_metadata(id, metadata).map_err(|e| e.to_string())?;
+                jar_mods.insert(metadata.mod_id.clone(), id);
+            }
+            Ok(())
+        });
+    }
+    let mod_for = |entry_name: &str| -> i64 {
+        entry_name
+            .strip_prefix("data/")
+            .and_then(|rest| rest.split('/').next())
+            .and_then(|namespace| jar_mods.get(namespace))
+            .copied()
+            .unwrap_or(mod_id)
+    };
+
+    let entry_names: Vec<String> = (0..archive.len())
+        .filter_map(|i| archive.by_index(i).ok().map(|e| e.name().to_string()))
+        .collect();
+
+    let mut nested_jars = Vec::new();
+    for entry_name in entry_names {
+        if is_nested_jar(&entry_name) {
+            nested_jars.push(entry_name);
+        } else if let Some(locale) = lang::locale_from_path(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let names = lang::parse_item_names(contents)?;
+                db.insert_item_names(mod_id, locale, &names).map_err(|e| e.to_string())
+            });
+        } else if let Some(machine_id) = machines::machine_id_from_path(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let name = machines::parse_machine_name(contents)?;
+                db.insert_machine(mod_for(&entry_name), &machine_id, name.as_deref(), contents)
+                    .map_err(|e| e.to_string())
+            });
+        } else if let Some(enchantment_id) = data_file_id(&entry_name, &["enchantment"]) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let parsed = enchantments::parse_enchantment(contents)?;
+                db.insert_enchantment(mod_for(&entry_name), &enchantment_id, contents, &parsed)
+                    .map_err(|e| e.to_string())
+            });
+        } else if let Some((kind, worldgen_id)) = worldgen::worldgen_entry(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let references = worldgen::collect_references(contents)?;
+                db.insert_worldgen(mod_for(&entry_name), &kind, &worldgen_id, contents, &references)
+                    .map_err(|e| e.to_string())
+            });
+        } else if let Some(source) = trades::trade_file_id(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let parsed = trades::parse_trades(contents)?;
+                db.insert_trades(mod_for(&entry_name), &source, &parsed).map_err(|e| e.to_string())
+            });
+        } else if let Some(recipe_id) = data_file_id(&entry_name, &["recipe", "recipes"]) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let parsed = recipe_parser::parse_recipe(contents)?;
+                db.insert_recipe(mod_for(&entry_name), &entry_name, &recipe_id, contents, &parsed)
+                    .map_err(|e| e.to_string())?;
+                on_recipe();
+                store_components(db, mod_for(&entry_name), &entry_name, contents)
+            });
+        } else if let Some(tag) = items::fluid_tag_id(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let file = items::parse_tag(contents)?;
+                db.insert_fluid_tag_entries(mod_for(&entry_name), &tag, &file.entries).map_err(|e| e.to_string())?;
+                if file.replace {
+                    db.record_tag_replace(mod_for(&entry_name), "fluid", &tag).map_err(|e| e.to_string())?;
+                }
+                Ok(())
+            });
+        } else if let Some(source) = items::item_source(&entry_name) {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                let found = items::collect_item_ids(source, contents)?;
+                db.insert_item_refs(mod_for(&entry_name), &entry_name, source, &found).map_err(|e| e.to_string())?;
+                if let Some(tag) = items::item_tag_id(&entry_name) {
+                    let file = items::parse_tag(contents)?;
+                    db.insert_tag_entries(mod_for(&entry_name), &tag, &file.entries).map_err(|e| e.to_string())?;
+                    if file.replace {
+                        db.record_tag_replace(mod_for(&entry_name), "item", &tag).map_err(|e| e.to_string())?;
+                    }
+                }
+                store_components(db, mod_for(&entry_name), &entry_name, contents)
+            });
+        } else if entry_name.starts_with("data/") && entry_name.ends_with(".json") {
+            process_entry(&mut archive, &entry_name, mod_name, errors, |contents| {
+                store_components(db, mod_for(&entry_name), &entry_name, contents)
+            });
+        }
+    }
+
+    if jar_path.matches("!/").count() >= MAX_NESTING {
+        return Ok(());
+    }
+    for entry_name in nested_jars {
+        let Some(bytes) = read_bytes(&mut archive, &entry_name) else {
+            continue;
+        };
+        let nested_path = format!("{}!/{}", jar_path, entry_name);
+        let nested_name = entry_name.rsplit('/').next().unwrap_or(&entry_name);
+        let result = hashing::hash_reader(&bytes[..])
+            .map_err(|e| e.to_string())
+            .and_then(|hashes| {
+                let nested = ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+                extract_archive(db, nested, &nested_path, nested_name, Some(mod_id), errors, on_recipe)?;
+                store_hashes(db, &nested_path, &hashes)
+            });
+        if let Err(e) = result {
+            errors.push(format!("{}:{}: {}", mod_name, entry_name, e));
+        }
+    }
+
+    Ok(())
+}
+
+/// Packs a folder data pack into an in-memory zip, so it is read the same
+/// way as a jar.
+fn folder_archive(dir: &Path) -> Result<ZipArchive<Cursor<Vec<u8>>>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut folders = vec![dir.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        let entries = fs::read_dir(&folder).map_err(|e| format!("Failed to read {}: {}", folder.display(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.is_dir() {
+                folders.push(path);
+                continue;
+            }
+            let name: Vec<String> = path
+                .strip_prefix(dir)
+                .map_err(|e| e.to_string())?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            zip.start_file(name.join("/"), options).map_err(|e| e.to_string())?;
+            zip.write_all(&bytes).map_err(|e| e.to_string())?;
+        }
+    }
+    let bytes = zip.finish().map_err(|e| e.to_string())?;
+    ZipArchive::new(bytes).map_err(|e| e.to_string())
+}
+
+/// Records the jar's digests on every mod row extracted from it.
+fn store_hashes(db: &Database, jar_path: &str, hashes: &JarHashes) -> Result<(), String> {
+    db.set_jar_hashes(jar_path, hashes)
+        .map_err(|e| format!("Failed to store jar hashes: {}", e))
+}
+
+fn is_nested_jar(entry_name: &str) -> bool {
+    entry_name.ends_with(".jar") && NESTED_JAR_FOLDERS.iter().any(|folder| entry_name.starts_with(folder))
+}
+
+/// Stores food, stack size and durability components set anywhere in a data
+/// file, e.g. on a recipe result or by a loot function.
+fn store_components(db: &Database, mod_id: i64, entry_name: &str, contents: &str) -> Result<(), String> {
+    // Most data files have no components; skip parsing those
+    if !contents.contains("components") {
+        return Ok(());
+    }
+    let found = components::collect_item_components(contents)?;
+    db.insert_item_components(mod_id, entry_name, &found).map_err(|e| e.to_string())
+}
+
+/// Reads one entry and hands its contents to `store`. Unreadable entries are
+/// skipped; parse and database failures are recorded against the entry.
+fn process_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    entry_name: &str,
+    mod_name: &str,
+    errors: &mut Vec<String>,
+    store: impl FnOnce(&str) -> Result<(), String>,
+) {
+    let Some(contents) = read_entry(archive, entry_name) else {
+        return;
+    };
+    if let Err(e) = store(&contents) {
+        errors.push(format!("{}:{}: {}", mod_name, entry_name, e));
+    }
+}
+
+/// Returns `<namespace>:<path>` for JSON files under `data/<namespace>/<folder>/`
+/// when the folder is one of `folders`.
+fn data_file_id(entry_name: &str, folders: &[&str]) -> Option<String> {
+    let rest = entry_name.strip_prefix("data/")?.strip_suffix(".json")?;
+    let (namespace, rest) = rest.split_once('/')?;
+    let path = folders
+        .iter()
+        .find_map(|folder| rest.strip_prefix(folder)?.strip_prefix('/'))?;
+    Some(format!("{}:{}", namespace, path))
+}
+
+/// Reads a text entry from the archive, or None if it is missing or unreadable.
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents).ok()?;
+    Some(contents)
+}
+
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Option<Vec<u8>> {
+    let mut entry = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{temp_db, STICK};
+
+    #[test]
+    fn test_data_file_id() {
+        assert_eq!(
+            data_file_id("data/create/recipes/crushing/iron_ore.json", &["recipe", "recipes"]),
+            Some("create:crushing/iron_ore".to_string())
+        );
+        assert_eq!(
+            data_file_id("data/minecraft/enchantment/sharpness.json", &["enchantment"]),
+            Some("minecraft:sharpness".to_string())
+        );
+        assert_eq!(data_file_id("data/minecraft/recipes_extra/x.json", &["recipes"]), None);
+        assert_eq!(data_file_id("assets/minecraft/recipes/x.json", &["recipes"]), None);
+    }
+
+    #[test]
+    fn test_extract_folder_datapack() {
+        let dir = std::env::temp_dir().join(format!("modpack-workbench-pack-{}", std::process::id()));
+        let recipes = dir.join("data/mypack/recipes");
+        fs::create_dir_all(&recipes).unwrap();
+        fs::write(dir.join("pack.mcmeta"), r#"{"pack":{"pack_format":15}}"#).unwrap();
+        fs::write(recipes.join("stick.json"), STICK).unwrap();
+
+        let db = temp_db();
+        let mut errors = Vec::new();
+        let mut stored = 0;
+        extract_jar(&db, &dir.to_string_lossy(), "mypack", &mut errors, || stored += 1).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(stored, 1);
+        assert!(db.recipe_ids_starting_with("mypack:").unwrap().contains("mypack:stick"));
+    }
+
+    #[test]
+    fn test_extract_nested_jar() {
+        let mut inner = ZipWriter::new(Cursor::new(Vec::new()));
+        inner.start_file("data/inner/recipes/stick.json", SimpleFileOptions::default()).unwrap();
+        inner.write_all(STICK.as_bytes()).unwrap();
+        let inner = inner.finish().unwrap().into_inner();
+
+        let dir = crate::workspaces::scratch_dir("nested-test").unwrap();
+        let jar_path = dir.join("outer-1.0.jar");
+        let mut jar = ZipWriter::new(File::create(&jar_path).unwrap());
+        jar.start_file("data/outer/recipes/stick.json", SimpleFileOptions::default()).unwrap();
+        jar.write_all(STICK.as_bytes()).unwrap();
+        jar.start_file("META-INF/jars/inner-1.0.jar", SimpleFileOptions::default()).unwrap();
+        jar.write_all(&inner).unwrap();
+        jar.finish().unwrap();
+
+        let db = temp_db();
+        let mut errors = Vec::new();
+        let mut stored = 0;
+        let jar_path = jar_path.to_string_lossy().to_string();
+        extract_jar(&db, &jar_path, "outer-1.0.jar", &mut errors, || stored += 1).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(stored, 2);
+
+        let mods = db.list_mods(None, true).unwrap();
+        let outer = mods.iter().find(|m| m.name == "outer-1.0.jar").unwrap();
+        let nested = mods.iter().find(|m| m.name == "inner-1.0.jar").unwrap();
+        assert_eq!(nested.parent_mod_id, Some(outer.id));
+        assert_eq!(nested.path, format!("{}!/META-INF/jars/inner-1.0.jar", jar_path));
+        assert!(db.recipe_ids_starting_with("inner:").unwrap().contains("inner:stick"));
+
+        db.remove_mod(outer.id).unwrap();
+        assert!(db.list_mods(None, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_jar() {
+        let dir = crate::workspaces::scratch_dir("snapshot-test").unwrap();
+        let jar_path = dir.join("mypack-1.0.jar");
+        let mut jar = ZipWriter::new(File::create(&jar_path).unwrap());
+        jar.start_file("data/mypack/recipes/stick.json", SimpleFileOptions::default()).unwrap();
+        jar.write_all(STICK.as_bytes()).unwrap();
+        jar.finish().unwrap();
+
+        let scratch = dir.join("scratch.db");
+        let mut errors = Vec::new();
+        let snapshot = snapshot_jar(&jar_path.to_string_lossy(), &scratch, &mut errors).unwrap();
+        assert!(!scratch.exists());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(snapshot.mods.len(), 1);
+        assert_eq!(snapshot.mods[0].mod_key, "mypack");
+        assert_eq!(snapshot.recipes.len(), 1);
+        assert_eq!((snapshot.recipes[0].0.as_str(), snapshot.recipes[0].1.as_str()), ("mypack", "mypack:stick"));
+    }
+}
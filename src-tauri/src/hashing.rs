@@ -0,0 +1,42 @@
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+use std::io::{self, Read};
+
+/// Hex digests of a jar, in the forms Modrinth and CurseForge manifests use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JarHashes {
+    pub sha1: String,
+    pub sha512: String,
+}
+
+/// Hashes everything `reader` yields.
+pub fn hash_reader(mut reader: impl Read) -> io::Result<JarHashes> {
+    let mut sha1 = Sha1::new();
+    let mut sha512 = Sha512::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        sha1.update(&buffer[..read]);
+        sha512.update(&buffer[..read]);
+    }
+    Ok(JarHashes {
+        sha1: format!("{:x}", sha1.finalize()),
+        sha512: format!("{:x}", sha512.finalize()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_reader() {
+        let hashes = hash_reader(&b"abc"[..]).unwrap();
+        assert_eq!(hashes.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert!(hashes.sha512.starts_with("ddaf35a193617aba"));
+        assert_eq!(hashes.sha512.len(), 128);
+    }
+}
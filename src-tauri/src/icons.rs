@@ -0,0 +1,28 @@
+use crate::scanner;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Copies a mod's logo out of its jar into `cache_dir` on first use and
+/// returns the cached file. The cache name covers the jar's path and
+/// modification time, so an updated jar gets a fresh copy.
+pub fn cached_icon(cache_dir: &Path, jar_path: &str, icon_path: &str) -> Result<PathBuf, String> {
+    let outer = jar_path.split("!/").next().unwrap_or(jar_path);
+    let modified = fs::metadata(outer).and_then(|m| m.modified()).ok();
+
+    let mut hasher = DefaultHasher::new();
+    (jar_path, icon_path, modified).hash(&mut hasher);
+    let extension = Path::new(icon_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("png");
+    let cached = cache_dir.join(format!("{:016x}.{}", hasher.finish(), extension));
+
+    if !cached.exists() {
+        let bytes = scanner::read_jar_file(jar_path, icon_path.trim_start_matches('/'))?;
+        fs::create_dir_all(cache_dir).map_err(|e| format!("Failed to create icon cache: {}", e))?;
+        fs::write(&cached, bytes).map_err(|e| format!("Failed to cache icon: {}", e))?;
+    }
+    Ok(cached)
+}
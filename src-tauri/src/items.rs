@@ -0,0 +1,125 @@
+use serde_json::Value;
+
+/// Item references recorded from data files other than recipes.
+pub const TAG: &str = "tag";
+pub const LOOT_TABLE: &str = "loot_table";
+
+/// Which kind of item-referencing data file this is: an item tag under
+/// `data/<namespace>/tags/item(s)/` or a loot table under `loot_table(s)/`.
+pub fn item_source(entry_name: &str) -> Option<&'static str> {
+    let rest = entry_name.strip_prefix("data/")?.strip_suffix(".json")?;
+    let (_, rest) = rest.split_once('/')?;
+    if rest.starts_with("tags/item/") || rest.starts_with("tags/items/") {
+        Some(TAG)
+    } else if rest.starts_with("loot_table/") || rest.starts_with("loot_tables/") {
+        Some(LOOT_TABLE)
+    } else {
+        None
+    }
+}
+
+/// Id of the item tag a file defines, e.g. `forge:ingots/iron` for
+/// `data/forge/tags/items/ingots/iron.json`.
+pub fn item_tag_id(entry_name: &str) -> Option<String> {
+    tag_id(entry_name, "tags/item/", "tags/items/")
+}
+
+/// Id of the fluid tag a file defines, e.g. `c:water` for
+/// `data/c/tags/fluid/water.json`.
+pub fn fluid_tag_id(entry_name: &str) -> Option<String> {
+    tag_id(entry_name, "tags/fluid/", "tags/fluids/")
+}
+
+fn tag_id(entry_name: &str, folder: &str, legacy_folder: &str) -> Option<String> {
+    let rest = entry_name.strip_prefix("data/")?.strip_suffix(".json")?;
+    let (namespace, rest) = rest.split_once('/')?;
+    let name = rest.strip_prefix(folder).or_else(|| rest.strip_prefix(legacy_folder))?;
+    Some(format!("{}:{}", namespace, name))
+}
+
+/// Item ids a tag lists or a loot table can drop. Nested tag references
+/// (`#forge:ingots`) are not items and are skipped.
+pub fn collect_item_ids(source: &str, json_str: &str) -> Result<Vec<String>, String> {
+    let mut items = Vec::new();
+    if source == TAG {
+        items = parse_tag(json_str)?.entries;
+        items.retain(|id| !id.starts_with('#'));
+    } else {
+        let value: Value = serde_json::from_str(json_str)
+            .map_err(|e| format!("Invalid JSON: {}", e))?;
+        collect_loot_items(&value, &mut items);
+    }
+    Ok(items)
+}
+
+/// What a tag file says: everything it lists, nested tag references
+/// included, and whether it replaces the tag instead of adding to it.
+pub struct TagFile {
+    pub entries: Vec<String>,
+    pub replace: bool,
+}
+
+pub fn parse_tag(json_str: &str) -> Result<TagFile, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+    let values = value.get("values").and_then(|v| v.as_array()).ok_or("Tag has no values")?;
+    let entries = values
+        .iter()
+        // Optional entries are written as {"id": ..., "required": false}
+        .filter_map(|entry| entry.as_str().or_else(|| entry.get("id").and_then(|id| id.as_str())))
+        .map(str::to_string)
+        .collect();
+    let replace = value.get("replace").and_then(|r| r.as_bool()).unwrap_or(false);
+    Ok(TagFile { entries, replace })
+}
+
+fn collect_loot_items(value: &Value, items: &mut Vec<String>) {
+    match value {
+        Value::Array(arr) => arr.iter().for_each(|v| collect_loot_items(v, items)),
+        Value::Object(obj) => {
+            let is_item = matches!(obj.get("type").and_then(|t| t.as_str()), Some("minecraft:item" | "item"));
+            if let (true, Some(name)) = (is_item, obj.get("name").and_then(|n| n.as_str())) {
+                items.push(name.to_string());
+            }
+            obj.values().for_each(|v| collect_loot_items(v, items));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_source() {
+        assert_eq!(item_source("data/c/tags/item/ingots.json"), Some(TAG));
+        assert_eq!(item_source("data/forge/tags/items/ingots/iron.json"), Some(TAG));
+        assert_eq!(item_source("data/minecraft/loot_tables/blocks/stone.json"), Some(LOOT_TABLE));
+        assert_eq!(item_source("data/minecraft/tags/block/logs.json"), None);
+        assert_eq!(item_tag_id("data/forge/tags/items/ingots/iron.json").as_deref(), Some("forge:ingots/iron"));
+        assert_eq!(fluid_tag_id("data/c/tags/fluid/water.json").as_deref(), Some("c:water"));
+        assert_eq!(fluid_tag_id("data/forge/tags/fluids/milk.json").as_deref(), Some("forge:milk"));
+        assert_eq!(item_tag_id("data/c/tags/fluid/water.json"), None);
+        assert_eq!(item_tag_id("data/minecraft/loot_tables/blocks/stone.json"), None);
+    }
+
+    #[test]
+    fn test_collect_item_ids() {
+        let tag = r##"{"values": ["create:zinc_ingot", "#c:ingots/zinc", {"id": "mekanism:ingot_zinc", "required": false}]}"##;
+        assert_eq!(collect_item_ids(TAG, tag).unwrap(), vec!["create:zinc_ingot", "mekanism:ingot_zinc"]);
+
+        let loot = r#"{"pools": [{"entries": [
+            {"type": "minecraft:item", "name": "minecraft:cobblestone"},
+            {"type": "minecraft:tag", "name": "minecraft:logs"}
+        ]}]}"#;
+        assert_eq!(collect_item_ids(LOOT_TABLE, loot).unwrap(), vec!["minecraft:cobblestone"]);
+    }
+
+    #[test]
+    fn test_parse_tag_replace() {
+        assert!(!parse_tag(r#"{"values": ["a:b"]}"#).unwrap().replace);
+        let tag = parse_tag(r#"{"replace": true, "values": []}"#).unwrap();
+        assert!(tag.replace && tag.entries.is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+/// KubeJS version assumed when the pack doesn't ship KubeJS.
+pub const DEFAULT_MAJOR: u32 = 6;
+
+/// A recipe to put in place of an existing one.
+#[derive(Deserialize)]
+pub struct Replacement {
+    pub recipe_id: String,
+    /// The new recipe as datapack JSON
+    pub json: String,
+}
+
+/// Major KubeJS version from a jar version, which is prefixed with the
+/// Minecraft version (`2001.6.5-build.16` is KubeJS 6 for 1.20.1).
+pub fn major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '-', '+']);
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first < 1000 {
+        return Some(first);
+    }
+    parts.next()?.parse().ok()
+}
+
+/// A server script removing `removals` and swapping in `replacements`, by
+/// recipe id. KubeJS 6 and later use `ServerEvents`, older ones `onEvent`.
+pub fn script(removals: &[String], replacements: &[Replacement], major: u32) -> Result<String, String> {
+    let mut lines = Vec::new();
+    for id in removals {
+        lines.push(format!("event.remove({{ id: {} }})", quote(id)));
+    }
+    for replacement in replacements {
+        let value: Value = serde_json::from_str(&replacement.json)
+            .map_err(|e| format!("Invalid JSON for {}: {}", replacement.recipe_id, e))?;
+        lines.push(format!("event.remove({{ id: {} }})", quote(&replacement.recipe_id)));
+        let add = crafting_call(&value).unwrap_or_else(|| format!("event.custom({})", value));
+        lines.push(format!("{}.id({})", add, quote(&replacement.recipe_id)));
+    }
+
+    let opening = if major >= 6 { "ServerEvents.recipes(event => {" } else { "onEvent('recipes', event => {" };
+    let mut out = format!("// Generated by modpack-workbench\n\n{}\n", opening);
+    for line in lines {
+        out.push_str(&format!("  {}\n", line));
+    }
+    out.push_str("})\n");
+    Ok(out)
+}
+
+/// `event.shaped` or `event.shapeless` for plain crafting recipes; None
+/// when the recipe needs `event.custom`.
+fn crafting_call(value: &Value) -> Option<String> {
+    let recipe_type = value.get("type")?.as_str()?;
+    let result = value.get("result")?;
+    let item = result.as_str().or_else(|| result.get("item").or_else(|| result.get("id"))?.as_str())?;
+    let count = result.get("count").and_then(Value::as_i64).unwrap_or(1);
+    let output = format!("Item.of({}, {})", quote(item), count);
+    match recipe_type.trim_start_matches("minecraft:") {
+        "crafting_shaped" => {
+            let pattern: Vec<String> =
+                value.get("pattern")?.as_array()?.iter().map(|row| row.as_str().map(quote)).collect::<Option<_>>()?;
+            let keys: Vec<String> = value
+                .get("key")?
+                .as_object()?
+                .iter()
+                .map(|(key, ingredient)| Some(format!("{}: {}", quote(key), ingredient_string(ingredient)?)))
+                .collect::<Option<_>>()?;
+            Some(format!("event.shaped({}, [{}], {{ {} }})", output, pattern.join(", "), keys.join(", ")))
+        }
+        "crafting_shapeless" => {
+            let ingredients: Vec<String> =
+                value.get("ingredients")?.as_array()?.iter().map(ingredient_string).collect::<Option<_>>()?;
+            Some(format!("event.shapeless({}, [{}])", output, ingredients.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// An ingredient as KubeJS takes it, `'minecraft:stick'` or `'#c:rods'`;
+/// None for alternatives and other shapes.
+fn ingredient_string(ingredient: &Value) -> Option<String> {
+    if let Some(id) = ingredient.as_str() {
+        return Some(quote(id));
+    }
+    if let Some(item) = ingredient.get("item").and_then(Value::as_str) {
+        return Some(quote(item));
+    }
+    let tag = ingredient.get("tag").and_then(Value::as_str)?;
+    Some(quote(&format!("#{}", tag)))
+}
+
+fn quote(text: &str) -> String {
+    Value::from(text).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(major_version("2001.6.5-build.16"), Some(6));
+        assert_eq!(major_version("1802.5.5-build.569"), Some(5));
+        assert_eq!(major_version("7.0.1"), Some(7));
+        assert_eq!(major_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_script() {
+        let replacements = vec![Replacement {
+            recipe_id: "create:gear".to_string(),
+            json: r#"{"type":"minecraft:crafting_shaped","pattern":["II"],"key":{"I":{"tag":"c:ingots"}},"result":{"item":"create:gear","count":2}}"#
+                .to_string(),
+        }];
+        let text = script(&["a:b".to_string()], &replacements, 6).unwrap();
+        assert!(text.contains("ServerEvents.recipes(event => {\n"));
+        assert!(text.contains("  event.remove({ id: \"a:b\" })\n"));
+        assert!(text.contains(
+            "  event.shaped(Item.of(\"create:gear\", 2), [\"II\"], { \"I\": \"#c:ingots\" }).id(\"create:gear\")\n"
+        ));
+        assert!(script(&[], &[], 5).unwrap().contains("onEvent('recipes', event => {"));
+    }
+
+    #[test]
+    fn test_symbol_keys_are_quoted() {
+        let json: Value = serde_json::from_str(
+            r##"{"type":"minecraft:crafting_shaped","pattern":["#"],"key":{"#":{"item":"minecraft:stick"}},"result":{"item":"minecraft:torch"}}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            crafting_call(&json).unwrap(),
+            "event.shaped(Item.of(\"minecraft:torch\", 1), [\"#\"], { \"#\": \"minecraft:stick\" })"
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use serde_json::Value;
+
+/// Returns the locale for `assets/<namespace>/lang/<locale>.json` entries.
+pub fn locale_from_path(path: &str) -> Option<&str> {
+    let parts: Vec<&str> = path.split('/').collect();
+    if parts.len() != 4 || parts[0] != "assets" || parts[2] != "lang" {
+        return None;
+    }
+    parts[3].strip_suffix(".json")
+}
+
+/// Extracts (item id, display name) pairs from a lang file. Only plain
+/// `item.<namespace>.<path>` and `block.<namespace>.<path>` keys name items;
+/// longer keys are tooltips and other descriptions.
+pub fn parse_item_names(json_str: &str) -> Result<Vec<(String, String)>, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let entries = value.as_object().ok_or("Lang file is not a JSON object")?;
+
+    let mut names = Vec::new();
+    for (key, name) in entries {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.len() == 3 && (parts[0] == "item" || parts[0] == "block") {
+            names.push((format!("{}:{}", parts[1], parts[2]), name.to_string()));
+        }
+    }
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_path() {
+        assert_eq!(locale_from_path("assets/minecraft/lang/en_us.json"), Some("en_us"));
+        assert_eq!(locale_from_path("assets/minecraft/lang/en_us.lang"), None);
+        assert_eq!(locale_from_path("data/minecraft/lang/en_us.json"), None);
+    }
+
+    #[test]
+    fn test_parse_item_names() {
+        let json = r#"{
+            "item.minecraft.iron_ingot": "Iron Ingot",
+            "block.create.andesite_casing": "Andesite Casing",
+            "item.minecraft.potion.effect.water": "Water Bottle",
+            "itemGroup.create.base": "Create"
+        }"#;
+
+        let mut names = parse_item_names(json).unwrap();
+        names.sort();
+        assert_eq!(names, vec![
+            ("create:andesite_casing".to_string(), "Andesite Casing".to_string()),
+            ("minecraft:iron_ingot".to_string(), "Iron Ingot".to_string()),
+        ]);
+    }
+}
@@ -1,14 +1,68 @@
+mod archive;
+mod batch;
+mod complexity;
+mod components;
+mod configs;
+mod conflicts;
+mod crafting_tree;
+mod crafttweaker;
+mod cycles;
 mod database;
+mod datapack;
+mod depgraph;
+mod dump;
+mod enchantments;
+mod extractor;
+mod hashing;
+mod icons;
+mod items;
+mod kubejs;
+mod lang;
+mod load_order;
+mod machines;
+mod matching;
+mod mcversion;
+mod modmeta;
+mod progression;
+mod recipe_diff;
+mod recipe_export;
+mod recipe_ids;
 mod recipe_parser;
+mod recipe_query;
+mod reports;
+mod saved_searches;
 mod scanner;
+mod session_diff;
+mod stats;
+#[cfg(test)]
+mod test_support;
+mod trades;
+mod unification;
+mod values;
+mod wiki;
+mod workspace_compare;
+mod workspaces;
+mod worldgen;
+mod xlsx;
 
-use database::{Database, ExtractionResult, Recipe};
+use database::{
+    BOOKMARK_ITEM, BOOKMARK_RECIPE, BrokenTag, Collection, CollectionContents, ConfigEntry,
+    DanglingReferences, Database, DbStats, DeadEndOutput, Enchantment, ExtractionResult,
+    ExtractionSession, FluidConversion, IngredientUsage, Item, ItemComponentEntry, ItemSources,
+    Machine, ModDetail, ModInfo, ModStats, NamespaceOwner, PinnedFilter, QuarantinedJar, Recipe,
+    RecipeCursor, RecipeNote, RecipeOverride, RecipePage, RecipeSearch, RecipeSearchHit, RecipeSort,
+    RecipeTrendPoint, SearchFacets, SimilarRecipe, SortKey, Trade, UnobtainableItem, WorldgenEntry,
+};
+use matching::MatchMode;
+use saved_searches::{SearchMode, SearchSpec};
+use stats::CategoryCount;
 use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
-use std::fs::File;
-use std::io::Read;
-use std::sync::OnceLock;
-use zip::ZipArchive;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, RwLock};
+use std::time::Instant;
+use workspaces::{WorkspaceInfo, WorkspaceRegistry};
 
 const PROGRESS_EMIT_BATCH_SIZE: usize = 50;
 
@@ -20,10 +74,199 @@ struct ExtractionProgress {
     recipes_extracted: usize,
 }
 
-static DATABASE: OnceLock<Database> = OnceLock::new();
+/// Database of the open workspace. Commands already running keep the one
+/// they started with when another workspace is opened.
+static DATABASE: RwLock<Option<Arc<Database>>> = RwLock::new(None);
+static WORKSPACES: OnceLock<Mutex<WorkspaceRegistry>> = OnceLock::new();
+/// Second workspace opened alongside the active one for comparison, with
+/// its database path.
+static COMPARISON: RwLock<Option<(PathBuf, Arc<Database>)>> = RwLock::new(None);
 
-fn get_db() -> &'static Database {
-    DATABASE.get().expect("Database not initialized")
+fn get_db() -> Arc<Database> {
+    DATABASE.read().unwrap().clone().expect("Database not initialized")
+}
+
+fn get_workspaces() -> MutexGuard<'static, WorkspaceRegistry> {
+    WORKSPACES.get().expect("Workspaces not initialized").lock().unwrap()
+}
+
+#[tauri::command]
+fn list_workspaces() -> Vec<WorkspaceInfo> {
+    get_workspaces().list()
+}
+
+#[tauri::command]
+fn create_workspace(name: String) -> Result<(), String> {
+    get_workspaces().create(&name)
+}
+
+/// Switches to the named workspace, creating its database if needed.
+#[tauri::command]
+fn open_workspace(name: String) -> Result<(), String> {
+    let mut workspaces = get_workspaces();
+    let db = Database::new(workspaces.path_of(&name)?)
+        .map_err(|e| format!("Failed to open workspace: {}", e))?;
+    workspaces.set_active(&name)?;
+    *DATABASE.write().unwrap() = Some(Arc::new(db));
+    Ok(())
+}
+
+/// Exports the open workspace's database to one file for sharing.
+#[tauri::command]
+async fn export_workspace(path: String) -> Result<(), String> {
+    let name = get_workspaces().active_name().to_string();
+    tauri::async_runtime::spawn_blocking(move || archive::export_archive(&get_db(), &name, Path::new(&path)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Adds an exported workspace under its original name, numbered if that is
+/// taken, and returns the name it was given.
+#[tauri::command]
+async fn import_workspace(path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&path);
+        let manifest = archive::read_manifest(path)?;
+        let (name, db_path) = {
+            let mut workspaces = get_workspaces();
+            let name = workspaces.unique_name(&manifest.workspace);
+            workspaces.create(&name)?;
+            let db_path = workspaces.path_of(&name)?;
+            (name, db_path)
+        };
+        if let Err(e) = archive::extract_database(path, &db_path) {
+            get_workspaces().delete(&name)?;
+            return Err(e);
+        }
+        Ok(name)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Moves a workspace's database into `directory`, e.g. the modpack's own
+/// folder or a synced drive, and remembers the new location. The old file
+/// is only deleted once the copy checks out and nothing has it open; if
+/// that delete fails, the move still stands and the error says so.
+#[tauri::command]
+async fn move_workspace(name: String, directory: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut workspaces = get_workspaces();
+        let old_path = workspaces.path_of(&name)?;
+        let file_name = old_path.file_name().ok_or("Workspace has no database file")?;
+        let new_path = Path::new(&directory).join(file_name);
+        if new_path.exists() {
+            return Err(format!("{} already exists", new_path.display()));
+        }
+
+        // The online backup copies the open database without closing it
+        let is_active = workspaces.active_name() == name;
+        let source = if is_active {
+            get_db()
+        } else {
+            Arc::new(Database::new(old_path.clone()).map_err(|e| format!("Failed to open workspace: {}", e))?)
+        };
+        source.backup_to(&new_path).map_err(|e| format!("Failed to copy database: {}", e))?;
+        let copy = match Database::new(new_path.clone()) {
+            Ok(copy) if copy.quick_check().unwrap_or(false) => copy,
+            _ => {
+                workspaces::remove_database_files(&new_path)?;
+                return Err(format!("Failed to copy database: {} is damaged", new_path.display()));
+            }
+        };
+        workspaces.set_location(&name, &new_path)?;
+        if is_active {
+            copy.set_pinned_filter(source.get_pinned_filter());
+            *DATABASE.write().unwrap() = Some(Arc::new(copy));
+        }
+        drop(source);
+        let mut comparison = COMPARISON.write().unwrap();
+        if comparison.as_ref().is_some_and(|(open, _)| *open == old_path) {
+            let db = Database::new(new_path.clone()).map_err(|e| format!("Failed to open workspace: {}", e))?;
+            *comparison = Some((new_path.clone(), Arc::new(db)));
+        }
+        drop(comparison);
+        workspaces::remove_database_files(&old_path).map_err(|e| {
+            format!("Moved to {}, but the old copy is still there: {}", new_path.display(), e)
+        })?;
+        Ok(new_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn rename_workspace(name: String, new_name: String) -> Result<(), String> {
+    get_workspaces().rename(&name, &new_name)
+}
+
+#[tauri::command]
+fn delete_workspace(name: String) -> Result<(), String> {
+    let mut workspaces = get_workspaces();
+    let path = workspaces.path_of(&name)?;
+    let mut comparison = COMPARISON.write().unwrap();
+    if comparison.as_ref().is_some_and(|(open, _)| *open == path) {
+        *comparison = None;
+    }
+    workspaces.delete(&name)
+}
+
+/// Opens a second workspace, e.g. another pack or version branch, for
+/// `compare_workspaces`.
+#[tauri::command]
+fn open_comparison_workspace(name: String) -> Result<(), String> {
+    let path = get_workspaces().path_of(&name)?;
+    let db = Database::new(path.clone()).map_err(|e| format!("Failed to open workspace: {}", e))?;
+    *COMPARISON.write().unwrap() = Some((path, Arc::new(db)));
+    Ok(())
+}
+
+#[tauri::command]
+fn close_comparison_workspace() {
+    *COMPARISON.write().unwrap() = None;
+}
+
+/// Shared recipes, items only one side has and outputs with differing
+/// costs, between the open workspace and the comparison workspace. The
+/// pinned filter applies to both, its mod matched by mod key; if the
+/// comparison workspace lacks that mod, it has no recipes to compare.
+#[tauri::command]
+async fn compare_workspaces() -> Result<workspace_compare::WorkspaceComparison, String> {
+    let other = COMPARISON
+        .read()
+        .unwrap()
+        .as_ref()
+        .map(|(_, db)| db.clone())
+        .ok_or("No comparison workspace is open")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let filter = db.get_pinned_filter();
+        let other_mod = match filter.mod_id {
+            Some(mod_id) => {
+                let key = db.mod_key_of(mod_id).map_err(|e| format!("Failed to load mod: {}", e))?;
+                match key {
+                    Some(key) => other.find_mod_by_key(&key).map_err(|e| format!("Failed to load mod: {}", e))?,
+                    None => None,
+                }
+            }
+            None => None,
+        };
+        let missing_mod = filter.mod_id.is_some() && other_mod.is_none();
+        other.set_pinned_filter(PinnedFilter { mod_id: other_mod, ..filter });
+        let load = |db: &Database| {
+            let recipes = db.value_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+            let items = db.item_ids().map_err(|e| format!("Failed to load items: {}", e))?;
+            Ok::<_, String>((recipes, items))
+        };
+        let (left_recipes, left_items) = load(&db)?;
+        let (mut right_recipes, right_items) = load(&other)?;
+        if missing_mod {
+            right_recipes.clear();
+        }
+        Ok(workspace_compare::compare(&left_recipes, &left_items, &right_recipes, &right_items))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
 #[tauri::command]
@@ -31,140 +274,195 @@ fn scan_folder(path: String) -> Result<Vec<scanner::FileInfo>, String> {
     scanner::scan_directory(&path)
 }
 
+/// Data packs in a `datapacks` folder, for extracting alongside the mods.
+#[tauri::command]
+fn scan_datapacks_folder(path: String) -> Result<Vec<scanner::FileInfo>, String> {
+    scanner::scan_datapacks(&path)
+}
+
+/// Indexes the instance's config directory, replacing any earlier scan.
+/// Returns how many settings were indexed.
+#[tauri::command]
+async fn scan_config_folder(path: String) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let files = configs::scan_config_dir(&path)?;
+        get_db()
+            .replace_config_entries(&files)
+            .map_err(|e| format!("Failed to index configs: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn search_configs(query: String, limit: Option<i64>) -> Result<Vec<ConfigEntry>, String> {
+    get_db()
+        .search_configs(&query, limit.unwrap_or(200))
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+#[tauri::command]
+fn search_recipes_fulltext(
+    query: String,
+    limit: Option<i64>,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<RecipeSearchHit>, String> {
+    let db = get_db();
+    let limit = limit.unwrap_or(200);
+    let hits = db
+        .search_recipes_fulltext(&query, limit, sort)
+        .map_err(|e| format!("Search failed: {}", e))?;
+    // Failing to record the search should not fail the search itself
+    let spec = SearchSpec { sort, limit: Some(limit), ..SearchSpec::pinned(&db, SearchMode::FullText, &query) };
+    let _ = saved_searches::record(&db, &spec, hits.len());
+    Ok(hits)
+}
+
 #[tauri::command]
 fn get_jar_contents(path: String) -> Result<Vec<scanner::JarEntry>, String> {
     scanner::read_jar_contents(&path)
 }
 
 #[tauri::command]
-async fn extract_all_recipes(app: AppHandle, paths: Vec<String>) -> Result<ExtractionResult, String> {
+async fn extract_all_recipes(
+    app: AppHandle,
+    paths: Vec<String>,
+    folder: Option<String>,
+    append: Option<bool>,
+) -> Result<ExtractionResult, String> {
+    let append = append.unwrap_or(false);
     // Run extraction in a background thread using tauri's async runtime
     tauri::async_runtime::spawn_blocking(move || {
         let db = get_db();
 
-        // Clear existing data for fresh extraction
-        db.clear_all().map_err(|e| format!("Failed to clear database: {}", e))?;
+        // Runs still marked as running were cut short by a crash
+        db.fail_interrupted_runs().map_err(|e| format!("Failed to check run history: {}", e))?;
+        let quarantined = db.quarantined_paths().map_err(|e| format!("Failed to load quarantine: {}", e))?;
+
+        // Appending keeps what is indexed and only replaces jars extracted
+        // again. A full extraction fills a staging database that replaces the
+        // indexed data at the end, so searches see the old data until then.
+        let staging_path = db.staging_path();
+        let staging = if append {
+            None
+        } else {
+            workspaces::remove_database_files(&staging_path)?;
+            Some(Database::new(staging_path.clone()).map_err(|e| format!("Failed to create staging database: {}", e))?)
+        };
+        let session_started = Instant::now();
+        let session_id = db
+            .start_session(folder.as_deref(), paths.len())
+            .map_err(|e| format!("Failed to record session: {}", e))?;
 
         let mut mods_processed = 0;
         let mut recipes_extracted = 0;
         let mut errors = Vec::new();
+        let mut warnings = Vec::new();
         let total = paths.len();
 
-        let mut last_emitted_count = 0;
-
-        for (index, jar_path) in paths.iter().enumerate() {
-            // Extract mod name from jar filename
-            let mod_name = std::path::Path::new(jar_path)
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| jar_path.clone());
-
-            // Emit progress event at start of each mod
-            let _ = app.emit("extraction-progress", ExtractionProgress {
-                current: index,
-                total,
-                current_mod: mod_name.clone(),
-                recipes_extracted,
-            });
-            last_emitted_count = recipes_extracted;
-
-            let file = match File::open(jar_path) {
-                Ok(f) => f,
-                Err(e) => {
-                    errors.push(format!("{}: {}", jar_path, e));
-                    continue;
-                }
-            };
-
-            let mut archive = match ZipArchive::new(file) {
-                Ok(a) => a,
-                Err(e) => {
-                    errors.push(format!("{}: {}", jar_path, e));
-                    continue;
-                }
-            };
-
-            let mod_id = match db.insert_mod(&mod_name, jar_path) {
-                Ok(id) => id,
-                Err(e) => {
-                    errors.push(format!("{}: Failed to insert mod: {}", mod_name, e));
-                    continue;
-                }
-            };
-
-            mods_processed += 1;
-
-            // Find and process recipe files
-            let entry_names: Vec<String> = (0..archive.len())
-                .filter_map(|i| archive.by_index(i).ok().map(|e| e.name().to_string()))
-                .collect();
+        // Everything after the session starts runs here, so an error can
+        // close the session instead of leaving it running
+        let mut run = |staging: Option<Database>| -> Result<(), String> {
+            let target: &Database = staging.as_ref().unwrap_or(&db);
+            for (index, jar_path) in paths.iter().enumerate() {
+                // Extract mod name from jar filename
+                let mod_name = std::path::Path::new(jar_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| jar_path.clone());
 
-            for entry_name in entry_names {
-                // Check if it's a recipe JSON file
-                let parts: Vec<&str> = entry_name.split('/').collect();
-                if parts.len() < 4 || parts[0] != "data" {
-                    continue;
-                }
-                if parts[2] != "recipe" && parts[2] != "recipes" {
-                    continue;
-                }
-                if !entry_name.ends_with(".json") {
+                if quarantined.contains(jar_path) {
+                    warnings.push(format!("{}: skipped, jar is quarantined", mod_name));
                     continue;
                 }
 
-                // Read the file contents
-                let mut entry = match archive.by_name(&entry_name) {
-                    Ok(e) => e,
-                    Err(_) => continue,
-                };
+                // Emit progress event at start of each mod
+                let _ = app.emit("extraction-progress", ExtractionProgress {
+                    current: index,
+                    total,
+                    current_mod: mod_name.clone(),
+                    recipes_extracted,
+                });
+                let mut last_emitted_count = recipes_extracted;
 
-                let mut contents = String::new();
-                if entry.read_to_string(&mut contents).is_err() {
-                    continue;
+                if append {
+                    db.remove_mods_at(jar_path)
+                        .map_err(|e| format!("Failed to replace {}: {}", mod_name, e))?;
                 }
 
-                // Parse the recipe
-                let parsed = match recipe_parser::parse_recipe(&contents) {
-                    Ok(p) => p,
-                    Err(e) => {
-                        errors.push(format!("{}:{}: {}", mod_name, entry_name, e));
-                        continue;
-                    }
-                };
+                let run_id = db
+                    .start_jar_run(jar_path, &scanner::mod_key(&mod_name))
+                    .map_err(|e| format!("Failed to record run: {}", e))?;
+                let started = Instant::now();
+                let recipes_before = recipes_extracted;
+                let errors_before = errors.len();
 
-                // Insert into database
-                match db.insert_recipe(
-                    mod_id,
-                    &entry_name,
-                    &parsed.recipe_type,
-                    parsed.result_item.as_deref(),
-                    parsed.result_count,
-                    &contents,
-                    &parsed.ingredients,
-                ) {
-                    Ok(_) => {
-                        recipes_extracted += 1;
-                        if recipes_extracted - last_emitted_count >= PROGRESS_EMIT_BATCH_SIZE {
-                            let _ = app.emit("extraction-progress", ExtractionProgress {
-                                current: index,
-                                total,
-                                current_mod: mod_name.clone(),
-                                recipes_extracted,
-                            });
-                            last_emitted_count = recipes_extracted;
-                        }
-                    }
-                    Err(e) => {
-                        errors.push(format!("{}:{}: {}", mod_name, entry_name, e));
+                let result = extractor::extract_jar(target, jar_path, &mod_name, &mut errors, || {
+                    recipes_extracted += 1;
+                    if recipes_extracted - last_emitted_count >= PROGRESS_EMIT_BATCH_SIZE {
+                        let _ = app.emit("extraction-progress", ExtractionProgress {
+                            current: index,
+                            total,
+                            current_mod: mod_name.clone(),
+                            recipes_extracted,
+                        });
+                        last_emitted_count = recipes_extracted;
                     }
+                });
+                match result {
+                    Ok(()) => mods_processed += 1,
+                    Err(e) => errors.push(format!("{}: {}", jar_path, e)),
+                }
+                // Close the run before reporting a failed write, or the jar
+                // would be blamed for a crash on the next start
+                let recorded = target.record_extraction_errors(jar_path, &errors[errors_before..]);
+                let newly_quarantined = db
+                    .finish_jar_run(
+                        run_id,
+                        jar_path,
+                        started.elapsed(),
+                        recipes_extracted - recipes_before,
+                        errors.len() - errors_before,
+                    )
+                    .map_err(|e| format!("Failed to record run: {}", e))?;
+                recorded.map_err(|e| format!("Failed to record errors: {}", e))?;
+                if newly_quarantined {
+                    warnings.push(format!("{}: quarantined after repeatedly stalling extraction", mod_name));
                 }
             }
+
+            target.rebuild_namespace_owners()
+                .map_err(|e| format!("Failed to map namespaces: {}", e))?;
+            target.apply_tag_replacements()
+                .map_err(|e| format!("Failed to apply tag replacements: {}", e))?;
+            target.rebuild_items()
+                .map_err(|e| format!("Failed to build item registry: {}", e))?;
+            if let Some(staging) = staging {
+                drop(staging);
+                db.replace_extracted(&staging_path)
+                    .map_err(|e| format!("Failed to store extracted data: {}", e))?;
+                workspaces::remove_database_files(&staging_path)?;
+            } else {
+                db.apply_hidden()
+                    .map_err(|e| format!("Failed to restore hidden mods: {}", e))?;
+            }
+            Ok(())
+        };
+        let outcome = run(staging).and_then(|()| {
+            db.finish_session(session_id, session_started.elapsed(), errors.len(), recipes_extracted)
+                .map_err(|e| format!("Failed to record session: {}", e))
+        });
+        if let Err(e) = outcome {
+            // The extraction error is the one worth reporting
+            let _ = db.fail_session(session_id, session_started.elapsed());
+            return Err(e);
         }
 
         Ok(ExtractionResult {
             mods_processed,
             recipes_extracted,
             errors,
+            warnings,
         })
     })
     .await
@@ -172,26 +470,1301 @@ async fn extract_all_recipes(app: AppHandle, paths: Vec<String>) -> Result<Extra
 }
 
 #[tauri::command]
-fn search_recipes_by_output(item: String) -> Result<Vec<Recipe>, String> {
+fn list_sessions() -> Result<Vec<ExtractionSession>, String> {
     get_db()
-        .search_by_output(&item)
-        .map_err(|e| format!("Search failed: {}", e))
+        .list_sessions()
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+/// Mods, recipes and tags that changed between two finished sessions.
+#[tauri::command]
+async fn diff_sessions(old_session: i64, new_session: i64) -> Result<session_diff::SessionDiff, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let mut diff = session_diff::diff(&load_snapshot(&db, old_session)?, &load_snapshot(&db, new_session)?);
+        count_tag_consumers(&db, &mut diff.changed_tags)?;
+        Ok(diff)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Tags whose members changed between two sessions, those the most recipes
+/// use first.
+#[tauri::command]
+async fn diff_session_tags(old_session: i64, new_session: i64) -> Result<Vec<session_diff::TagChange>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let (old, new) = (load_snapshot(&db, old_session)?, load_snapshot(&db, new_session)?);
+        let mut changes = session_diff::diff(&old, &new).changed_tags;
+        count_tag_consumers(&db, &mut changes)?;
+        changes.sort_by(|a, b| b.consumers.cmp(&a.consumers).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(changes)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Writes an analysis to `path` as Markdown, for issue trackers and pack docs.
+#[tauri::command]
+async fn export_report(report: reports::ReportKind, path: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let text = match report {
+            reports::ReportKind::Conflicts => {
+                let recipes = db.crafting_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+                reports::render_conflicts(&conflicts::find_conflicts(&recipes))
+            }
+            reports::ReportKind::Unification => {
+                let tags = db.tag_unification().map_err(|e| format!("Failed to analyze tags: {}", e))?;
+                reports::render_unification(&tags)
+            }
+            reports::ReportKind::Obtainability => {
+                let items = db.unobtainable_items().map_err(|e| format!("Failed to analyze items: {}", e))?;
+                reports::render_obtainability(&items)
+            }
+            reports::ReportKind::SessionDiff { old_session, new_session, locale } => {
+                session_changelog(&db, old_session, new_session, locale)?
+            }
+        };
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Writes the chosen recipes to `path` as a Markdown recipe book for pack
+/// guides, and returns how many it holds.
+#[tauri::command]
+async fn export_recipe_book(
+    source: reports::RecipeBookSource,
+    title: Option<String>,
+    locale: Option<String>,
+    path: String,
+) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let search = |filters: RecipeSearch| {
+            db.search_recipes(&RecipeSearch { offset: 0, limit: Some(-1), ..filters })
+                .map_err(|e| format!("Search failed: {}", e))
+        };
+        let (default_title, recipes) = match source {
+            reports::RecipeBookSource::Mod { mod_id } => {
+                let recipes = search(RecipeSearch { mod_id: Some(mod_id), ..Default::default() })?;
+                (recipes.first().map_or_else(|| "Recipes".to_string(), |r| r.mod_name.clone()), recipes)
+            }
+            reports::RecipeBookSource::Collection { collection_id } => {
+                let contents = db
+                    .get_collection(collection_id)
+                    .map_err(|e| format!("Failed to load collection: {}", e))?
+                    .ok_or_else(|| format!("No collection {}", collection_id))?;
+                (contents.collection.name, contents.recipes)
+            }
+            reports::RecipeBookSource::Search { filters } => ("Recipes".to_string(), search(*filters)?),
+        };
+        let names = recipe_item_names(&db, &recipes, locale.as_deref())?;
+        let text = reports::render_recipe_book(title.as_deref().unwrap_or(&default_title), &recipes, &names);
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        Ok(recipes.len())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Writes a static HTML recipe reference for players into `directory`, and
+/// returns how many pages it has.
+#[tauri::command]
+async fn export_wiki(directory: String, title: Option<String>, locale: Option<String>) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db
+            .search_recipes(&RecipeSearch { limit: Some(-1), ..Default::default() })
+            .map_err(|e| format!("Search failed: {}", e))?;
+        let names = recipe_item_names(&db, &recipes, locale.as_deref())?;
+        let files = wiki::build_site(title.as_deref().unwrap_or("Recipes"), &recipes, &names);
+        wiki::write_site(&files, Path::new(&directory))?;
+        Ok(files.iter().filter(|(path, _)| path.ends_with(".html")).count())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Writes an .xlsx workbook with sheets for recipes, conflicts, unification
+/// candidates and pack stats.
+#[tauri::command]
+async fn export_xlsx(path: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db
+            .search_recipes(&RecipeSearch { limit: Some(-1), ..Default::default() })
+            .map_err(|e| format!("Search failed: {}", e))?;
+        let crafting = db.crafting_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let outputs = db.output_items().map_err(|e| format!("Failed to load outputs: {}", e))?;
+        let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+        let stats = stats::collect_pack_stats(&db, &stats::StatsOptions::default())
+            .map_err(|e| format!("Failed to collect stats: {}", e))?;
+        let bytes = xlsx::workbook(
+            &recipes,
+            &conflicts::find_conflicts(&crafting),
+            &unification::duplicate_groups(&outputs, &tags),
+            &stats,
+        )?;
+        std::fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path, e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Display names of the items in `recipes`.
+fn recipe_item_names(db: &Database, recipes: &[Recipe], locale: Option<&str>) -> Result<HashMap<String, String>, String> {
+    let mut items: Vec<String> = recipes
+        .iter()
+        .flat_map(|r| r.ingredients.iter().map(|i| i.item.clone()).chain(r.result_item.clone()))
+        .filter(|i| !i.starts_with('#'))
+        .collect();
+    items.sort();
+    items.dedup();
+    db.get_item_names(&items, locale.unwrap_or("en_us"))
+        .map_err(|e| format!("Failed to load names: {}", e))
+}
+
+fn session_changelog(db: &Database, old_session: i64, new_session: i64, locale: Option<String>) -> Result<String, String> {
+    let (old, new) = (load_snapshot(db, old_session)?, load_snapshot(db, new_session)?);
+    let locale = locale.unwrap_or_else(|| "en_us".to_string());
+    let name = |item: &str| {
+        db.get_item_names(&[item.to_string()], &locale)
+            .ok()
+            .and_then(|names| names.into_values().next())
+            .unwrap_or_else(|| item.to_string())
+    };
+    Ok(session_diff::changelog(&old, &new, &name))
+}
+
+fn load_snapshot(db: &Database, session_id: i64) -> Result<session_diff::SessionSnapshot, String> {
+    db.session_snapshot(session_id)
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .ok_or_else(|| format!("Session {} has no stored snapshot", session_id))
+}
+
+fn count_tag_consumers(db: &Database, changes: &mut [session_diff::TagChange]) -> Result<(), String> {
+    let tags: Vec<String> = changes.iter().map(|c| c.tag.clone()).collect();
+    let counts = db
+        .tag_consumer_counts(&tags)
+        .map_err(|e| format!("Failed to count tag consumers: {}", e))?;
+    for change in changes {
+        change.consumers = counts.get(&change.tag).copied().unwrap_or(0);
+    }
+    Ok(())
+}
+
+/// Markdown changelog between two sessions, written to `path` when given.
+#[tauri::command]
+async fn export_changelog(
+    old_session: i64,
+    new_session: i64,
+    locale: Option<String>,
+    path: Option<String>,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let text = session_changelog(&get_db(), old_session, new_session, locale)?;
+        if let Some(path) = path {
+            std::fs::write(&path, &text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        }
+        Ok(text)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Extracts the old and new versions of one jar apart from the workspace
+/// and reports what the update changes, including overrides and removals
+/// in the pack aimed at recipes the new version dropped.
+#[tauri::command]
+async fn get_update_impact(old_path: String, new_path: String) -> Result<session_diff::UpdateImpact, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let scratch = workspaces::scratch_dir("update")?;
+        let mut errors = Vec::new();
+        let snapshots = extractor::snapshot_jar(&old_path, &scratch.join("old.db"), &mut errors).and_then(|old| {
+            Ok((old, extractor::snapshot_jar(&new_path, &scratch.join("new.db"), &mut errors)?))
+        });
+        let _ = std::fs::remove_dir_all(&scratch);
+        let (old, new) = snapshots?;
+        let diff = session_diff::diff(&old, &new);
+        let dropped: Vec<String> = diff.removed_recipes.iter().map(|r| r.recipe_id.clone()).collect();
+        let updated = old.mods.first().map_or(String::new(), |m| m.mod_key.clone());
+        let stale = get_db()
+            .stale_targets(&dropped, &updated)
+            .map_err(|e| format!("Failed to check overrides: {}", e))?;
+        Ok(session_diff::UpdateImpact { diff, stale, errors })
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
 }
 
+/// When a recipe appeared, changed or disappeared across extraction sessions.
 #[tauri::command]
-fn search_recipes_by_ingredient(item: String) -> Result<Vec<Recipe>, String> {
+fn get_recipe_history(id: i64) -> Result<Option<session_diff::RecipeHistory>, String> {
     get_db()
-        .search_by_ingredient(&item)
-        .map_err(|e| format!("Search failed: {}", e))
+        .recipe_history(id)
+        .map_err(|e| format!("Failed to load recipe history: {}", e))
 }
 
 #[tauri::command]
-fn list_recipes(offset: i64, limit: i64) -> Result<Vec<Recipe>, String> {
+fn list_quarantined_jars() -> Result<Vec<QuarantinedJar>, String> {
     get_db()
-        .list_recipes(offset, limit)
+        .list_quarantined_jars()
         .map_err(|e| format!("List failed: {}", e))
 }
 
+#[tauri::command]
+fn quarantine_jar(path: String) -> Result<(), String> {
+    get_db()
+        .quarantine_jar(&path, "Quarantined by user")
+        .map_err(|e| format!("Quarantine failed: {}", e))
+}
+
+#[tauri::command]
+fn release_quarantined_jar(path: String) -> Result<(), String> {
+    get_db()
+        .release_quarantined_jar(&path)
+        .map_err(|e| format!("Release failed: {}", e))
+}
+
+#[tauri::command]
+fn search_recipes_by_output(
+    item: String,
+    match_mode: Option<MatchMode>,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<Recipe>, String> {
+    let db = get_db();
+    let match_mode = match_mode.unwrap_or_default();
+    let recipes = db
+        .search_by_output(&item, match_mode, sort.unwrap_or_default())
+        .map_err(|e| format!("Search failed: {}", e))?;
+    let spec = SearchSpec { match_mode, sort, ..SearchSpec::pinned(&db, SearchMode::Output, &item) };
+    let _ = saved_searches::record(&db, &spec, recipes.len());
+    Ok(recipes)
+}
+
+#[tauri::command]
+fn search_recipes_by_ingredient(
+    item: String,
+    match_mode: Option<MatchMode>,
+    expand_tags: Option<bool>,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<Recipe>, String> {
+    let db = get_db();
+    let match_mode = match_mode.unwrap_or_default();
+    let expand_tags = expand_tags.unwrap_or(false);
+    let recipes = db
+        .search_by_ingredient(&item, match_mode, expand_tags, sort.unwrap_or_default())
+        .map_err(|e| format!("Search failed: {}", e))?;
+    let spec = SearchSpec { match_mode, expand_tags, sort, ..SearchSpec::pinned(&db, SearchMode::Ingredient, &item) };
+    let _ = saved_searches::record(&db, &spec, recipes.len());
+    Ok(recipes)
+}
+
+/// A random sample of recipes, for spot-checking what the extractor parsed.
+#[tauri::command]
+fn get_random_recipes(count: i64, filters: Option<RecipeSearch>) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .random_recipes(&filters.unwrap_or_default(), count)
+        .map_err(|e| format!("Failed to sample recipes: {}", e))
+}
+
+/// How an item is made, resolved recipe by recipe down to `depth` levels.
+#[tauri::command]
+fn get_crafting_tree(
+    item: String,
+    count: Option<i64>,
+    depth: Option<u32>,
+    options: Option<crafting_tree::TreeOptions>,
+) -> Result<crafting_tree::CraftingNode, String> {
+    crafting_tree::build_tree(&get_db(), &item, count.unwrap_or(1), depth.unwrap_or(8), &options.unwrap_or_default())
+        .map_err(|e| format!("Failed to build crafting tree: {}", e))
+}
+
+/// What making every target takes: crafts in build order and raw materials.
+#[tauri::command]
+fn plan_crafting(
+    targets: Vec<crafting_tree::PlanTarget>,
+    depth: Option<u32>,
+    options: Option<crafting_tree::TreeOptions>,
+) -> Result<crafting_tree::ShoppingList, String> {
+    crafting_tree::plan(&get_db(), &targets, depth.unwrap_or(8), &options.unwrap_or_default())
+        .map_err(|e| format!("Failed to plan crafting: {}", e))
+}
+
+/// Loops in the recipe graph of up to `max_length` recipes, duplicating ones
+/// first.
+#[tauri::command]
+async fn find_recipe_cycles(
+    max_length: Option<usize>,
+    duplicates_only: Option<bool>,
+) -> Result<Vec<cycles::RecipeCycle>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let edges = get_db()
+            .recipe_edges()
+            .map_err(|e| format!("Failed to load recipes: {}", e))?;
+        Ok(cycles::find_cycles(
+            &edges,
+            max_length.unwrap_or(cycles::DEFAULT_MAX_LENGTH),
+            duplicates_only.unwrap_or(false),
+        ))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Crafting recipes with identical inputs that make different items.
+#[tauri::command]
+async fn find_recipe_conflicts() -> Result<Vec<conflicts::RecipeConflict>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let recipes = get_db()
+            .crafting_recipes()
+            .map_err(|e| format!("Failed to load recipes: {}", e))?;
+        Ok(conflicts::find_conflicts(&recipes))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Complexity scores of items, with averages per namespace for comparing
+/// mods.
+#[tauri::command]
+async fn get_item_complexity(filter: Option<complexity::ComplexityFilter>) -> Result<complexity::ComplexityReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db.complexity_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+        Ok(complexity::score_items(&recipes, &tags, &filter.unwrap_or_default()))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Items from different mods that look like the same thing, grouped by
+/// shared tag or name, for planning unification.
+#[tauri::command]
+fn find_duplicate_outputs() -> Result<Vec<unification::DuplicateGroup>, String> {
+    let db = get_db();
+    let outputs = db.output_items().map_err(|e| format!("Failed to load outputs: {}", e))?;
+    let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+    Ok(unification::duplicate_groups(&outputs, &tags))
+}
+
+/// Tags with items from several mods, with which recipes make each variant
+/// and which ask for a variant instead of the tag.
+#[tauri::command]
+async fn get_tag_unification() -> Result<Vec<unification::TagUnification>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        get_db()
+            .tag_unification()
+            .map_err(|e| format!("Failed to analyze tags: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Ingredients no recipe, loot table or trade produces.
+#[tauri::command]
+fn find_unobtainable_items() -> Result<Vec<UnobtainableItem>, String> {
+    get_db()
+        .unobtainable_items()
+        .map_err(|e| format!("Failed to analyze items: {}", e))
+}
+
+/// Recipe outputs nothing uses, apart from the given endpoints.
+#[tauri::command]
+fn find_dead_end_outputs(endpoints: Option<Vec<String>>) -> Result<Vec<DeadEndOutput>, String> {
+    get_db()
+        .dead_end_outputs(&endpoints.unwrap_or_default())
+        .map_err(|e| format!("Failed to analyze items: {}", e))
+}
+
+/// The most used ingredients and tags, with the mods whose recipes use them.
+#[tauri::command]
+fn get_ingredient_usage(limit: Option<usize>) -> Result<Vec<IngredientUsage>, String> {
+    get_db()
+        .ingredient_usage(limit.unwrap_or(100))
+        .map_err(|e| format!("Failed to analyze ingredients: {}", e))
+}
+
+/// Ingredient tags that are undefined or resolve to no items in the pack.
+#[tauri::command]
+fn find_broken_tags() -> Result<Vec<BrokenTag>, String> {
+    get_db()
+        .broken_tags()
+        .map_err(|e| format!("Failed to analyze tags: {}", e))
+}
+
+/// Recipes naming items no scanned jar has, by the mod shipping them.
+#[tauri::command]
+fn find_dangling_references() -> Result<Vec<DanglingReferences>, String> {
+    get_db()
+        .dangling_references()
+        .map_err(|e| format!("Failed to analyze items: {}", e))
+}
+
+/// Recipes that can be made from the given items alone, for planning
+/// progression from what is on hand.
+#[tauri::command]
+fn find_craftable_recipes(items: Vec<String>, sort: Option<RecipeSort>) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .craftable_recipes(&items, sort.unwrap_or_default())
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Recipes of one type; `crafting_shaped` and `minecraft:crafting_shaped`
+/// find the same recipes.
+#[tauri::command]
+fn search_recipes_by_type(
+    recipe_type: String,
+    offset: Option<i64>,
+    limit: Option<i64>,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<Recipe>, String> {
+    let filters = RecipeSearch {
+        recipe_type: Some(recipe_type),
+        offset: offset.unwrap_or(0),
+        limit,
+        sort: sort.unwrap_or_default(),
+        ..Default::default()
+    };
+    search_recipes(filters)
+}
+
+#[tauri::command]
+fn search_recipes(filters: RecipeSearch) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .search_recipes(&filters)
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Counts every match of `filters` by recipe type, mod and namespace,
+/// ignoring paging.
+#[tauri::command]
+fn get_search_facets(filters: RecipeSearch) -> Result<SearchFacets, String> {
+    get_db()
+        .recipe_facets(&filters)
+        .map_err(|e| format!("Facet count failed: {}", e))
+}
+
+/// Writes every recipe matching `filters` to `path` as CSV or JSON,
+/// ignoring paging, and returns how many were written.
+#[tauri::command]
+async fn export_recipes(mut filters: RecipeSearch, format: recipe_export::ExportFormat, path: String) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        filters.offset = 0;
+        filters.limit = Some(-1);
+        let recipes = get_db().search_recipes(&filters).map_err(|e| format!("Search failed: {}", e))?;
+        let text = recipe_export::render(&recipes, format)?;
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+        Ok(recipes.len())
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Dumps every recipe, with its ingredients, raw JSON and mod metadata, to
+/// `path` as NDJSON for external scripts. Returns how many were written.
+#[tauri::command]
+async fn dump_recipes(path: String) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = std::fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        dump::write_recipes(&get_db(), &mut std::io::BufWriter::new(file))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Writes a datapack zip to `path` that disables the chosen recipes on
+/// `minecraft_version`, and returns how many recipes it covers.
+#[tauri::command]
+async fn generate_removal_datapack(
+    source: datapack::RemovalSource,
+    minecraft_version: String,
+    path: String,
+) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let recipe_ids = removal_ids(&get_db(), source)?;
+        if recipe_ids.is_empty() {
+            return Err("No recipes to remove".to_string());
+        }
+        let files = datapack::removal_files(&recipe_ids, &minecraft_version, "Recipe removals from modpack-workbench")?;
+        datapack::write_zip(&files, Path::new(&path))?;
+        Ok(files.len() - 1)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn get_script_engine() -> Result<crafttweaker::ScriptEngine, String> {
+    let engine = get_db()
+        .get_setting(crafttweaker::SCRIPT_ENGINE_SETTING)
+        .map_err(|e| format!("Failed to read setting: {}", e))?;
+    Ok(engine.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+}
+
+#[tauri::command]
+fn set_script_engine(engine: crafttweaker::ScriptEngine) -> Result<(), String> {
+    let json = serde_json::to_string(&engine).map_err(|e| format!("Failed to encode engine: {}", e))?;
+    get_db()
+        .set_setting(crafttweaker::SCRIPT_ENGINE_SETTING, &json)
+        .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+/// Writes a script to `path` that removes the chosen recipes and swaps in
+/// the replacements, for the workspace's script engine unless another is
+/// given. The KubeJS version defaults to the one in the pack.
+#[tauri::command]
+async fn export_recipe_script(
+    removals: Option<datapack::RemovalSource>,
+    replacements: Option<Vec<kubejs::Replacement>>,
+    engine: Option<crafttweaker::ScriptEngine>,
+    kubejs_version: Option<String>,
+    path: String,
+) -> Result<(), String> {
+    let engine = match engine {
+        Some(engine) => engine,
+        None => get_script_engine()?,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let removals = match removals {
+            Some(source) => removal_ids(&db, source)?,
+            None => Vec::new(),
+        };
+        let replacements = replacements.unwrap_or_default();
+        if removals.is_empty() && replacements.is_empty() {
+            return Err("No recipes to remove or replace".to_string());
+        }
+        let text = match engine {
+            crafttweaker::ScriptEngine::CraftTweaker => {
+                let types = db
+                    .recipe_types_by_id(&removals)
+                    .map_err(|e| format!("Failed to load recipes: {}", e))?;
+                let removals: Vec<(String, String)> = removals
+                    .into_iter()
+                    .map(|id| {
+                        let recipe_type = types.get(&id).cloned().unwrap_or_else(|| "minecraft:crafting_shaped".to_string());
+                        (id, recipe_type)
+                    })
+                    .collect();
+                crafttweaker::script(&removals, &replacements)?
+            }
+            crafttweaker::ScriptEngine::KubeJs => {
+                let version = match kubejs_version {
+                    Some(version) => Some(version),
+                    None => db
+                        .list_mods(None, true)
+                        .map_err(|e| format!("Failed to load mods: {}", e))?
+                        .into_iter()
+                        .find(|m| m.loader_mod_id.as_deref() == Some("kubejs"))
+                        .and_then(|m| m.version),
+                };
+                let major = version.as_deref().and_then(kubejs::major_version).unwrap_or(kubejs::DEFAULT_MAJOR);
+                kubejs::script(&removals, &replacements, major)?
+            }
+        };
+        std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path, e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+fn removal_ids(db: &Database, source: datapack::RemovalSource) -> Result<Vec<String>, String> {
+    match source {
+        datapack::RemovalSource::RecipeIds { recipe_ids } => Ok(recipe_ids),
+        datapack::RemovalSource::Collection { collection_id } => db.collection_recipe_ids(collection_id),
+        datapack::RemovalSource::Marked => db.recipe_ids_with_status("remove"),
+    }
+    .map_err(|e| format!("Failed to load recipes: {}", e))
+}
+
+/// Runs a query-language search, e.g.
+/// `output:create:* AND type:create:pressing AND NOT mod:thermal`.
+#[tauri::command]
+fn query_recipes(
+    query: String,
+    offset: Option<i64>,
+    limit: Option<i64>,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<Recipe>, String> {
+    let query = recipe_query::parse(&query).map_err(|e| format!("Invalid query: {}", e))?;
+    get_db()
+        .query_recipes(&query, offset.unwrap_or(0), limit.unwrap_or(200), sort.unwrap_or_default())
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Sorted by mod unless `sort` says otherwise.
+#[tauri::command]
+fn list_recipes(offset: i64, limit: i64, sort: Option<RecipeSort>) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .list_recipes(offset, limit, sort.unwrap_or(RecipeSort::by(SortKey::Mod)))
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+/// Like `list_recipes`, but paged by cursor: pass back `next_cursor` to get
+/// the following page.
+#[tauri::command]
+fn list_recipes_page(cursor: Option<String>, limit: i64, sort: Option<RecipeSort>) -> Result<RecipePage, String> {
+    let filters = RecipeSearch {
+        sort: sort.unwrap_or(RecipeSort::by(SortKey::Mod)),
+        limit: Some(limit),
+        ..Default::default()
+    };
+    search_recipes_page(filters, cursor)
+}
+
+/// Like `search_recipes`, but paged by cursor instead of offset.
+#[tauri::command]
+fn search_recipes_page(filters: RecipeSearch, cursor: Option<String>) -> Result<RecipePage, String> {
+    let cursor = cursor.as_deref().map(RecipeCursor::decode).transpose()?;
+    get_db()
+        .search_recipes_page(&filters, cursor.as_ref())
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+/// Canonical recipe types with how many recipes use each, most common first.
+#[tauri::command]
+fn get_recipe_types() -> Result<Vec<CategoryCount>, String> {
+    get_db()
+        .recipe_type_counts()
+        .map_err(|e| format!("Failed to count recipe types: {}", e))
+}
+
+#[tauri::command]
+fn browse_fluid_conversions() -> Result<Vec<FluidConversion>, String> {
+    get_db()
+        .browse_fluid_conversions()
+        .map_err(|e| format!("Browse failed: {}", e))
+}
+
+#[tauri::command]
+fn get_item_names(items: Vec<String>, locale: String) -> Result<HashMap<String, String>, String> {
+    get_db()
+        .get_item_names(&items, &locale)
+        .map_err(|e| format!("Lookup failed: {}", e))
+}
+
+#[tauri::command]
+fn list_machines() -> Result<Vec<Machine>, String> {
+    get_db()
+        .list_machines()
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+#[tauri::command]
+fn list_machine_recipes(machine_id: String) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .list_machine_recipes(&machine_id)
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+#[tauri::command]
+fn list_enchantments() -> Result<Vec<Enchantment>, String> {
+    get_db()
+        .list_enchantments()
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+#[tauri::command]
+fn search_enchantments(query: String) -> Result<Vec<Enchantment>, String> {
+    get_db()
+        .search_enchantments(&query)
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+#[tauri::command]
+fn search_enchantments_by_item(item: String) -> Result<Vec<Enchantment>, String> {
+    get_db()
+        .search_enchantments_by_item(&item)
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+#[tauri::command]
+fn get_mod_recipe_trend(mod_id: i64) -> Result<Vec<RecipeTrendPoint>, String> {
+    get_db()
+        .get_mod_recipe_trend(mod_id)
+        .map_err(|e| format!("Trend failed: {}", e))
+}
+
+#[tauri::command]
+fn get_pinned_filter() -> PinnedFilter {
+    get_db().get_pinned_filter()
+}
+
+#[tauri::command]
+fn pin_filter(filter: PinnedFilter) {
+    get_db().set_pinned_filter(filter);
+}
+
+#[tauri::command]
+fn clear_pinned_filter() {
+    get_db().set_pinned_filter(PinnedFilter::default());
+}
+
+#[tauri::command]
+fn list_worldgen(kind: Option<String>) -> Result<Vec<WorldgenEntry>, String> {
+    get_db()
+        .list_worldgen(kind.as_deref())
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+#[tauri::command]
+fn search_worldgen(reference: String) -> Result<Vec<WorldgenEntry>, String> {
+    get_db()
+        .search_worldgen(&reference)
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+#[tauri::command]
+fn get_recipe(id: i64) -> Result<Option<Recipe>, String> {
+    get_db()
+        .get_recipe(id)
+        .map_err(|e| format!("Failed to load recipe: {}", e))
+}
+
+/// Field-level differences between two recipes (row ids), e.g. a recipe and
+/// the copy overriding it.
+#[tauri::command]
+fn compare_recipes(id_a: i64, id_b: i64) -> Result<recipe_diff::RecipeComparison, String> {
+    let db = get_db();
+    let load = |id: i64| {
+        db.get_recipe(id)
+            .map_err(|e| format!("Failed to load recipe: {}", e))?
+            .ok_or_else(|| format!("No recipe with id {}", id))
+    };
+    Ok(recipe_diff::compare(&load(id_a)?, &load(id_b)?))
+}
+
+/// An overriding recipe (row id) against the vanilla copy it replaces.
+#[tauri::command]
+fn compare_with_vanilla(id: i64) -> Result<recipe_diff::VanillaComparison, String> {
+    let db = get_db();
+    let modded = db
+        .get_recipe(id)
+        .map_err(|e| format!("Failed to load recipe: {}", e))?
+        .ok_or_else(|| format!("No recipe with id {}", id))?;
+    let vanilla = db
+        .vanilla_copy(&modded.recipe_id)
+        .map_err(|e| format!("Failed to load recipe: {}", e))?
+        .ok_or_else(|| format!("No vanilla copy of {} was scanned", modded.recipe_id))?;
+    if vanilla.id == modded.id {
+        return Err(format!("{} is the vanilla recipe", modded.recipe_id));
+    }
+    Ok(recipe_diff::compare_with_vanilla(vanilla, modded))
+}
+
+/// Recipes most like the given one, for spotting near-duplicates across mods.
+#[tauri::command]
+fn find_similar_recipes(id: i64, min_score: Option<f64>, limit: Option<usize>) -> Result<Vec<SimilarRecipe>, String> {
+    get_db()
+        .similar_recipes(id, min_score.unwrap_or(0.5), limit.unwrap_or(20))
+        .map_err(|e| format!("Failed to find similar recipes: {}", e))
+}
+
+#[tauri::command]
+fn get_recipes(ids: Vec<i64>) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .get_recipes(&ids)
+        .map_err(|e| format!("Failed to load recipes: {}", e))
+}
+
+#[tauri::command]
+fn get_recipe_providers(recipe_id: String) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .get_recipe_providers(&recipe_id)
+        .map_err(|e| format!("Failed to load providers: {}", e))
+}
+
+#[tauri::command]
+fn list_recipe_overrides(load_order: Option<Vec<String>>) -> Result<Vec<RecipeOverride>, String> {
+    get_db()
+        .list_recipe_overrides(&load_order.unwrap_or_default())
+        .map_err(|e| format!("Failed to list overrides: {}", e))
+}
+
+#[tauri::command]
+fn get_recipe_note(recipe_id: String) -> Result<Option<RecipeNote>, String> {
+    get_db()
+        .get_recipe_note(&recipe_id)
+        .map_err(|e| format!("Failed to load note: {}", e))
+}
+
+#[tauri::command]
+fn set_recipe_note(recipe_id: String, note: String, status: Option<String>) -> Result<(), String> {
+    if recipe_id.is_empty() {
+        return Err("Recipe has no id to attach a note to".to_string());
+    }
+    get_db()
+        .set_recipe_note(&recipe_id, &note, status.as_deref())
+        .map_err(|e| format!("Failed to save note: {}", e))
+}
+
+#[tauri::command]
+fn create_collection(name: String) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Collection name is empty".to_string());
+    }
+    get_db()
+        .create_collection(name)
+        .map_err(|e| format!("Failed to create collection: {}", e))
+}
+
+#[tauri::command]
+fn delete_collection(collection_id: i64) -> Result<(), String> {
+    match get_db().delete_collection(collection_id) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No collection with id {}", collection_id)),
+        Err(e) => Err(format!("Failed to delete collection: {}", e)),
+    }
+}
+
+#[tauri::command]
+fn list_collections() -> Result<Vec<Collection>, String> {
+    get_db()
+        .list_collections()
+        .map_err(|e| format!("Failed to list collections: {}", e))
+}
+
+/// `kind` is "recipe" (target is a recipe id) or "item" (target is an item id).
+#[tauri::command]
+fn add_to_collection(collection_id: i64, kind: String, target: String) -> Result<(), String> {
+    check_bookmark_kind(&kind)?;
+    get_db()
+        .add_to_collection(collection_id, &kind, &target)
+        .map_err(|e| format!("Failed to add bookmark: {}", e))
+}
+
+#[tauri::command]
+fn remove_from_collection(collection_id: i64, kind: String, target: String) -> Result<bool, String> {
+    check_bookmark_kind(&kind)?;
+    get_db()
+        .remove_from_collection(collection_id, &kind, &target)
+        .map_err(|e| format!("Failed to remove bookmark: {}", e))
+}
+
+fn check_bookmark_kind(kind: &str) -> Result<(), String> {
+    match kind {
+        BOOKMARK_RECIPE | BOOKMARK_ITEM => Ok(()),
+        _ => Err(format!("Unknown bookmark kind: {}", kind)),
+    }
+}
+
+#[tauri::command]
+fn get_collection(collection_id: i64) -> Result<CollectionContents, String> {
+    get_db()
+        .get_collection(collection_id)
+        .map_err(|e| format!("Failed to load collection: {}", e))?
+        .ok_or_else(|| format!("No collection with id {}", collection_id))
+}
+
+#[tauri::command]
+fn save_search(search: saved_searches::SavedSearch) -> Result<(), String> {
+    if search.name.trim().is_empty() {
+        return Err("Search name is empty".to_string());
+    }
+    saved_searches::save(&get_db(), &search)
+}
+
+#[tauri::command]
+fn list_saved_searches() -> Result<saved_searches::SavedSearchList, String> {
+    saved_searches::list(&get_db())
+}
+
+#[tauri::command]
+fn run_saved_search(name: String) -> Result<Vec<Recipe>, String> {
+    saved_searches::run(&get_db(), &name)
+}
+
+#[tauri::command]
+fn list_search_history(limit: Option<i64>) -> Result<Vec<saved_searches::HistoryEntry>, String> {
+    saved_searches::history(&get_db(), limit.unwrap_or(100))
+}
+
+#[tauri::command]
+fn replay_search(id: i64) -> Result<Vec<Recipe>, String> {
+    saved_searches::replay(&get_db(), id)
+}
+
+#[tauri::command]
+fn clear_search_history() -> Result<(), String> {
+    get_db()
+        .clear_search_history()
+        .map_err(|e| format!("Failed to clear search history: {}", e))
+}
+
+#[tauri::command]
+fn delete_saved_search(name: String) -> Result<(), String> {
+    match get_db().delete_saved_search(&name) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No saved search named {}", name)),
+        Err(e) => Err(format!("Failed to delete search: {}", e)),
+    }
+}
+
+/// The pack's gate items, in progression order.
+#[tauri::command]
+fn get_progression_gates() -> Result<Vec<String>, String> {
+    let gates = get_db()
+        .get_setting(progression::GATES_SETTING)
+        .map_err(|e| format!("Failed to read setting: {}", e))?;
+    Ok(gates.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+}
+
+#[tauri::command]
+fn set_progression_gates(gates: Vec<String>) -> Result<(), String> {
+    let json = serde_json::to_string(&gates).map_err(|e| format!("Failed to encode gates: {}", e))?;
+    get_db()
+        .set_setting(progression::GATES_SETTING, &json)
+        .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+/// Tiers of items and recipes unlocked by each gate, using the saved gates
+/// unless others are given.
+#[tauri::command]
+async fn get_progression_report(gates: Option<Vec<String>>) -> Result<progression::ProgressionReport, String> {
+    let gates = match gates {
+        Some(gates) => gates,
+        None => get_progression_gates()?,
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db.complexity_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+        Ok(progression::progression(&recipes, &tags, &gates))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// What removing the given recipes (row ids) would leave unobtainable or
+/// uncraftable, without touching anything.
+#[tauri::command]
+async fn simulate_recipe_removal(recipe_ids: Vec<i64>) -> Result<progression::RemovalImpact, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db.complexity_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+        let sources = db.non_recipe_sources().map_err(|e| format!("Failed to load sources: {}", e))?;
+        let removed = recipe_ids.into_iter().collect();
+        Ok(progression::removal_impact(&recipes, &tags, &sources, &removed))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// The user's seed values for value propagation.
+#[tauri::command]
+fn get_value_seeds() -> Result<HashMap<String, f64>, String> {
+    let seeds = get_db()
+        .get_setting(values::SEEDS_SETTING)
+        .map_err(|e| format!("Failed to read setting: {}", e))?;
+    Ok(seeds.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+}
+
+#[tauri::command]
+fn set_value_seeds(seeds: HashMap<String, f64>) -> Result<(), String> {
+    let json = serde_json::to_string(&seeds).map_err(|e| format!("Failed to encode seeds: {}", e))?;
+    get_db()
+        .set_setting(values::SEEDS_SETTING, &json)
+        .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+/// Item values spread from the saved seeds, on top of a preset if one is
+/// named, with recipes costing far more than they make.
+#[tauri::command]
+async fn compute_item_values(options: Option<values::ValueOptions>) -> Result<values::ValueReport, String> {
+    let options = options.unwrap_or_default();
+    let mut seeds = match &options.preset {
+        Some(name) => values::preset(name).ok_or_else(|| format!("Unknown preset: {}", name))?,
+        None => HashMap::new(),
+    };
+    seeds.extend(get_value_seeds()?);
+    tauri::async_runtime::spawn_blocking(move || {
+        let db = get_db();
+        let recipes = db.value_recipes().map_err(|e| format!("Failed to load recipes: {}", e))?;
+        let tags = db.tag_entries().map_err(|e| format!("Failed to load tags: {}", e))?;
+        let ratio = options.outlier_ratio.unwrap_or(values::DEFAULT_OUTLIER_RATIO);
+        Ok(values::propagate(&recipes, &tags, &seeds, ratio))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn get_recipe_id_prefix() -> Result<String, String> {
+    let prefix = get_db()
+        .get_setting(recipe_ids::PREFIX_SETTING)
+        .map_err(|e| format!("Failed to read setting: {}", e))?;
+    Ok(prefix.unwrap_or_else(|| recipe_ids::DEFAULT_PREFIX.to_string()))
+}
+
+#[tauri::command]
+fn set_recipe_id_prefix(prefix: String) -> Result<(), String> {
+    get_db()
+        .set_setting(recipe_ids::PREFIX_SETTING, &prefix)
+        .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+#[tauri::command]
+fn suggest_recipe_id(result_item: String, recipe_type: String) -> Result<String, String> {
+    let prefix = get_recipe_id_prefix()?;
+    let taken = get_db()
+        .recipe_ids_starting_with(&prefix)
+        .map_err(|e| format!("Failed to load recipe ids: {}", e))?;
+    Ok(recipe_ids::suggest_recipe_id(&prefix, &result_item, &recipe_type, |id| taken.contains(id)))
+}
+
+#[tauri::command]
+fn list_trades() -> Result<Vec<Trade>, String> {
+    get_db()
+        .list_trades()
+        .map_err(|e| format!("Failed to list trades: {}", e))
+}
+
+#[tauri::command]
+fn list_item_components(item: Option<String>) -> Result<Vec<ItemComponentEntry>, String> {
+    get_db()
+        .list_item_components(item.as_deref())
+        .map_err(|e| format!("Failed to list item components: {}", e))
+}
+
+#[tauri::command]
+fn list_mods(loader: Option<String>, include_hidden: Option<bool>) -> Result<Vec<ModInfo>, String> {
+    get_db()
+        .list_mods(loader.as_deref(), include_hidden.unwrap_or(false))
+        .map_err(|e| format!("Failed to list mods: {}", e))
+}
+
+#[tauri::command]
+fn set_mod_hidden(mod_id: i64, hidden: bool) -> Result<(), String> {
+    match get_db().set_mod_hidden(mod_id, hidden) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No mod with id {}", mod_id)),
+        Err(e) => Err(format!("Failed to update mod: {}", e)),
+    }
+}
+
+#[tauri::command]
+fn set_recipe_hidden(recipe_id: i64, hidden: bool) -> Result<(), String> {
+    match get_db().set_recipe_hidden(recipe_id, hidden) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("No recipe with id {}", recipe_id)),
+        Err(e) => Err(format!("Failed to update recipe: {}", e)),
+    }
+}
+
+/// Mods whose jar has this SHA-1 or SHA-512 digest, e.g. from a pack manifest.
+#[tauri::command]
+fn find_mods_by_hash(hash: String) -> Result<Vec<ModInfo>, String> {
+    get_db()
+        .find_mods_by_hash(&hash)
+        .map_err(|e| format!("Hash lookup failed: {}", e))
+}
+
+/// Drops one mod's data, e.g. after its jar was removed from the pack,
+/// without a full re-extraction.
+#[tauri::command]
+fn remove_mod(mod_id: i64) -> Result<(), String> {
+    let db = get_db();
+    if !db.remove_mod(mod_id).map_err(|e| format!("Failed to remove mod: {}", e))? {
+        return Err(format!("No mod with id {}", mod_id));
+    }
+    db.rebuild_namespace_owners()
+        .map_err(|e| format!("Failed to map namespaces: {}", e))?;
+    db.rebuild_items()
+        .map_err(|e| format!("Failed to build item registry: {}", e))
+}
+
+#[tauri::command]
+fn get_mod(mod_id: i64) -> Result<Option<ModInfo>, String> {
+    get_db()
+        .get_mod(mod_id)
+        .map_err(|e| format!("Failed to load mod: {}", e))
+}
+
+#[tauri::command]
+fn get_mod_detail(mod_id: i64) -> Result<ModDetail, String> {
+    get_db()
+        .get_mod_detail(mod_id)
+        .map_err(|e| format!("Failed to load mod: {}", e))?
+        .ok_or_else(|| format!("No mod with id {}", mod_id))
+}
+
+/// Report card figures for one mod, or every mod when no id is given.
+#[tauri::command]
+fn get_mod_stats(mod_id: Option<i64>) -> Result<Vec<ModStats>, String> {
+    get_db()
+        .mod_stats(mod_id)
+        .map_err(|e| format!("Failed to load mod stats: {}", e))
+}
+
+#[tauri::command]
+fn list_recipes_by_mod(
+    mod_id: i64,
+    offset: i64,
+    limit: i64,
+    sort: Option<RecipeSort>,
+) -> Result<Vec<Recipe>, String> {
+    get_db()
+        .list_recipes_by_mod(mod_id, offset, limit, sort.unwrap_or(RecipeSort::by(SortKey::RecipeId)))
+        .map_err(|e| format!("List failed: {}", e))
+}
+
+/// Path to the mod's logo, cached on disk, or None if it declares none.
+#[tauri::command]
+fn get_mod_icon(app: AppHandle, mod_id: i64) -> Result<Option<String>, String> {
+    let source = get_db()
+        .get_mod_icon_source(mod_id)
+        .map_err(|e| format!("Failed to look up mod: {}", e))?;
+    let Some((jar_path, icon)) = source else {
+        return Ok(None);
+    };
+    let cache_dir = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get cache dir: {}", e))?
+        .join("icons");
+    let cached = icons::cached_icon(&cache_dir, &jar_path, &icon)?;
+    Ok(Some(cached.to_string_lossy().to_string()))
+}
+
+#[tauri::command]
+fn list_namespaces() -> Result<Vec<NamespaceOwner>, String> {
+    get_db()
+        .list_namespaces()
+        .map_err(|e| format!("Failed to list namespaces: {}", e))
+}
+
+#[tauri::command]
+fn list_items(namespace: Option<String>) -> Result<Vec<Item>, String> {
+    get_db()
+        .list_items(namespace.as_deref())
+        .map_err(|e| format!("Failed to list items: {}", e))
+}
+
+#[tauri::command]
+fn autocomplete_items(prefix: String, limit: Option<i64>) -> Result<Vec<Item>, String> {
+    get_db()
+        .autocomplete_items(&prefix, limit.unwrap_or(20))
+        .map_err(|e| format!("Autocomplete failed: {}", e))
+}
+
+#[tauri::command]
+fn search_items(query: String, limit: Option<i64>, match_mode: Option<MatchMode>) -> Result<Vec<Item>, String> {
+    get_db()
+        .search_items(&query, limit.unwrap_or(50), match_mode.unwrap_or_default())
+        .map_err(|e| format!("Search failed: {}", e))
+}
+
+#[tauri::command]
+fn get_mod_dependency_graph() -> Result<depgraph::DependencyGraph, String> {
+    // Hidden mods are still installed, so they still satisfy dependencies
+    let mods = list_mods(None, Some(true))?;
+    Ok(depgraph::build_dependency_graph(&mods))
+}
+
+/// Edges from each mod to the mods whose items its recipes use.
+#[tauri::command]
+fn get_recipe_dependency_graph() -> Result<Vec<depgraph::RecipeDependency>, String> {
+    get_db()
+        .recipe_dependencies()
+        .map_err(|e| format!("Failed to build recipe graph: {}", e))
+}
+
+/// Mods whose declared Minecraft versions exclude the pack's version.
+#[tauri::command]
+fn find_version_mismatches(minecraft_version: String) -> Result<Vec<ModInfo>, String> {
+    let mods = list_mods(None, Some(true))?;
+    Ok(mods
+        .into_iter()
+        .filter(|m| {
+            let range = m.minecraft_versions.as_deref();
+            range.and_then(|r| mcversion::range_includes(r, &minecraft_version)) == Some(false)
+        })
+        .collect())
+}
+
+/// How a player can get an item: recipes, trades and loot tables.
+#[tauri::command]
+fn get_item_sources(item: String) -> Result<ItemSources, String> {
+    get_db()
+        .get_item_sources(&item)
+        .map_err(|e| format!("Failed to look up item sources: {}", e))
+}
+
+/// Item tags holding an item, including through nested tags.
+#[tauri::command]
+fn get_item_tags(item: String) -> Result<Vec<String>, String> {
+    get_db()
+        .tags_containing(&item)
+        .map_err(|e| format!("Failed to look up item tags: {}", e))
+}
+
+/// Items in a tag, following nested tags.
+#[tauri::command]
+fn get_tag_members(tag: String) -> Result<Vec<String>, String> {
+    get_db()
+        .tag_members(&tag)
+        .map_err(|e| format!("Failed to look up tag: {}", e))
+}
+
+/// Runs several independent queries at once and returns their results by key.
+#[tauri::command]
+async fn batch_query(requests: Vec<batch::BatchRequest>) -> Result<HashMap<String, batch::BatchResult>, String> {
+    tauri::async_runtime::spawn_blocking(move || batch::run_batch(&get_db(), requests))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
+
+/// Writes an anonymized stats JSON for attaching to issue reports.
+#[tauri::command]
+fn export_pack_stats_bundle(path: String, options: Option<stats::StatsOptions>) -> Result<(), String> {
+    let stats = stats::collect_pack_stats(&get_db(), &options.unwrap_or_default())
+        .map_err(|e| format!("Failed to collect stats: {}", e))?;
+    let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[tauri::command]
+fn db_stats() -> Result<DbStats, String> {
+    get_db()
+        .db_stats()
+        .map_err(|e| format!("Failed to read database stats: {}", e))
+}
+
+/// Runs VACUUM and ANALYZE, then returns the updated stats.
+#[tauri::command]
+async fn maintain_database() -> Result<DbStats, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let db = get_db();
+        db.run_maintenance().map_err(|e| format!("Maintenance failed: {}", e))?;
+        db.db_stats().map_err(|e| format!("Failed to read database stats: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[tauri::command]
+fn backup_database(path: String) -> Result<(), String> {
+    get_db()
+        .backup_to(Path::new(&path))
+        .map_err(|e| format!("Backup failed: {}", e))
+}
+
+/// Replaces the open workspace's data with a backup.
+#[tauri::command]
+fn restore_database(path: String) -> Result<(), String> {
+    get_db()
+        .restore_from(Path::new(&path))
+        .map_err(|e| format!("Restore failed: {}", e))
+}
+
 #[tauri::command]
 fn get_recipe_count() -> Result<i64, String> {
     get_db()
@@ -207,21 +1780,150 @@ pub fn run() {
         .setup(|app| {
             // Initialize database in app data directory
             let app_data = app.path().app_data_dir().expect("Failed to get app data dir");
-            let db_path = app_data.join("recipes.db");
+            let workspaces = WorkspaceRegistry::load(&app_data).expect("Failed to load workspaces");
 
-            let db = Database::new(db_path).expect("Failed to initialize database");
-            DATABASE.set(db).expect("Database already initialized");
+            let db = Database::new(workspaces.active_path()).expect("Failed to initialize database");
+            *DATABASE.write().unwrap() = Some(Arc::new(db));
+            WORKSPACES.set(Mutex::new(workspaces)).ok().expect("Workspaces already initialized");
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             scan_folder,
+            scan_datapacks_folder,
+            list_workspaces,
+            create_workspace,
+            open_workspace,
+            rename_workspace,
+            move_workspace,
+            export_workspace,
+            import_workspace,
+            delete_workspace,
+            open_comparison_workspace,
+            close_comparison_workspace,
+            compare_workspaces,
             get_jar_contents,
+            scan_config_folder,
+            search_configs,
+            search_recipes_fulltext,
             extract_all_recipes,
             search_recipes_by_output,
             search_recipes_by_ingredient,
+            search_recipes,
+            get_search_facets,
+            export_recipes,
+            dump_recipes,
+            generate_removal_datapack,
+            get_script_engine,
+            set_script_engine,
+            export_recipe_script,
+            search_recipes_by_type,
+            find_craftable_recipes,
+            get_crafting_tree,
+            plan_crafting,
+            find_recipe_cycles,
+            find_recipe_conflicts,
+            find_duplicate_outputs,
+            get_tag_unification,
+            find_unobtainable_items,
+            find_dead_end_outputs,
+            find_dangling_references,
+            find_broken_tags,
+            get_item_complexity,
+            get_ingredient_usage,
+            get_mod_stats,
+            get_progression_gates,
+            set_progression_gates,
+            get_progression_report,
+            get_value_seeds,
+            set_value_seeds,
+            compute_item_values,
+            simulate_recipe_removal,
+            list_recipes_page,
+            search_recipes_page,
+            get_random_recipes,
+            query_recipes,
             list_recipes,
-            get_recipe_count
+            get_recipe_types,
+            get_recipe_count,
+            db_stats,
+            maintain_database,
+            backup_database,
+            restore_database,
+            browse_fluid_conversions,
+            get_item_names,
+            list_machines,
+            list_machine_recipes,
+            list_quarantined_jars,
+            quarantine_jar,
+            release_quarantined_jar,
+            list_enchantments,
+            search_enchantments,
+            search_enchantments_by_item,
+            get_mod_recipe_trend,
+            list_sessions,
+            diff_sessions,
+            diff_session_tags,
+            export_changelog,
+            export_report,
+            export_recipe_book,
+            export_wiki,
+            export_xlsx,
+            get_update_impact,
+            get_recipe_history,
+            get_pinned_filter,
+            pin_filter,
+            clear_pinned_filter,
+            list_worldgen,
+            search_worldgen,
+            get_recipe,
+            get_recipes,
+            compare_recipes,
+            find_similar_recipes,
+            compare_with_vanilla,
+            get_recipe_providers,
+            list_recipe_overrides,
+            get_recipe_note,
+            set_recipe_note,
+            create_collection,
+            delete_collection,
+            list_collections,
+            add_to_collection,
+            remove_from_collection,
+            get_collection,
+            save_search,
+            list_saved_searches,
+            run_saved_search,
+            delete_saved_search,
+            list_search_history,
+            replay_search,
+            clear_search_history,
+            get_recipe_id_prefix,
+            set_recipe_id_prefix,
+            suggest_recipe_id,
+            list_trades,
+            get_item_sources,
+            get_item_tags,
+            get_tag_members,
+            list_mods,
+            set_mod_hidden,
+            set_recipe_hidden,
+            find_version_mismatches,
+            get_mod_dependency_graph,
+            get_recipe_dependency_graph,
+            get_mod_icon,
+            get_mod,
+            get_mod_detail,
+            list_recipes_by_mod,
+            remove_mod,
+            find_mods_by_hash,
+            list_namespaces,
+            list_items,
+            search_items,
+            autocomplete_items,
+            list_item_components,
+            batch_query,
+            export_pack_stats_bundle
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,80 @@
+use serde::Serialize;
+
+/// Where a copy of a recipe comes from. The game loads the kinds in this
+/// order, and a later copy of a recipe id replaces an earlier one.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    Vanilla,
+    Mod,
+    /// A jar with data but no loader metadata
+    Datapack,
+}
+
+/// Classifies a jar by its metadata, falling back to its file name for the
+/// game jar (`1.20.1.jar`, `minecraft-client.jar`).
+pub fn provider_kind(jar_name: &str, loader: Option<&str>, loader_mod_id: Option<&str>) -> ProviderKind {
+    let name = jar_name.to_lowercase();
+    let game_jar = name.starts_with("minecraft") || name.starts_with("client") || name.starts_with("server")
+        || name.starts_with(|c: char| c.is_ascii_digit());
+    match (loader, loader_mod_id) {
+        (_, Some("minecraft")) => ProviderKind::Vanilla,
+        (None, _) if game_jar => ProviderKind::Vanilla,
+        (None, _) => ProviderKind::Datapack,
+        _ => ProviderKind::Mod,
+    }
+}
+
+/// A provider as load order sees it.
+pub struct LoadEntry<'a> {
+    pub kind: ProviderKind,
+    pub jar_name: &'a str,
+    /// Loader mod id, which mods load by; datapacks go by jar name
+    pub sort_name: &'a str,
+}
+
+/// Index of the entry that loads last and so wins. Kinds load in order;
+/// within a kind, jars named in `load_order` load after the rest, in the
+/// order given, and the rest load alphabetically.
+pub fn winner(entries: &[LoadEntry], load_order: &[String]) -> Option<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, entry)| load_key(entry, load_order))
+        .map(|(i, _)| i)
+}
+
+/// Sorts providers in the order `winner` loads them, last loaded greatest.
+pub fn load_key(entry: &LoadEntry, load_order: &[String]) -> (ProviderKind, Option<usize>, String) {
+    let position = load_order.iter().position(|name| name == entry.jar_name);
+    (entry.kind, position, entry.sort_name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: ProviderKind, jar_name: &str) -> LoadEntry<'_> {
+        LoadEntry { kind, jar_name, sort_name: jar_name }
+    }
+
+    #[test]
+    fn test_provider_kind() {
+        assert_eq!(provider_kind("1.20.1.jar", None, None), ProviderKind::Vanilla);
+        assert_eq!(provider_kind("create-1.0.jar", Some("forge"), Some("create")), ProviderKind::Mod);
+        assert_eq!(provider_kind("tweaks.jar", None, None), ProviderKind::Datapack);
+    }
+
+    #[test]
+    fn test_winner() {
+        let entries = [
+            entry(ProviderKind::Datapack, "b.jar"),
+            entry(ProviderKind::Vanilla, "1.20.1.jar"),
+            entry(ProviderKind::Datapack, "a.jar"),
+            entry(ProviderKind::Mod, "z.jar"),
+        ];
+        assert_eq!(winner(&entries, &[]), Some(0));
+        assert_eq!(winner(&entries, &["b.jar".to_string(), "a.jar".to_string()]), Some(2));
+        assert_eq!(winner(&entries[1..2], &[]), Some(0));
+    }
+}
@@ -0,0 +1,71 @@
+use serde_json::Value;
+
+/// Mod folders that hold a `machines/` folder of their own.
+const MACHINE_MOD_FOLDERS: [&str; 1] = ["modular_machinery_reborn"];
+
+/// Returns the machine id for machine definition files, which live under
+/// `data/<namespace>/machines/` (Custom Machinery) or
+/// `data/<namespace>/modular_machinery_reborn/machines/`.
+pub fn machine_id_from_path(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path.split('/').collect();
+    if parts.len() < 4 || parts[0] != "data" || !path.ends_with(".json") {
+        return None;
+    }
+
+    let start = if parts[2] == "machines" {
+        3
+    } else if parts.len() > 4 && MACHINE_MOD_FOLDERS.contains(&parts[2]) && parts[3] == "machines" {
+        4
+    } else {
+        return None;
+    };
+
+    let name = parts[start..].join("/");
+    Some(format!("{}:{}", parts[1], name.trim_end_matches(".json")))
+}
+
+/// Reads the display name of a machine definition. Names may be plain
+/// strings or text components (`{"text": ...}` or `{"translate": ...}`).
+pub fn parse_machine_name(json_str: &str) -> Result<Option<String>, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let name = match value.get("name") {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(Value::Object(obj)) => obj.get("text")
+            .or(obj.get("translate"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        _ => None,
+    };
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_machine_id_from_path() {
+        assert_eq!(
+            machine_id_from_path("data/mypack/machines/ore_washer.json"),
+            Some("mypack:ore_washer".to_string())
+        );
+        assert_eq!(
+            machine_id_from_path("data/mypack/modular_machinery_reborn/machines/big/smelter.json"),
+            Some("mypack:big/smelter".to_string())
+        );
+        assert_eq!(machine_id_from_path("data/mypack/recipes/ore_washer.json"), None);
+        assert_eq!(machine_id_from_path("data/mypack/recipes/machines/casing.json"), None);
+    }
+
+    #[test]
+    fn test_parse_machine_name() {
+        assert_eq!(
+            parse_machine_name(r#"{"name": {"text": "Ore Washer", "color": "blue"}}"#).unwrap(),
+            Some("Ore Washer".to_string())
+        );
+        assert_eq!(parse_machine_name(r#"{"components": []}"#).unwrap(), None);
+    }
+}
@@ -0,0 +1,159 @@
+use regex::{Regex, RegexBuilder};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::{Connection, Error, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// How a search term is compared against ids and names.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    Exact,
+    Prefix,
+    #[default]
+    Substring,
+    /// Trigram similarity, tolerant of typos
+    Fuzzy,
+    /// Regular expression, unanchored unless the pattern uses `^` and `$`
+    Regex,
+}
+
+/// Lowest trigram similarity a fuzzy match needs, as in pg_trgm.
+const FUZZY_THRESHOLD: f64 = 0.3;
+
+/// A regex query is abandoned after this long.
+const REGEX_TIMEOUT: Duration = Duration::from_secs(10);
+/// Caps the compiled size of a pattern, so huge repetitions are rejected
+/// instead of eating memory.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Registers `fuzzy_match(value, term)` and `regex_match(value, pattern)` on
+/// a connection, which conditions from `condition` call.
+pub fn register_functions(conn: &Connection) -> SqliteResult<()> {
+    conn.create_scalar_function(
+        "fuzzy_match",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| {
+            let value = ctx.get::<Option<String>>(0)?;
+            let term = ctx.get::<String>(1)?;
+            Ok(value.is_some_and(|value| fuzzy_match(&value, &term)))
+        },
+    )?;
+    conn.create_scalar_function(
+        "regex_match",
+        2,
+        // Not deterministic: the same row can fail once the deadline passes
+        FunctionFlags::SQLITE_UTF8,
+        |ctx| {
+            // Compiled once per statement; the deadline starts with it
+            let compiled = ctx.get_or_create_aux(1, |pattern| -> Result<(Regex, Instant), Error> {
+                let regex = compile_regex(pattern.as_str()?).map_err(|e| Error::UserFunctionError(e.into()))?;
+                Ok((regex, Instant::now() + REGEX_TIMEOUT))
+            })?;
+            let (regex, deadline) = &*compiled;
+            if Instant::now() > *deadline {
+                return Err(Error::UserFunctionError("Regex search timed out".into()));
+            }
+            let value = ctx.get::<Option<String>>(0)?;
+            Ok(value.is_some_and(|value| regex.is_match(&value)))
+        },
+    )
+}
+
+/// Compiles a search pattern, rejecting ones too large to run.
+pub fn compile_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))
+}
+
+/// SQL condition matching `column` against `term`, pushing the term onto
+/// `params`.
+pub fn condition(column: &str, term: &str, mode: MatchMode, params: &mut Vec<Value>) -> String {
+    let escaped = escape_like(term);
+    let (sql, param) = match mode {
+        MatchMode::Exact => (format!("{} = ?{}", column, params.len() + 1), term.to_string()),
+        MatchMode::Prefix => (format!("{} LIKE ?{} ESCAPE '\\'", column, params.len() + 1), format!("{}%", escaped)),
+        MatchMode::Substring => (format!("{} LIKE ?{} ESCAPE '\\'", column, params.len() + 1), format!("%{}%", escaped)),
+        MatchMode::Fuzzy => (format!("fuzzy_match({}, ?{})", column, params.len() + 1), term.to_string()),
+        MatchMode::Regex => (format!("regex_match({}, ?{})", column, params.len() + 1), term.to_string()),
+    };
+    params.push(Value::from(param));
+    sql
+}
+
+/// Escapes LIKE wildcards for use with `ESCAPE '\\'`.
+pub fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Whether `value` is close enough to `term`. A term without a namespace is
+/// also compared with the value's path, so `iron_ingto` finds
+/// `minecraft:iron_ingot`.
+pub fn fuzzy_match(value: &str, term: &str) -> bool {
+    let path = match value.split_once(':') {
+        Some((_, path)) if !term.contains(':') => path,
+        _ => value,
+    };
+    trigram_similarity(path, term) >= FUZZY_THRESHOLD || trigram_similarity(value, term) >= FUZZY_THRESHOLD
+}
+
+/// Shared trigrams over all distinct trigrams of the two strings, padded
+/// so word starts and ends count.
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+}
+
+fn trigrams(s: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = format!("  {} ", s.to_lowercase()).chars().collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::RecipeSort;
+    use crate::test_support::{add_shapeless, temp_db};
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert!(fuzzy_match("minecraft:iron_ingot", "iron_ingto"));
+        assert!(fuzzy_match("minecraft:iron_ingot", "minecraft:iron_ingot"));
+        assert!(!fuzzy_match("minecraft:oak_planks", "iron_ingot"));
+    }
+
+    #[test]
+    fn test_condition_escapes_wildcards() {
+        let mut params = Vec::new();
+        assert_eq!(condition("r.result_item", "iron_ingot", MatchMode::Prefix, &mut params), "r.result_item LIKE ?1 ESCAPE '\\'");
+        assert_eq!(params, vec![Value::from("iron\\_ingot%".to_string())]);
+    }
+
+    #[test]
+    fn test_compile_regex() {
+        assert!(compile_regex("^minecraft:.*_ingot$").unwrap().is_match("minecraft:iron_ingot"));
+        assert!(compile_regex("iron(").unwrap_err().starts_with("Invalid regex"));
+        assert!(compile_regex("(a{1000}){1000}").unwrap_err().starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn test_regex_search() {
+        let mut params = vec![Value::from("first".to_string())];
+        assert_eq!(condition("r.result_item", "_ingot$", MatchMode::Regex, &mut params), "regex_match(r.result_item, ?2)");
+        assert_eq!(params[1], Value::from("_ingot$".to_string()));
+
+        let db = temp_db();
+        let pack = db.insert_mod("a.jar", "/mods/a.jar", None).unwrap();
+        add_shapeless(&db, pack, "a:iron_ingot", "minecraft:iron_ingot", &["minecraft:raw_iron"]);
+        add_shapeless(&db, pack, "a:iron_nugget", "minecraft:iron_nugget", &["minecraft:iron_ingot"]);
+        let found = db.search_by_output("^minecraft:iron_(ingot|block)$", MatchMode::Regex, RecipeSort::default()).unwrap();
+        let ids: Vec<&str> = found.iter().map(|r| r.recipe_id.as_str()).collect();
+        assert_eq!(ids, vec!["a:iron_ingot"]);
+        assert!(db.search_by_output("iron(", MatchMode::Regex, RecipeSort::default()).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use std::cmp::Ordering;
+
+/// Whether a declared Minecraft version range accepts `version`. Handles
+/// Maven ranges from mods.toml (`[1.20.1,1.21)`) and Fabric/Quilt predicates
+/// (`>=1.20 <1.21`, `~1.20.1`, `1.20.x`, alternatives joined with `||`).
+/// Returns None when the range can't be understood.
+pub fn range_includes(range: &str, version: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.starts_with('[') || range.starts_with('(') {
+        return maven_includes(range, version);
+    }
+    let mut any = false;
+    for alternative in range.split("||") {
+        let mut all = true;
+        for predicate in alternative.split_whitespace() {
+            all &= predicate_includes(predicate, version)?;
+        }
+        any |= all;
+    }
+    Some(any)
+}
+
+/// Checks a union of Maven intervals such as `[1.20,1.20.1],[1.21,)`.
+fn maven_includes(range: &str, version: &str) -> Option<bool> {
+    let mut rest = range;
+    let mut any = false;
+    while let Some(start) = rest.find(['[', '(']) {
+        let end = start + rest[start..].find([']', ')'])?;
+        let interval = &rest[start..=end];
+        let inner = &interval[1..interval.len() - 1];
+
+        let included = match inner.split_once(',') {
+            Some((low, high)) => {
+                let low_ok = low.trim().is_empty() || match compare(version, low.trim()) {
+                    Ordering::Greater => true,
+                    Ordering::Equal => interval.starts_with('['),
+                    Ordering::Less => false,
+                };
+                let high_ok = high.trim().is_empty() || match compare(version, high.trim()) {
+                    Ordering::Less => true,
+                    Ordering::Equal => interval.ends_with(']'),
+                    Ordering::Greater => false,
+                };
+                low_ok && high_ok
+            }
+            // `[1.20.1]` pins one version
+            None => compare(version, inner.trim()) == Ordering::Equal,
+        };
+        any |= included;
+        rest = &rest[end + 1..];
+    }
+    Some(any)
+}
+
+fn predicate_includes(predicate: &str, version: &str) -> Option<bool> {
+    if predicate == "*" {
+        return Some(true);
+    }
+    if let Some(bound) = predicate.strip_prefix(">=") {
+        return Some(compare(version, bound).is_ge());
+    }
+    if let Some(bound) = predicate.strip_prefix("<=") {
+        return Some(compare(version, bound).is_le());
+    }
+    if let Some(bound) = predicate.strip_prefix('>') {
+        return Some(compare(version, bound).is_gt());
+    }
+    if let Some(bound) = predicate.strip_prefix('<') {
+        return Some(compare(version, bound).is_lt());
+    }
+    if let Some(bound) = predicate.strip_prefix('=') {
+        return Some(compare(version, bound).is_eq());
+    }
+    // `~1.20.1` allows patch changes, `^1.20.1` allows minor changes
+    if let Some(bound) = predicate.strip_prefix('~') {
+        let prefix: Vec<&str> = bound.split('.').take(2).collect();
+        return Some(compare(version, bound).is_ge() && has_prefix(version, &prefix));
+    }
+    if let Some(bound) = predicate.strip_prefix('^') {
+        let prefix: Vec<&str> = bound.split('.').take(1).collect();
+        return Some(compare(version, bound).is_ge() && has_prefix(version, &prefix));
+    }
+    if let Some(prefix) = predicate.strip_suffix(".x").or(predicate.strip_suffix(".*")) {
+        let prefix: Vec<&str> = prefix.split('.').collect();
+        return Some(has_prefix(version, &prefix));
+    }
+    if predicate.starts_with(|c: char| c.is_ascii_digit()) {
+        return Some(compare(version, predicate) == Ordering::Equal);
+    }
+    None
+}
+
+fn has_prefix(version: &str, prefix: &[&str]) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    prefix.iter().enumerate().all(|(i, p)| parts.get(i) == Some(p))
+}
+
+/// Newest release whose data pack format is known.
+const LATEST_KNOWN: &str = "1.21.5";
+
+/// Data pack formats by the first release using them, newest first.
+const PACK_FORMATS: &[(&str, u32)] = &[
+    ("1.21.5", 71),
+    ("1.21.4", 61),
+    ("1.21.2", 57),
+    ("1.21", 48),
+    ("1.20.5", 41),
+    ("1.20.3", 26),
+    ("1.20.2", 18),
+    ("1.20", 15),
+    ("1.19.4", 12),
+    ("1.19", 10),
+    ("1.18.2", 9),
+    ("1.18", 8),
+    ("1.17", 7),
+    ("1.16.2", 6),
+    ("1.15", 5),
+];
+
+/// The `pack_format` a data pack for `version` declares, or None for
+/// versions older than any known or newer than `LATEST_KNOWN`.
+pub fn pack_format(version: &str) -> Option<u32> {
+    if compare(version, LATEST_KNOWN).is_gt() {
+        return None;
+    }
+    PACK_FORMATS
+        .iter()
+        .find(|(first, _)| compare(version, first).is_ge())
+        .map(|(_, format)| *format)
+}
+
+/// Data pack folder holding recipes, which lost its plural in 1.21.
+pub fn recipe_folder(version: &str) -> &'static str {
+    if compare(version, "1.21").is_ge() {
+        "recipe"
+    } else {
+        "recipes"
+    }
+}
+
+/// Compares dotted numeric versions; missing parts count as 0 and anything
+/// after a `-` (pre-release tags) is ignored.
+fn compare(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u32> {
+        v.split('-')
+            .next()
+            .unwrap_or(v)
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parse(a), parse(b));
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maven_ranges() {
+        assert_eq!(range_includes("[1.20.1,1.21)", "1.20.4"), Some(true));
+        assert_eq!(range_includes("[1.20.1,1.21)", "1.21"), Some(false));
+        assert_eq!(range_includes("[1.21,)", "1.21.1"), Some(true));
+        assert_eq!(range_includes("[1.20.1]", "1.20.1"), Some(true));
+        assert_eq!(range_includes("[1.19.2],[1.20,1.20.1]", "1.20.1"), Some(true));
+    }
+
+    #[test]
+    fn test_pack_format() {
+        assert_eq!(pack_format("1.20.1"), Some(15));
+        assert_eq!(pack_format("1.21.1"), Some(48));
+        assert_eq!(pack_format("1.16"), Some(5));
+        assert_eq!(pack_format("1.16.1"), Some(5));
+        assert_eq!(pack_format("1.16.2"), Some(6));
+        assert_eq!(pack_format("1.12.2"), None);
+        assert_eq!(pack_format("1.21.5"), Some(71));
+        assert_eq!(pack_format("1.21.6"), None);
+        assert_eq!(recipe_folder("1.20.4"), "recipes");
+        assert_eq!(recipe_folder("1.21"), "recipe");
+    }
+
+    #[test]
+    fn test_fabric_predicates() {
+        assert_eq!(range_includes(">=1.20 <1.21", "1.20.6"), Some(true));
+        assert_eq!(range_includes(">=1.20 <1.21", "1.21"), Some(false));
+        assert_eq!(range_includes("~1.20.1", "1.20.4"), Some(true));
+        assert_eq!(range_includes("~1.20.1", "1.21"), Some(false));
+        assert_eq!(range_includes("1.20.x || 1.21", "1.21"), Some(true));
+        assert_eq!(range_includes("1.20.1", "1.21.1"), Some(false));
+        assert_eq!(range_includes("*", "1.21.1"), Some(true));
+        assert_eq!(range_includes("latest", "1.21.1"), None);
+    }
+}
@@ -0,0 +1,400 @@
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use toml::{Table, Value};
+
+/// Metadata files checked for mod info, in order. Quilt jars often ship a
+/// fabric.mod.json too, so quilt.mod.json is preferred.
+pub const METADATA_PATHS: &[&str] = &[
+    "META-INF/neoforge.mods.toml",
+    "META-INF/mods.toml",
+    "quilt.mod.json",
+    "fabric.mod.json",
+];
+
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// What a jar declares about one of its mods in its loader metadata file.
+#[derive(Debug, PartialEq)]
+pub struct ModMetadata {
+    /// `forge`, `neoforge`, `fabric` or `quilt`
+    pub loader: String,
+    pub mod_id: String,
+    pub display_name: Option<String>,
+    pub version: Option<String>,
+    /// Fabric/Quilt side restriction: `*`, `client` or `server`
+    pub environment: Option<String>,
+    /// Path of the mod's logo inside the jar
+    pub icon: Option<String>,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+    pub issue_tracker: Option<String>,
+    /// Authors as one display string, e.g. `simibubi, MRH0`
+    pub authors: Option<String>,
+    pub dependencies: Vec<ModDependency>,
+}
+
+impl ModMetadata {
+    /// The Minecraft versions the mod targets, as declared on its
+    /// `minecraft` dependency.
+    pub fn minecraft_versions(&self) -> Option<&str> {
+        self.dependencies
+            .iter()
+            .find(|dep| dep.mod_id == "minecraft")
+            .and_then(|dep| dep.version_range.as_deref())
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ModDependency {
+    pub mod_id: String,
+    pub version_range: Option<String>,
+    pub required: bool,
+}
+
+/// Parses whichever of `METADATA_PATHS` was found at `path`. Returns every
+/// mod the jar declares, main mod first.
+pub fn parse_metadata(path: &str, contents: &str, manifest: Option<&str>) -> Result<Vec<ModMetadata>, String> {
+    match path {
+        "fabric.mod.json" => Ok(vec![parse_fabric_mod_json(contents)?]),
+        "quilt.mod.json" => Ok(vec![parse_quilt_mod_json(contents)?]),
+        _ => {
+            let mut mods = parse_mods_toml(contents, manifest)?;
+            if path.contains("neoforge") {
+                mods.iter_mut().for_each(|m| m.loader = "neoforge".to_string());
+            }
+            Ok(mods)
+        }
+    }
+}
+
+/// Parses a Forge/NeoForge mods.toml, which may declare several mods (e.g.
+/// an API beside the main mod). `${file.jarVersion}` versions are resolved
+/// from the jar manifest when it is available.
+pub fn parse_mods_toml(contents: &str, manifest: Option<&str>) -> Result<Vec<ModMetadata>, String> {
+    let table: Table = contents.parse().map_err(|e| format!("Invalid TOML: {}", e))?;
+
+    let mods: Vec<ModMetadata> = table
+        .get("mods")
+        .and_then(|m| m.as_array())
+        .map(|mods| {
+            mods.iter()
+                .filter_map(|m| m.as_table())
+                .filter_map(|m| parse_toml_mod(&table, m, manifest))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if mods.is_empty() {
+        return Err("No [[mods]] entry with a modId".to_string());
+    }
+    Ok(mods)
+}
+
+fn parse_toml_mod(table: &Table, entry: &Table, manifest: Option<&str>) -> Option<ModMetadata> {
+    let mod_id = get_str(entry, "modId")?;
+
+    let version = get_str(entry, "version").and_then(|version| {
+        if version.contains("${file.jarVersion}") {
+            manifest.and_then(|m| manifest_value(m, "Implementation-Version"))
+        } else {
+            Some(version)
+        }
+    });
+
+    let dependencies = table
+        .get("dependencies")
+        .and_then(|d| d.get(&mod_id))
+        .and_then(|d| d.as_array())
+        .map(|deps| deps.iter().filter_map(|d| d.as_table()).filter_map(parse_dependency).collect())
+        .unwrap_or_default();
+
+    Some(ModMetadata {
+        loader: "forge".to_string(),
+        display_name: get_str(entry, "displayName"),
+        mod_id,
+        version,
+        environment: None,
+        // logoFile may be set per mod or for the whole file
+        icon: get_str(entry, "logoFile").or_else(|| get_str(table, "logoFile")),
+        license: get_str(table, "license"),
+        homepage: get_str(entry, "displayURL"),
+        issue_tracker: get_str(table, "issueTrackerURL"),
+        authors: get_str(entry, "authors"),
+        dependencies,
+    })
+}
+
+/// Parses a Fabric fabric.mod.json. `depends` are required; `recommends` are not.
+pub fn parse_fabric_mod_json(contents: &str) -> Result<ModMetadata, String> {
+    let value: JsonValue = serde_json::from_str(contents)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let mut dependencies = fabric_dependencies(value.get("depends"), true);
+    dependencies.extend(fabric_dependencies(value.get("recommends"), false));
+
+    Ok(ModMetadata {
+        loader: "fabric".to_string(),
+        mod_id: json_str(&value, "id").ok_or("Missing id")?,
+        display_name: json_str(&value, "name"),
+        version: json_str(&value, "version"),
+        environment: json_str(&value, "environment"),
+        icon: value.get("icon").and_then(icon_path),
+        license: value.get("license").and_then(license),
+        homepage: value.get("contact").and_then(|c| json_str(c, "homepage")),
+        issue_tracker: value.get("contact").and_then(|c| json_str(c, "issues")),
+        authors: value.get("authors").and_then(authors),
+        dependencies,
+    })
+}
+
+/// Reads a Fabric `{"modid": "range" | ["range", ...]}` dependency map.
+fn fabric_dependencies(deps: Option<&JsonValue>, required: bool) -> Vec<ModDependency> {
+    let Some(JsonValue::Object(deps)) = deps else {
+        return Vec::new();
+    };
+    deps.iter()
+        .map(|(mod_id, range)| ModDependency {
+            mod_id: mod_id.clone(),
+            version_range: version_range(range),
+            required,
+        })
+        .collect()
+}
+
+/// Parses a Quilt quilt.mod.json, where dependencies are ids or objects
+/// with `id`, `versions` and `optional`.
+pub fn parse_quilt_mod_json(contents: &str) -> Result<ModMetadata, String> {
+    let value: JsonValue = serde_json::from_str(contents)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+    let loader = value.get("quilt_loader").ok_or("Missing quilt_loader")?;
+    let metadata = loader.get("metadata");
+    let contact = metadata.and_then(|m| m.get("contact"));
+
+    let dependencies = loader
+        .get("depends")
+        .and_then(|d| d.as_array())
+        .map(|deps| {
+            deps.iter()
+                .filter_map(|dep| match dep {
+                    JsonValue::String(id) => Some(ModDependency {
+                        mod_id: id.clone(),
+                        version_range: None,
+                        required: true,
+                    }),
+                    JsonValue::Object(_) => Some(ModDependency {
+                        mod_id: json_str(dep, "id")?,
+                        version_range: dep.get("versions").and_then(version_range),
+                        required: !dep.get("optional").and_then(|o| o.as_bool()).unwrap_or(false),
+                    }),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(ModMetadata {
+        loader: "quilt".to_string(),
+        mod_id: json_str(loader, "id").ok_or("Missing id")?,
+        display_name: metadata.and_then(|m| json_str(m, "name")),
+        version: json_str(loader, "version"),
+        environment: value.get("minecraft").and_then(|m| json_str(m, "environment")),
+        icon: metadata.and_then(|m| m.get("icon")).and_then(icon_path),
+        license: metadata.and_then(|m| m.get("license")).and_then(license),
+        homepage: contact.and_then(|c| json_str(c, "homepage")),
+        issue_tracker: contact.and_then(|c| json_str(c, "issues")),
+        // Quilt maps contributor names to roles
+        authors: metadata
+            .and_then(|m| m.get("contributors"))
+            .and_then(|c| c.as_object())
+            .map(|c| c.keys().cloned().collect::<Vec<_>>().join(", ")),
+        dependencies,
+    })
+}
+
+/// A license id, a list of them, or Quilt's `{"id": ...}` form.
+fn license(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Array(arr) => {
+            let ids: Vec<String> = arr.iter().filter_map(license).collect();
+            (!ids.is_empty()).then(|| ids.join(", "))
+        }
+        JsonValue::Object(_) => json_str(value, "id").or_else(|| json_str(value, "name")),
+        _ => None,
+    }
+}
+
+/// Fabric authors: names or `{"name": ...}` person objects.
+fn authors(value: &JsonValue) -> Option<String> {
+    let names: Vec<String> = value
+        .as_array()?
+        .iter()
+        .filter_map(|a| a.as_str().map(|s| s.to_string()).or_else(|| json_str(a, "name")))
+        .collect();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// An icon path, or the largest of a `{"16": path, "64": path}` size map.
+fn icon_path(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Object(sizes) => sizes
+            .iter()
+            .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+            .and_then(|(_, path)| path.as_str())
+            .map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+/// A version predicate or a list of alternatives, joined with `||`.
+fn version_range(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        JsonValue::Array(arr) => {
+            let ranges: Vec<&str> = arr.iter().filter_map(|v| v.as_str()).collect();
+            (!ranges.is_empty()).then(|| ranges.join(" || "))
+        }
+        _ => None,
+    }
+}
+
+fn json_str(value: &JsonValue, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn parse_dependency(dep: &Table) -> Option<ModDependency> {
+    // Forge uses `mandatory`, NeoForge uses `type = "required"`
+    let required = match (dep.get("mandatory"), get_str(dep, "type")) {
+        (Some(Value::Boolean(mandatory)), _) => *mandatory,
+        (_, Some(kind)) => kind == "required",
+        _ => true,
+    };
+    Some(ModDependency {
+        mod_id: get_str(dep, "modId")?,
+        version_range: get_str(dep, "versionRange"),
+        required,
+    })
+}
+
+fn get_str(table: &Table, key: &str) -> Option<String> {
+    table.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// Reads a `Key: value` line from a jar manifest.
+fn manifest_value(manifest: &str, key: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mods_toml() {
+        let toml = r#"
+modLoader = "javafml"
+loaderVersion = "[47,)"
+
+[[mods]]
+modId = "create"
+version = "${file.jarVersion}"
+displayName = "Create"
+
+[[dependencies.create]]
+modId = "forge"
+mandatory = true
+versionRange = "[47.1.3,)"
+
+[[dependencies.create]]
+modId = "jei"
+type = "optional"
+"#;
+        let manifest = "Manifest-Version: 1.0\r\nImplementation-Version: 0.5.1.f\r\n";
+
+        let mods = parse_mods_toml(toml, Some(manifest)).unwrap();
+        let meta = &mods[0];
+        assert_eq!(meta.mod_id, "create");
+        assert_eq!(meta.display_name, Some("Create".to_string()));
+        assert_eq!(meta.version, Some("0.5.1.f".to_string()));
+        assert_eq!(meta.dependencies, vec![
+            ModDependency { mod_id: "forge".to_string(), version_range: Some("[47.1.3,)".to_string()), required: true },
+            ModDependency { mod_id: "jei".to_string(), version_range: None, required: false },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_multiple_mods() {
+        let toml = r#"
+[[mods]]
+modId = "ae2"
+displayName = "Applied Energistics 2"
+
+[[mods]]
+modId = "ae2api"
+
+[[dependencies.ae2api]]
+modId = "minecraft"
+versionRange = "[1.20.1,1.21)"
+"#;
+        let mods = parse_metadata("META-INF/neoforge.mods.toml", toml, None).unwrap();
+        assert_eq!(mods.len(), 2);
+        assert_eq!(mods[1].mod_id, "ae2api");
+        assert_eq!(mods[1].loader, "neoforge");
+        assert_eq!(mods[1].minecraft_versions(), Some("[1.20.1,1.21)"));
+        assert!(mods[0].dependencies.is_empty());
+    }
+
+    #[test]
+    fn test_parse_fabric_and_quilt() {
+        let fabric = r#"{
+            "schemaVersion": 1,
+            "id": "sodium",
+            "name": "Sodium",
+            "version": "0.5.8",
+            "environment": "client",
+            "icon": {"16": "assets/sodium/icon_16.png", "128": "assets/sodium/icon.png"},
+            "license": "LGPL-3.0-only",
+            "authors": ["JellySquid", {"name": "IMS"}],
+            "contact": {"homepage": "https://example.org/sodium", "issues": "https://example.org/sodium/issues"},
+            "depends": {"fabricloader": ">=0.12.0", "minecraft": ["1.20.1", "1.20.2"]},
+            "recommends": {"indium": "*"}
+        }"#;
+        let meta = parse_metadata("fabric.mod.json", fabric, None).unwrap().remove(0);
+        assert_eq!(meta.loader, "fabric");
+        assert_eq!(meta.display_name, Some("Sodium".to_string()));
+        assert_eq!(meta.environment, Some("client".to_string()));
+        assert_eq!(meta.icon, Some("assets/sodium/icon.png".to_string()));
+        assert_eq!(meta.license, Some("LGPL-3.0-only".to_string()));
+        assert_eq!(meta.authors, Some("JellySquid, IMS".to_string()));
+        assert_eq!(meta.issue_tracker, Some("https://example.org/sodium/issues".to_string()));
+        assert_eq!(meta.dependencies.len(), 3);
+        assert!(meta.dependencies.contains(&ModDependency {
+            mod_id: "minecraft".to_string(),
+            version_range: Some("1.20.1 || 1.20.2".to_string()),
+            required: true,
+        }));
+        assert!(!meta.dependencies.iter().find(|d| d.mod_id == "indium").unwrap().required);
+
+        let quilt = r#"{
+            "schema_version": 1,
+            "quilt_loader": {
+                "id": "qsl",
+                "version": "6.1.2",
+                "metadata": {"name": "Quilt Standard Libraries"},
+                "depends": ["quilt_loader", {"id": "emi", "versions": ">=1.0", "optional": true}]
+            }
+        }"#;
+        let meta = parse_metadata("quilt.mod.json", quilt, None).unwrap().remove(0);
+        assert_eq!(meta.loader, "quilt");
+        assert_eq!(meta.mod_id, "qsl");
+        assert_eq!(meta.dependencies[1], ModDependency {
+            mod_id: "emi".to_string(),
+            version_range: Some(">=1.0".to_string()),
+            required: false,
+        });
+    }
+}
@@ -0,0 +1,233 @@
+use crate::complexity::ComplexityRecipe;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Settings key holding the pack's gate items as a JSON array, in
+/// progression order.
+pub const GATES_SETTING: &str = "progression_gates";
+
+/// What becomes obtainable once a gate item is.
+#[derive(Serialize)]
+pub struct ProgressionTier {
+    /// `None` for what is obtainable before any gate
+    pub gate: Option<String>,
+    pub items: Vec<String>,
+    /// Row ids of the recipes that become craftable
+    pub recipes: Vec<i64>,
+}
+
+/// What pretending some recipes were removed would break.
+#[derive(Serialize)]
+pub struct RemovalImpact {
+    /// Items obtainable now that would no longer be
+    pub unobtainable: Vec<String>,
+    /// Row ids of recipes, other than the removed ones, that could no longer
+    /// be crafted
+    pub uncraftable_recipes: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ProgressionReport {
+    pub tiers: Vec<ProgressionTier>,
+    /// Items made only by recipes that stay locked after every gate, e.g.
+    /// ones needing something nothing produces
+    pub never_unlocked: Vec<String>,
+}
+
+/// Sorts the pack into tiers by the gates (item ids) a player must have.
+/// Gates are opened in the order given; items no recipe makes count as
+/// available from the start unless they are gates, and a tag is available
+/// once any of its items is.
+pub fn progression(recipes: &[ComplexityRecipe], tag_entries: &[(String, String)], gates: &[String]) -> ProgressionReport {
+    let outputs: HashSet<&str> = recipes.iter().map(|r| r.output.as_str()).collect();
+    let mut unlock = Unlock::new(recipes, tag_entries, &HashSet::new());
+    let mut tier = ProgressionTier { gate: None, items: Vec::new(), recipes: Vec::new() };
+    let start: Vec<&String> = raw_items(recipes, tag_entries).filter(|i| !gates.contains(i)).collect();
+    unlock.start(start, &mut tier);
+
+    let mut tiers = vec![tier];
+    for gate in gates {
+        let mut tier = ProgressionTier { gate: Some(gate.clone()), items: Vec::new(), recipes: Vec::new() };
+        unlock.make_available(gate, &mut tier);
+        unlock.settle(&mut tier);
+        tiers.push(tier);
+    }
+    for tier in &mut tiers {
+        tier.items.sort();
+        tier.recipes.sort();
+    }
+
+    let mut never_unlocked: Vec<String> = outputs
+        .into_iter()
+        .filter(|item| !unlock.available.contains(*item))
+        .map(str::to_string)
+        .collect();
+    never_unlocked.sort();
+    ProgressionReport { tiers, never_unlocked }
+}
+
+/// Simulates removing the recipes with the given row ids. Items no recipe
+/// makes, and those from `other_sources` such as loot tables and trades,
+/// stay obtainable.
+pub fn removal_impact(
+    recipes: &[ComplexityRecipe],
+    tag_entries: &[(String, String)],
+    other_sources: &[String],
+    removed: &HashSet<i64>,
+) -> RemovalImpact {
+    let start: Vec<&String> = raw_items(recipes, tag_entries).chain(other_sources).collect();
+    let mut before = ProgressionTier { gate: None, items: Vec::new(), recipes: Vec::new() };
+    Unlock::new(recipes, tag_entries, &HashSet::new()).start(start.clone(), &mut before);
+    let mut after = ProgressionTier { gate: None, items: Vec::new(), recipes: Vec::new() };
+    Unlock::new(recipes, tag_entries, removed).start(start, &mut after);
+
+    let still_items: HashSet<&String> = after.items.iter().collect();
+    let still_recipes: HashSet<i64> = after.recipes.into_iter().collect();
+    let mut unobtainable: Vec<String> = before.items.into_iter().filter(|i| !still_items.contains(i)).collect();
+    unobtainable.sort();
+    let mut uncraftable_recipes: Vec<i64> = before
+        .recipes
+        .into_iter()
+        .filter(|id| !still_recipes.contains(id) && !removed.contains(id))
+        .collect();
+    uncraftable_recipes.sort();
+    RemovalImpact { unobtainable, uncraftable_recipes }
+}
+
+/// Items that recipes use or tags list but no recipe makes.
+fn raw_items<'a>(recipes: &'a [ComplexityRecipe], tag_entries: &'a [(String, String)]) -> impl Iterator<Item = &'a String> {
+    let outputs: HashSet<&str> = recipes.iter().map(|r| r.output.as_str()).collect();
+    recipes
+        .iter()
+        .flat_map(|r| &r.ingredients)
+        .chain(tag_entries.iter().map(|(_, entry)| entry))
+        .filter(move |i| !i.starts_with('#') && !outputs.contains(i.as_str()))
+}
+
+struct Unlock<'a> {
+    recipes: &'a [ComplexityRecipe],
+    consumers: HashMap<&'a str, Vec<usize>>,
+    tags_of: HashMap<&'a str, Vec<String>>,
+    /// Ingredients each recipe still lacks
+    missing: Vec<usize>,
+    available: HashSet<String>,
+    queue: Vec<String>,
+}
+
+impl<'a> Unlock<'a> {
+    /// Removed recipes never count as craftable.
+    fn new(recipes: &'a [ComplexityRecipe], tag_entries: &'a [(String, String)], removed: &HashSet<i64>) -> Self {
+        let mut consumers: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut missing: Vec<usize> = Vec::with_capacity(recipes.len());
+        for (index, recipe) in recipes.iter().enumerate() {
+            if removed.contains(&recipe.id) {
+                missing.push(usize::MAX);
+                continue;
+            }
+            let ingredients: HashSet<&str> = recipe.ingredients.iter().map(String::as_str).collect();
+            missing.push(ingredients.len());
+            for ingredient in ingredients {
+                consumers.entry(ingredient).or_default().push(index);
+            }
+        }
+        let mut tags_of: HashMap<&str, Vec<String>> = HashMap::new();
+        for (tag, entry) in tag_entries {
+            tags_of.entry(entry).or_default().push(format!("#{}", tag));
+        }
+        Unlock { recipes, consumers, tags_of, missing, available: HashSet::new(), queue: Vec::new() }
+    }
+
+    /// Makes the starting items available, along with everything they and
+    /// recipes without ingredients lead to.
+    fn start(&mut self, items: Vec<&String>, tier: &mut ProgressionTier) {
+        for item in items {
+            self.make_available(item, tier);
+        }
+        let recipes = self.recipes;
+        for (index, recipe) in recipes.iter().enumerate() {
+            if self.missing[index] == 0 {
+                tier.recipes.push(recipe.id);
+                self.make_available(&recipe.output, tier);
+            }
+        }
+        self.settle(tier);
+    }
+
+    fn make_available(&mut self, node: &str, tier: &mut ProgressionTier) {
+        if self.available.insert(node.to_string()) {
+            if !node.starts_with('#') {
+                tier.items.push(node.to_string());
+            }
+            self.queue.push(node.to_string());
+        }
+    }
+
+    /// Follows newly available items and tags to the recipes and tags they
+    /// complete.
+    fn settle(&mut self, tier: &mut ProgressionTier) {
+        while let Some(node) = self.queue.pop() {
+            for tag in self.tags_of.get(node.as_str()).cloned().unwrap_or_default() {
+                self.make_available(&tag, tier);
+            }
+            for index in self.consumers.get(node.as_str()).cloned().unwrap_or_default() {
+                self.missing[index] -= 1;
+                if self.missing[index] == 0 {
+                    let recipe = &self.recipes[index];
+                    tier.recipes.push(recipe.id);
+                    self.make_available(&recipe.output, tier);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(id: i64, output: &str, ingredients: &[&str]) -> ComplexityRecipe {
+        ComplexityRecipe {
+            id,
+            output: output.to_string(),
+            recipe_type: "minecraft:crafting_shaped".to_string(),
+            ingredients: ingredients.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_progression() {
+        let recipes = vec![
+            recipe(1, "a:plate", &["a:ingot"]),
+            recipe(2, "a:powder", &["a:blaze_rod"]),
+            recipe(3, "a:engine", &["#c:powders", "a:plate"]),
+            recipe(4, "a:star_tool", &["a:nether_star", "a:engine"]),
+            recipe(5, "a:loop", &["a:other"]),
+            recipe(6, "a:other", &["a:loop"]),
+        ];
+        let tags = vec![("c:powders".to_string(), "a:powder".to_string())];
+        let gates = vec!["a:blaze_rod".to_string(), "a:nether_star".to_string()];
+        let report = progression(&recipes, &tags, &gates);
+
+        assert_eq!(report.tiers.len(), 3);
+        assert_eq!(report.tiers[0].items, vec!["a:ingot", "a:plate"]);
+        assert_eq!(report.tiers[1].items, vec!["a:blaze_rod", "a:engine", "a:powder"]);
+        assert_eq!(report.tiers[1].recipes, vec![2, 3]);
+        assert_eq!(report.tiers[2].items, vec!["a:nether_star", "a:star_tool"]);
+        assert_eq!(report.never_unlocked, vec!["a:loop", "a:other"]);
+    }
+
+    #[test]
+    fn test_removal_impact() {
+        let recipes = vec![
+            recipe(1, "a:plate", &["a:ingot"]),
+            recipe(2, "a:gear", &["a:plate"]),
+            recipe(3, "a:gear", &["a:ingot", "a:ingot"]),
+            recipe(4, "a:machine", &["a:plate", "a:gear"]),
+            recipe(5, "a:drop", &["a:ingot"]),
+        ];
+        let removed = HashSet::from([1, 5]);
+        let impact = removal_impact(&recipes, &[], &["a:drop".to_string()], &removed);
+        assert_eq!(impact.unobtainable, vec!["a:machine", "a:plate"]);
+        assert_eq!(impact.uncraftable_recipes, vec![2, 4]);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::database::Recipe;
+use crate::recipe_parser;
+use crate::session_diff::{self, JsonChange};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How two recipes differ, field by field.
+#[derive(Serialize)]
+pub struct RecipeComparison {
+    pub a: i64,
+    pub b: i64,
+    /// Nothing below differs
+    pub same: bool,
+    pub fields: Vec<FieldDiff>,
+    /// Items and tags whose counts differ; 0 where a recipe lacks one
+    pub ingredients: Vec<CountDiff>,
+    /// Fluids, outputs prefixed with `->`, whose amounts differ
+    pub fluids: Vec<CountDiff>,
+    /// Everything that differs in the raw JSON, for what the fields miss
+    pub json: Vec<JsonChange>,
+}
+
+#[derive(Serialize)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CountDiff {
+    pub item: String,
+    pub a: i64,
+    pub b: i64,
+}
+
+pub fn compare(a: &Recipe, b: &Recipe) -> RecipeComparison {
+    let parsed_a = recipe_parser::parse_recipe(&a.raw_json).ok();
+    let parsed_b = recipe_parser::parse_recipe(&b.raw_json).ok();
+    let pattern = |parsed: &Option<recipe_parser::ParsedRecipe>| {
+        parsed.as_ref().filter(|p| !p.pattern.is_empty()).map(|p| p.pattern.join("/"))
+    };
+
+    let candidates = [
+        ("type", Some(a.canonical_type.clone()), Some(b.canonical_type.clone())),
+        ("result", a.result_item.clone(), b.result_item.clone()),
+        ("result_count", a.result_count.map(|c| c.to_string()), b.result_count.map(|c| c.to_string())),
+        ("pattern", pattern(&parsed_a), pattern(&parsed_b)),
+        ("machine", a.machine.clone(), b.machine.clone()),
+    ];
+    let fields: Vec<FieldDiff> = candidates
+        .into_iter()
+        .filter(|(_, x, y)| x != y)
+        .map(|(field, a, b)| FieldDiff { field, a, b })
+        .collect();
+
+    let ingredients = count_diffs(
+        a.ingredients.iter().map(|i| (i.item.clone(), i.count as i64)),
+        b.ingredients.iter().map(|i| (i.item.clone(), i.count as i64)),
+    );
+    let fluids_of = |parsed: &Option<recipe_parser::ParsedRecipe>| -> Vec<(String, i64)> {
+        parsed
+            .iter()
+            .flat_map(|p| &p.fluids)
+            .map(|f| (format!("{}{}", if f.is_output { "->" } else { "" }, f.fluid), f.amount.unwrap_or(0) as i64))
+            .collect()
+    };
+    let fluids = count_diffs(fluids_of(&parsed_a).into_iter(), fluids_of(&parsed_b).into_iter());
+    let json = session_diff::json_changes(&a.raw_json, &b.raw_json);
+
+    RecipeComparison {
+        a: a.id,
+        b: b.id,
+        same: fields.is_empty() && ingredients.is_empty() && fluids.is_empty(),
+        fields,
+        ingredients,
+        fluids,
+        json,
+    }
+}
+
+/// An override set against the vanilla recipe it replaces.
+#[derive(Serialize)]
+pub struct VanillaComparison {
+    pub vanilla: Recipe,
+    pub modded: Recipe,
+    pub diff: RecipeComparison,
+    /// Ingredients per item made by the override over the vanilla recipe's;
+    /// below 1 the override is cheaper
+    pub cost_ratio: Option<f64>,
+}
+
+pub fn compare_with_vanilla(vanilla: Recipe, modded: Recipe) -> VanillaComparison {
+    let per_item = |recipe: &Recipe| {
+        let total: i64 = recipe.ingredients.iter().map(|i| i.count.max(1) as i64).sum();
+        total as f64 / recipe.result_count.unwrap_or(1).max(1) as f64
+    };
+    let cost_ratio = Some(per_item(&vanilla)).filter(|cost| *cost > 0.0).map(|cost| per_item(&modded) / cost);
+    VanillaComparison { diff: compare(&vanilla, &modded), vanilla, modded, cost_ratio }
+}
+
+/// How alike two recipes are, from 0 to 1, and the ingredient part of that
+/// score: shared ingredient amounts over total amounts (weighted Jaccard).
+/// The score averages it with whether both make the same item.
+pub fn similarity(a: &Recipe, b: &Recipe) -> (f64, f64) {
+    let mut totals: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+    for i in &a.ingredients {
+        totals.entry(&i.item).or_default().0 += i.count.max(1) as i64;
+    }
+    for i in &b.ingredients {
+        totals.entry(&i.item).or_default().1 += i.count.max(1) as i64;
+    }
+    let shared: i64 = totals.values().map(|(x, y)| x.min(y)).sum();
+    let total: i64 = totals.values().map(|(x, y)| x.max(y)).sum();
+    let ingredients = if total == 0 { 0.0 } else { shared as f64 / total as f64 };
+    let same_output = a.result_item.is_some() && a.result_item == b.result_item;
+    ((ingredients + if same_output { 1.0 } else { 0.0 }) / 2.0, ingredients)
+}
+
+/// Totals per item on each side, keeping those that differ.
+fn count_diffs(a: impl Iterator<Item = (String, i64)>, b: impl Iterator<Item = (String, i64)>) -> Vec<CountDiff> {
+    let mut totals: BTreeMap<String, (i64, i64)> = BTreeMap::new();
+    for (item, count) in a {
+        totals.entry(item).or_default().0 += count;
+    }
+    for (item, count) in b {
+        totals.entry(item).or_default().1 += count;
+    }
+    totals
+        .into_iter()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(item, (a, b))| CountDiff { item, a, b })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe_parser::IngredientCount;
+    use crate::test_support::recipe;
+
+    #[test]
+    fn test_count_diffs() {
+        let a = [("a:x".to_string(), 2), ("a:y".to_string(), 1), ("a:x".to_string(), 1)];
+        let b = [("a:x".to_string(), 3), ("a:z".to_string(), 1)];
+        let diffs = count_diffs(a.into_iter(), b.into_iter());
+        let items: Vec<(&str, i64, i64)> = diffs.iter().map(|d| (d.item.as_str(), d.a, d.b)).collect();
+        assert_eq!(items, vec![("a:y", 1, 0), ("a:z", 0, 1)]);
+    }
+
+    fn with_ingredients(result: &str, ingredients: &[(&str, i32)]) -> Recipe {
+        Recipe {
+            ingredients: ingredients
+                .iter()
+                .map(|(item, count)| IngredientCount { item: item.to_string(), count: *count })
+                .collect(),
+            ..recipe(result, Some(result))
+        }
+    }
+
+    #[test]
+    fn test_similarity() {
+        let a = with_ingredients("a:gear", &[("a:ingot", 4), ("a:stick", 1)]);
+        let b = with_ingredients("a:gear", &[("a:ingot", 2), ("a:stick", 1), ("a:nugget", 1)]);
+        // Shared 2 + 1 of 4 + 1 + 1 ingredients, and the same output
+        assert_eq!(similarity(&a, &b), ((0.5 + 1.0) / 2.0, 0.5));
+        let c = with_ingredients("b:plate", &[("a:ingot", 4), ("a:stick", 1)]);
+        assert_eq!(similarity(&a, &c), (0.5, 1.0));
+        assert_eq!(similarity(&with_ingredients("a:x", &[]), &with_ingredients("b:y", &[])), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_compare_with_vanilla() {
+        let vanilla = with_ingredients("minecraft:torch", &[("minecraft:coal", 1), ("minecraft:stick", 1)]);
+        let modded = Recipe {
+            result_count: Some(4),
+            ..with_ingredients("minecraft:torch", &[("minecraft:coal", 1), ("minecraft:stick", 1)])
+        };
+        let comparison = compare_with_vanilla(vanilla, modded);
+        // 2 ingredients for 4 torches against 2 for 1
+        assert_eq!(comparison.cost_ratio, Some(0.25));
+        assert!(comparison.diff.ingredients.is_empty());
+        assert!(comparison.diff.fields.iter().any(|f| f.field == "result_count"));
+
+        let free = compare_with_vanilla(with_ingredients("a:x", &[]), with_ingredients("a:x", &[("a:y", 1)]));
+        assert_eq!(free.cost_ratio, None);
+    }
+}
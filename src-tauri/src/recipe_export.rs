@@ -0,0 +1,97 @@
+use crate::database::Recipe;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// One recipe as a spreadsheet row.
+#[derive(Serialize)]
+struct FlatRecipe<'a> {
+    recipe_id: &'a str,
+    mod_name: &'a str,
+    recipe_type: &'a str,
+    result_item: &'a str,
+    result_count: i32,
+    ingredients: String,
+    path: &'a str,
+}
+
+const COLUMNS: [&str; 7] = ["recipe_id", "mod_name", "recipe_type", "result_item", "result_count", "ingredients", "path"];
+
+/// Renders recipes as CSV, or as a JSON array of the same rows.
+pub fn render(recipes: &[Recipe], format: ExportFormat) -> Result<String, String> {
+    let rows: Vec<FlatRecipe> = recipes.iter().map(flatten).collect();
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&rows).map_err(|e| e.to_string()),
+        ExportFormat::Csv => {
+            let mut out = COLUMNS.join(",");
+            out.push('\n');
+            for row in &rows {
+                let fields = [
+                    row.recipe_id.to_string(),
+                    row.mod_name.to_string(),
+                    row.recipe_type.to_string(),
+                    row.result_item.to_string(),
+                    row.result_count.to_string(),
+                    row.ingredients.clone(),
+                    row.path.to_string(),
+                ];
+                out.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+fn flatten(recipe: &Recipe) -> FlatRecipe<'_> {
+    FlatRecipe {
+        recipe_id: &recipe.recipe_id,
+        mod_name: &recipe.mod_name,
+        recipe_type: &recipe.canonical_type,
+        result_item: recipe.result_item.as_deref().unwrap_or(""),
+        result_count: result_count(recipe),
+        ingredients: ingredient_summary(recipe),
+        path: &recipe.path,
+    }
+}
+
+/// How many items the recipe makes: its count, or 1 when only the result
+/// item is given and 0 without one.
+pub fn result_count(recipe: &Recipe) -> i32 {
+    recipe.result_count.unwrap_or(if recipe.result_item.is_some() { 1 } else { 0 })
+}
+
+/// Ingredients in one cell, as `2x minecraft:iron_ingot; 1x #c:plates`.
+pub fn ingredient_summary(recipe: &Recipe) -> String {
+    recipe
+        .ingredients
+        .iter()
+        .map(|i| format!("{}x {}", i.count, i.item))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Quotes a field holding a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("a:b"), "a:b");
+        assert_eq!(csv_field("My, \"Mod\""), "\"My, \"\"Mod\"\"\"");
+    }
+}
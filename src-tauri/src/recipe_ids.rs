@@ -0,0 +1,63 @@
+/// Settings key holding the pack's prefix for new recipe ids.
+pub const PREFIX_SETTING: &str = "recipe_id_prefix";
+
+/// Prefix used for suggested ids until the user configures their own.
+pub const DEFAULT_PREFIX: &str = "custom:";
+
+/// Suggests an id for a new recipe: the prefix, then the result item's path,
+/// then `_from_<type>` for anything other than crafting table recipes
+/// (mirroring vanilla's `iron_ingot_from_smelting`). A numeric suffix is added
+/// until `exists` reports the id as free.
+pub fn suggest_recipe_id(
+    prefix: &str,
+    result_item: &str,
+    recipe_type: &str,
+    exists: impl Fn(&str) -> bool,
+) -> String {
+    // A bare namespace like "mypack" is treated as "mypack:"
+    let prefix = if prefix.contains(':') {
+        prefix.to_string()
+    } else {
+        format!("{}:", prefix)
+    };
+
+    let item_path = result_item.rsplit(':').next().unwrap_or(result_item);
+    let type_path = recipe_type.rsplit(':').next().unwrap_or(recipe_type);
+
+    let mut base = format!("{}{}", prefix, item_path);
+    if !type_path.starts_with("crafting") {
+        base = format!("{}_from_{}", base, type_path);
+    }
+
+    if !exists(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}_{}", base, n))
+        .find(|id| !exists(id))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_recipe_id() {
+        let taken = ["mypack:iron_ingot_from_smelting", "mypack:iron_ingot_from_smelting_2"];
+        let exists = |id: &str| taken.contains(&id);
+
+        assert_eq!(
+            suggest_recipe_id("mypack", "minecraft:iron_block", "minecraft:crafting_shaped", exists),
+            "mypack:iron_block"
+        );
+        assert_eq!(
+            suggest_recipe_id("mypack:", "minecraft:iron_ingot", "minecraft:smelting", exists),
+            "mypack:iron_ingot_from_smelting_3"
+        );
+        assert_eq!(
+            suggest_recipe_id("mypack:create/", "create:zinc_ingot", "create:mixing", exists),
+            "mypack:create/zinc_ingot_from_mixing"
+        );
+    }
+}
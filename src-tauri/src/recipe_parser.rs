@@ -1,10 +1,60 @@
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 pub struct ParsedRecipe {
     pub recipe_type: String,
     pub result_item: Option<String>,
     pub result_count: Option<i32>,
-    pub ingredients: Vec<String>,
+    pub ingredients: Vec<IngredientCount>,
+    pub fluids: Vec<FluidAmount>,
+    /// Machine id for data-driven machine recipes
+    pub machine: Option<String>,
+    /// Rows of a shaped pattern, top to bottom; empty if there is none
+    pub pattern: Vec<String>,
+}
+
+/// Keys that identify a fluid or chemical stack rather than an item.
+/// Tag-valued keys are stored with a `#` prefix like item tags.
+const FLUID_KEYS: &[(&str, bool)] = &[
+    ("fluid", false),
+    ("fluidTag", true),
+    ("fluid_tag", true),
+    ("gas", false),
+    ("chemical", false),
+    ("slurry", false),
+    ("pigment", false),
+    ("infuse_type", false),
+];
+
+/// A fluid or chemical consumed or produced by a recipe.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct FluidAmount {
+    pub fluid: String,
+    pub amount: Option<i32>,
+    pub is_output: bool,
+}
+
+/// An ingredient item (or `#tag`) and how many of it one craft consumes.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct IngredientCount {
+    pub item: String,
+    pub count: i32,
+}
+
+/// Type stored when a recipe declares none.
+const UNKNOWN_TYPE: &str = "unknown";
+
+/// Canonical form of a recipe type: lowercase and namespaced, so
+/// `crafting_shaped` and `minecraft:crafting_shaped` are the same type.
+/// Unqualified ids belong to `minecraft`, as they do in game.
+pub fn canonical_recipe_type(recipe_type: &str) -> String {
+    let recipe_type = recipe_type.trim().to_lowercase();
+    if recipe_type == UNKNOWN_TYPE || recipe_type.contains(':') {
+        recipe_type
+    } else {
+        format!("minecraft:{}", recipe_type)
+    }
 }
 
 pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
@@ -13,10 +63,19 @@ pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
 
     let recipe_type = value.get("type")
         .and_then(|t| t.as_str())
-        .unwrap_or("unknown")
+        .unwrap_or(UNKNOWN_TYPE)
         .to_string();
 
-    let mut ingredients = Vec::new();
+    // Data-driven machine recipes (Custom Machinery, Modular Machinery Reborn)
+    // describe every input and output as a requirement of a machine
+    let machine = value.get("machine").and_then(|m| m.as_str());
+    let requirements = value.get("requirements").and_then(|r| r.as_array());
+    if let (Some(machine), Some(requirements)) = (machine, requirements) {
+        return Ok(parse_machine_recipe(recipe_type, machine, requirements));
+    }
+
+    // Item -> number of slots it fills, so repeated ingredients stack
+    let mut ingredients = BTreeMap::new();
     let mut result_item = None;
     let mut result_count = None;
 
@@ -28,37 +87,37 @@ pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
     }
 
     // Extract ingredients based on recipe type
-    match recipe_type.as_str() {
+    match canonical_recipe_type(&recipe_type).as_str() {
         // Shaped crafting: has pattern and key
-        "minecraft:crafting_shaped" | "crafting_shaped" => {
+        "minecraft:crafting_shaped" => {
             if let Some(key) = value.get("key").and_then(|k| k.as_object()) {
-                for (_symbol, ingredient) in key {
-                    extract_ingredients_from_value(ingredient, &mut ingredients);
+                for (symbol, ingredient) in key {
+                    let slots = count_pattern_slots(&value, symbol);
+                    add_slot(ingredient, slots, &mut ingredients);
                 }
             }
         }
 
         // Shapeless crafting: has ingredients array
-        "minecraft:crafting_shapeless" | "crafting_shapeless" => {
+        "minecraft:crafting_shapeless" => {
             if let Some(ing_array) = value.get("ingredients").and_then(|i| i.as_array()) {
                 for ing in ing_array {
-                    extract_ingredients_from_value(ing, &mut ingredients);
+                    add_slot(ing, 1, &mut ingredients);
                 }
             }
         }
 
         // Smelting/cooking recipes: single ingredient
-        "minecraft:smelting" | "minecraft:blasting" | "minecraft:smoking" | "minecraft:campfire_cooking"
-        | "smelting" | "blasting" | "smoking" | "campfire_cooking" => {
+        "minecraft:smelting" | "minecraft:blasting" | "minecraft:smoking" | "minecraft:campfire_cooking" => {
             if let Some(ingredient) = value.get("ingredient") {
-                extract_ingredients_from_value(ingredient, &mut ingredients);
+                add_slot(ingredient, 1, &mut ingredients);
             }
         }
 
         // Stonecutting: single ingredient, result is just item string
-        "minecraft:stonecutting" | "stonecutting" => {
+        "minecraft:stonecutting" => {
             if let Some(ingredient) = value.get("ingredient") {
-                extract_ingredients_from_value(ingredient, &mut ingredients);
+                add_slot(ingredient, 1, &mut ingredients);
             }
             // Stonecutting result is sometimes just the item string
             if result_item.is_none() {
@@ -69,25 +128,25 @@ pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
         }
 
         // Smithing recipes (1.20+): template + base + addition
-        "minecraft:smithing_transform" | "minecraft:smithing_trim" | "smithing_transform" | "smithing_trim" => {
+        "minecraft:smithing_transform" | "minecraft:smithing_trim" => {
             if let Some(template) = value.get("template") {
-                extract_ingredients_from_value(template, &mut ingredients);
+                add_slot(template, 1, &mut ingredients);
             }
             if let Some(base) = value.get("base") {
-                extract_ingredients_from_value(base, &mut ingredients);
+                add_slot(base, 1, &mut ingredients);
             }
             if let Some(addition) = value.get("addition") {
-                extract_ingredients_from_value(addition, &mut ingredients);
+                add_slot(addition, 1, &mut ingredients);
             }
         }
 
         // Legacy smithing (pre-1.20)
-        "minecraft:smithing" | "smithing" => {
+        "minecraft:smithing" => {
             if let Some(base) = value.get("base") {
-                extract_ingredients_from_value(base, &mut ingredients);
+                add_slot(base, 1, &mut ingredients);
             }
             if let Some(addition) = value.get("addition") {
-                extract_ingredients_from_value(addition, &mut ingredients);
+                add_slot(addition, 1, &mut ingredients);
             }
         }
 
@@ -102,17 +161,18 @@ pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
             if let Some(ingredients_val) = value.get("ingredients").or(value.get("ingredient")) {
                 if let Some(arr) = ingredients_val.as_array() {
                     for ing in arr {
-                        extract_ingredients_from_value(ing, &mut ingredients);
+                        add_slot(ing, 1, &mut ingredients);
                     }
                 } else {
-                    extract_ingredients_from_value(ingredients_val, &mut ingredients);
+                    add_slot(ingredients_val, 1, &mut ingredients);
                 }
             }
 
             // Try key-based ingredients
             if let Some(key) = value.get("key").and_then(|k| k.as_object()) {
-                for (_symbol, ingredient) in key {
-                    extract_ingredients_from_value(ingredient, &mut ingredients);
+                for (symbol, ingredient) in key {
+                    let slots = count_pattern_slots(&value, symbol);
+                    add_slot(ingredient, slots, &mut ingredients);
                 }
             }
 
@@ -120,27 +180,136 @@ pub fn parse_recipe(json_str: &str) -> Result<ParsedRecipe, String> {
             if let Some(input) = value.get("input").or(value.get("inputs")) {
                 if let Some(arr) = input.as_array() {
                     for ing in arr {
-                        extract_ingredients_from_value(ing, &mut ingredients);
+                        add_slot(ing, 1, &mut ingredients);
                     }
                 } else {
-                    extract_ingredients_from_value(input, &mut ingredients);
+                    add_slot(input, 1, &mut ingredients);
                 }
             }
         }
     }
 
-    // Deduplicate ingredients
-    ingredients.sort();
-    ingredients.dedup();
-
     Ok(ParsedRecipe {
         recipe_type,
         result_item,
         result_count,
-        ingredients,
+        ingredients: into_counts(ingredients),
+        fluids: extract_fluids(&value),
+        machine: None,
+        pattern: extract_pattern(&value),
     })
 }
 
+fn parse_machine_recipe(recipe_type: String, machine: &str, requirements: &[Value]) -> ParsedRecipe {
+    let mut ingredients = BTreeMap::new();
+    let mut fluids = Vec::new();
+    let mut result_item = None;
+    let mut result_count = None;
+
+    for requirement in requirements {
+        // "custommachinery:item" -> "item"
+        let kind = requirement.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        let kind = kind.rsplit(':').next().unwrap_or(kind);
+        let is_output = requirement.get("mode").and_then(|m| m.as_str()) == Some("output");
+        let amount = requirement.get("amount")
+            .or(requirement.get("count"))
+            .and_then(|a| a.as_i64())
+            .map(|a| a as i32);
+
+        match kind {
+            "item" => {
+                let mut items = Vec::new();
+                if let Some(item) = requirement.get("ingredient").or(requirement.get("item")) {
+                    extract_ingredients_from_value(item, &mut items);
+                }
+                if let Some(tag) = requirement.get("tag").and_then(|t| t.as_str()) {
+                    items.push(format!("#{}", tag));
+                }
+                for item in items {
+                    if !is_output {
+                        *ingredients.entry(item).or_insert(0) += amount.unwrap_or(1);
+                    } else if result_item.is_none() {
+                        result_item = Some(item);
+                        result_count = amount.or(Some(1));
+                    }
+                }
+            }
+            "fluid" => {
+                if let Some(fluid) = requirement.get("fluid").and_then(|f| f.as_str()) {
+                    fluids.push(FluidAmount {
+                        fluid: fluid.to_string(),
+                        amount,
+                        is_output,
+                    });
+                }
+            }
+            // Energy, time, dimension and other non-material requirements
+            _ => {}
+        }
+    }
+
+    ParsedRecipe {
+        recipe_type,
+        result_item,
+        result_count,
+        ingredients: into_counts(ingredients),
+        fluids,
+        machine: Some(machine.to_string()),
+        pattern: Vec::new(),
+    }
+}
+
+fn into_counts(ingredients: BTreeMap<String, i32>) -> Vec<IngredientCount> {
+    ingredients
+        .into_iter()
+        .map(|(item, count)| IngredientCount { item, count })
+        .collect()
+}
+
+/// Finds fluid/chemical stacks anywhere in the recipe. Anything under a
+/// top-level key mentioning "result" or "output" counts as produced.
+fn extract_fluids(recipe: &Value) -> Vec<FluidAmount> {
+    let mut fluids = Vec::new();
+    if let Some(obj) = recipe.as_object() {
+        for (key, value) in obj {
+            let key = key.to_lowercase();
+            let is_output = key.contains("result") || key.contains("output");
+            collect_fluids(value, is_output, &mut fluids);
+        }
+    }
+    fluids
+}
+
+fn collect_fluids(value: &Value, is_output: bool, fluids: &mut Vec<FluidAmount>) {
+    match value {
+        Value::Object(obj) => {
+            for (key, is_tag) in FLUID_KEYS {
+                if let Some(id) = obj.get(*key).and_then(|v| v.as_str()) {
+                    let amount = obj.get("amount")
+                        .or(obj.get("count"))
+                        .and_then(|a| a.as_i64())
+                        .map(|a| a as i32);
+                    fluids.push(FluidAmount {
+                        fluid: if *is_tag { format!("#{}", id) } else { id.to_string() },
+                        amount,
+                        is_output,
+                    });
+                    return;
+                }
+            }
+            for nested in obj.values() {
+                collect_fluids(nested, is_output, fluids);
+            }
+        }
+        Value::Array(arr) => {
+            for item in arr {
+                collect_fluids(item, is_output, fluids);
+            }
+        }
+        _ => {}
+    }
+}
+
 fn extract_item_and_count(value: &Value) -> (Option<String>, Option<i32>) {
     match value {
         // Simple string: "minecraft:iron_ingot"
@@ -165,6 +334,72 @@ fn extract_item_and_count(value: &Value) -> (Option<String>, Option<i32>) {
     }
 }
 
+/// Adds every item accepted by one ingredient slot, `slots` times over.
+/// Alternatives within a slot are counted once each, not summed.
+fn add_slot(value: &Value, slots: i32, ingredients: &mut BTreeMap<String, i32>) {
+    if slots == 0 {
+        return;
+    }
+    let mut items = Vec::new();
+    extract_ingredients_from_value(value, &mut items);
+    items.sort();
+    items.dedup();
+    for item in items {
+        *ingredients.entry(item).or_insert(0) += slots;
+    }
+}
+
+/// One string for everything an ingredient accepts, the same however its
+/// alternatives are ordered, e.g. `#forge:ingots/iron|minecraft:iron_ingot`.
+pub fn ingredient_key(value: &Value) -> String {
+    let mut items = Vec::new();
+    extract_ingredients_from_value(value, &mut items);
+    items.sort();
+    items.dedup();
+    items.join("|")
+}
+
+/// A recipe's shaped pattern, shrunk to the cells it uses.
+pub fn extract_pattern(recipe: &Value) -> Vec<String> {
+    let rows: Vec<String> = recipe
+        .get("pattern")
+        .and_then(|p| p.as_array())
+        .map(|rows| rows.iter().filter_map(|row| row.as_str()).map(str::to_string).collect())
+        .unwrap_or_default();
+    shrink_pattern(&rows)
+}
+
+/// Pads a pattern's rows to one width and drops blank rows and columns
+/// around the cells in use, as the game does, so `["A ", "  "]` and
+/// `[" A"]` are both `["A"]`. A pattern with no cells in use is empty.
+pub fn shrink_pattern(rows: &[String]) -> Vec<String> {
+    let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    let grid: Vec<Vec<char>> = rows
+        .iter()
+        .map(|row| row.chars().chain(std::iter::repeat(' ')).take(width).collect())
+        .collect();
+    let used_rows: Vec<usize> = (0..grid.len()).filter(|&r| grid[r].iter().any(|c| *c != ' ')).collect();
+    let used_cols: Vec<usize> = (0..width).filter(|&c| grid.iter().any(|row| row[c] != ' ')).collect();
+    let (Some(&top), Some(&bottom), Some(&left), Some(&right)) =
+        (used_rows.first(), used_rows.last(), used_cols.first(), used_cols.last())
+    else {
+        return Vec::new();
+    };
+    grid[top..=bottom].iter().map(|row| row[left..=right].iter().collect()).collect()
+}
+
+/// Number of grid cells using `symbol` in a shaped pattern (1 if there is no pattern).
+fn count_pattern_slots(recipe: &Value, symbol: &str) -> i32 {
+    let Some(pattern) = recipe.get("pattern").and_then(|p| p.as_array()) else {
+        return 1;
+    };
+    pattern
+        .iter()
+        .filter_map(|row| row.as_str())
+        .map(|row| row.matches(symbol).count() as i32)
+        .sum()
+}
+
 fn extract_ingredients_from_value(value: &Value, ingredients: &mut Vec<String>) {
     match value {
         // Simple string: "minecraft:iron_ingot"
@@ -197,6 +432,10 @@ fn extract_ingredients_from_value(value: &Value, ingredients: &mut Vec<String>)
 mod tests {
     use super::*;
 
+    fn count_of(parsed: &ParsedRecipe, item: &str) -> Option<i32> {
+        parsed.ingredients.iter().find(|i| i.item == item).map(|i| i.count)
+    }
+
     #[test]
     fn test_shaped_recipe() {
         let json = r####"{
@@ -213,8 +452,9 @@ mod tests {
         assert_eq!(parsed.recipe_type, "minecraft:crafting_shaped");
         assert_eq!(parsed.result_item, Some("minecraft:iron_pickaxe".to_string()));
         assert_eq!(parsed.result_count, Some(1));
-        assert!(parsed.ingredients.contains(&"minecraft:iron_ingot".to_string()));
-        assert!(parsed.ingredients.contains(&"minecraft:stick".to_string()));
+        assert_eq!(parsed.pattern, vec!["###", " | ", " | "]);
+        assert_eq!(count_of(&parsed, "minecraft:iron_ingot"), Some(3));
+        assert_eq!(count_of(&parsed, "minecraft:stick"), Some(2));
     }
 
     #[test]
@@ -246,7 +486,7 @@ mod tests {
 
         let parsed = parse_recipe(json).unwrap();
         assert_eq!(parsed.recipe_type, "minecraft:smelting");
-        assert!(parsed.ingredients.contains(&"minecraft:iron_ore".to_string()));
+        assert_eq!(count_of(&parsed, "minecraft:iron_ore"), Some(1));
     }
 
     #[test]
@@ -261,6 +501,89 @@ mod tests {
         }"####;
 
         let parsed = parse_recipe(json).unwrap();
-        assert!(parsed.ingredients.contains(&"#forge:ingots/iron".to_string()));
+        assert_eq!(count_of(&parsed, "#forge:ingots/iron"), Some(3));
+        assert!(parsed.fluids.is_empty());
+    }
+
+    #[test]
+    fn test_fluid_conversion() {
+        let json = r#"{
+            "type": "create:mixing",
+            "ingredients": [
+                {"item": "minecraft:sugar"},
+                {"fluidTag": "forge:milk", "amount": 250}
+            ],
+            "results": [{"fluid": "create:chocolate", "amount": 250}],
+            "heatRequirement": "heated"
+        }"#;
+
+        let parsed = parse_recipe(json).unwrap();
+        assert_eq!(parsed.fluids, vec![
+            FluidAmount { fluid: "#forge:milk".to_string(), amount: Some(250), is_output: false },
+            FluidAmount { fluid: "create:chocolate".to_string(), amount: Some(250), is_output: true },
+        ]);
+        assert_eq!(count_of(&parsed, "minecraft:sugar"), Some(1));
+    }
+
+    #[test]
+    fn test_machine_recipe() {
+        let json = r#"{
+            "type": "custommachinery:custom_machine",
+            "machine": "mypack:ore_washer",
+            "time": 100,
+            "requirements": [
+                {"type": "custommachinery:item", "mode": "input", "item": "minecraft:raw_iron", "amount": 2},
+                {"type": "custommachinery:fluid", "mode": "input", "fluid": "minecraft:water", "amount": 500},
+                {"type": "custommachinery:energy", "mode": "input", "amount": 20},
+                {"type": "custommachinery:item", "mode": "output", "item": "minecraft:iron_ingot", "amount": 3}
+            ]
+        }"#;
+
+        let parsed = parse_recipe(json).unwrap();
+        assert_eq!(parsed.machine, Some("mypack:ore_washer".to_string()));
+        assert_eq!(parsed.result_item, Some("minecraft:iron_ingot".to_string()));
+        assert_eq!(parsed.result_count, Some(3));
+        assert_eq!(parsed.ingredients, vec![IngredientCount { item: "minecraft:raw_iron".to_string(), count: 2 }]);
+        assert_eq!(parsed.fluids, vec![
+            FluidAmount { fluid: "minecraft:water".to_string(), amount: Some(500), is_output: false },
+        ]);
+    }
+
+    #[test]
+    fn test_shapeless_repeated_ingredients_stack() {
+        let json = r#"{
+            "type": "minecraft:crafting_shapeless",
+            "ingredients": [
+                {"item": "minecraft:iron_nugget"},
+                {"item": "minecraft:iron_nugget"},
+                {"item": "minecraft:iron_nugget"},
+                {"item": "minecraft:iron_nugget"},
+                [{"item": "minecraft:coal"}, {"item": "minecraft:charcoal"}]
+            ],
+            "result": {"item": "minecraft:iron_ingot"}
+        }"#;
+
+        let parsed = parse_recipe(json).unwrap();
+        assert_eq!(parsed.ingredients.len(), 3);
+        assert_eq!(count_of(&parsed, "minecraft:iron_nugget"), Some(4));
+        assert_eq!(count_of(&parsed, "minecraft:coal"), Some(1));
+        assert_eq!(count_of(&parsed, "minecraft:charcoal"), Some(1));
+    }
+
+    #[test]
+    fn test_shrink_pattern() {
+        let rows = |rows: &[&str]| rows.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+        assert_eq!(shrink_pattern(&rows(&["A ", "  "])), vec!["A"]);
+        assert_eq!(shrink_pattern(&rows(&[" A", " "])), vec!["A"]);
+        assert_eq!(shrink_pattern(&rows(&["   ", " AB", " C"])), vec!["AB", "C "]);
+        assert!(shrink_pattern(&rows(&["  ", ""])).is_empty());
+    }
+
+    #[test]
+    fn test_canonical_recipe_type() {
+        assert_eq!(canonical_recipe_type("crafting_shaped"), "minecraft:crafting_shaped");
+        assert_eq!(canonical_recipe_type("minecraft:crafting_shaped"), "minecraft:crafting_shaped");
+        assert_eq!(canonical_recipe_type("Create:Mixing"), "create:mixing");
+        assert_eq!(canonical_recipe_type("unknown"), "unknown");
     }
 }
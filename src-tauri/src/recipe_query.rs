@@ -0,0 +1,308 @@
+use crate::matching::escape_like;
+use crate::recipe_parser;
+use rusqlite::types::Value;
+
+/// A parsed recipe query such as
+/// `output:create:* AND type:create:pressing AND NOT mod:thermal`.
+///
+/// Terms are `field:value`, or a bare value matched against result items and
+/// recipe ids by substring. Field values match exactly, with `*` as a
+/// wildcard. Terms next to each other are ANDed; `OR`, `NOT` and
+/// parentheses work as usual, and quotes allow spaces in a value. A tag as
+/// the `output` value, e.g. `output:#c:plates`, matches the items in it.
+#[derive(Debug, PartialEq)]
+pub enum Query {
+    Term(Field, String),
+    Text(String),
+    Not(Box<Query>),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Field {
+    Output,
+    Input,
+    Fluid,
+    Type,
+    Mod,
+    Namespace,
+    Id,
+    Path,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Field> {
+        Some(match name {
+            "output" | "out" => Field::Output,
+            "input" | "in" | "ingredient" => Field::Input,
+            "fluid" => Field::Fluid,
+            "type" => Field::Type,
+            "mod" => Field::Mod,
+            "namespace" | "ns" => Field::Namespace,
+            "id" => Field::Id,
+            "path" => Field::Path,
+            _ => return None,
+        })
+    }
+}
+
+/// Deepest nesting of parentheses and `NOT`s a query may have.
+const MAX_DEPTH: usize = 32;
+
+/// Most terms a query may have, so the SQL stays within SQLite's limits.
+const MAX_TERMS: usize = 200;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Word(String),
+}
+
+pub fn parse(input: &str) -> Result<Query, String> {
+    let tokens = tokenize(input)?;
+    if tokens.iter().filter(|t| matches!(t, Token::Word(_))).count() > MAX_TERMS {
+        return Err(format!("Query has more than {} terms", MAX_TERMS));
+    }
+    let mut parser = Parser { tokens, pos: 0, depth: 0 };
+    let query = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        None => Ok(query),
+        Some(Token::Close) => Err("Unmatched )".to_string()),
+        Some(token) => Err(format!("Unexpected {:?}", token)),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+                while let Some(&c) = chars.peek() {
+                    if !quoted && (c.is_whitespace() || c == '(' || c == ')') {
+                        break;
+                    }
+                    chars.next();
+                    if c == '"' {
+                        quoted = !quoted;
+                    } else {
+                        word.push(c);
+                    }
+                }
+                if quoted {
+                    return Err("Unclosed quote".to_string());
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Parentheses and `NOT`s around the current token
+    depth: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Query, String> {
+        let mut query = self.and()?;
+        while self.tokens.get(self.pos) == Some(&Token::Or) {
+            self.pos += 1;
+            query = Query::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Query, String> {
+        let mut query = self.unary()?;
+        loop {
+            match self.tokens.get(self.pos) {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Open | Token::Not | Token::Word(_)) => {}
+                _ => return Ok(query),
+            }
+            query = Query::And(Box::new(query), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Query, String> {
+        let token = self.tokens.get(self.pos).ok_or("Query ends early")?;
+        self.pos += 1;
+        if let Token::Word(word) = token {
+            return Ok(term(word));
+        }
+        if !matches!(token, Token::Not | Token::Open) {
+            return Err(format!("Unexpected {:?}", token));
+        }
+        let is_not = *token == Token::Not;
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("Query nests deeper than {} levels", MAX_DEPTH));
+        }
+        let query = match is_not {
+            true => Query::Not(Box::new(self.unary()?)),
+            false => {
+                let query = self.or()?;
+                if self.tokens.get(self.pos) != Some(&Token::Close) {
+                    return Err("Missing )".to_string());
+                }
+                self.pos += 1;
+                query
+            }
+        };
+        self.depth -= 1;
+        Ok(query)
+    }
+}
+
+/// `type:create:pressing` is a term, but `minecraft:stick` is a bare value
+/// because `minecraft` is not a field.
+fn term(word: &str) -> Query {
+    match word.split_once(':') {
+        Some((name, value)) => match Field::parse(&name.to_lowercase()) {
+            Some(field) => Query::Term(field, value.to_string()),
+            None => Query::Text(word.to_string()),
+        },
+        None => Query::Text(word.to_string()),
+    }
+}
+
+impl Query {
+    /// SQL condition over `recipes r`, pushing values onto `params`.
+    pub fn to_sql(&self, params: &mut Vec<Value>) -> String {
+        match self {
+            Query::Text(text) => {
+                let escaped = format!("%{}%", escape_like(text));
+                params.push(Value::from(escaped));
+                let n = params.len();
+                format!("(r.result_item LIKE ?{n} ESCAPE '\\' OR r.recipe_id LIKE ?{n} ESCAPE '\\')")
+            }
+            Query::Term(field, value) => match field {
+                Field::Output => match value.strip_prefix('#') {
+                    Some(tag) => format!(
+                        "r.result_item IN (
+                             WITH RECURSIVE members(item) AS (
+                                 SELECT entry FROM item_tags WHERE {}
+                                 UNION
+                                 SELECT t.entry FROM members m JOIN item_tags t ON m.item = '#' || t.tag
+                             )
+                             SELECT item FROM members
+                         )",
+                        matches("tag", tag, params)
+                    ),
+                    None => matches("r.result_item", value, params),
+                },
+                Field::Input => format!(
+                    "r.id IN (SELECT recipe_id FROM recipe_ingredients WHERE {})",
+                    matches("item", value, params)
+                ),
+                Field::Fluid => format!(
+                    "r.id IN (SELECT recipe_id FROM recipe_fluids WHERE {})",
+                    matches("fluid", value, params)
+                ),
+                Field::Type => {
+                    let value = match value.contains('*') {
+                        true => value.to_lowercase(),
+                        false => recipe_parser::canonical_recipe_type(value),
+                    };
+                    matches("r.canonical_type", &value, params)
+                }
+                Field::Mod => {
+                    let by_id = matches("loader_mod_id", value, params);
+                    let by_name = matches("name", value, params);
+                    format!("r.mod_id IN (SELECT id FROM mods WHERE {} OR {})", by_id, by_name)
+                }
+                Field::Namespace => matches("r.namespace", value, params),
+                Field::Id => matches("r.recipe_id", value, params),
+                Field::Path => matches("r.path", value, params),
+            },
+            Query::Not(inner) => format!("NOT ({})", inner.to_sql(params)),
+            Query::And(a, b) => format!("({} AND {})", a.to_sql(params), b.to_sql(params)),
+            Query::Or(a, b) => format!("({} OR {})", a.to_sql(params), b.to_sql(params)),
+        }
+    }
+}
+
+/// Exact match, or a LIKE pattern when the value has `*` wildcards.
+fn matches(column: &str, value: &str, params: &mut Vec<Value>) -> String {
+    if value.contains('*') {
+        params.push(Value::from(escape_like(value).replace('*', "%")));
+        format!("{} LIKE ?{} ESCAPE '\\'", column, params.len())
+    } else {
+        params.push(Value::from(value.to_string()));
+        format!("{} = ?{}", column, params.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let query = parse("output:#forge:plates AND type:create:pressing AND NOT mod:thermal").unwrap();
+        let expected = Query::And(
+            Box::new(Query::And(
+                Box::new(Query::Term(Field::Output, "#forge:plates".into())),
+                Box::new(Query::Term(Field::Type, "create:pressing".into())),
+            )),
+            Box::new(Query::Not(Box::new(Query::Term(Field::Mod, "thermal".into())))),
+        );
+        assert_eq!(query, expected);
+
+        let query = parse(r#"minecraft:stick (in:"a b" OR ns:create)"#).unwrap();
+        assert!(matches!(query, Query::And(ref text, _) if **text == Query::Text("minecraft:stick".into())));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("(type:smelting").is_err());
+        assert!(parse("type:smelting)").is_err());
+        assert!(parse("NOT").is_err());
+        assert!(parse("in:\"open").is_err());
+        assert!(parse(&format!("{}a{}", "(".repeat(MAX_DEPTH + 1), ")".repeat(MAX_DEPTH + 1))).is_err());
+        assert!(parse(&format!("{}a", "NOT ".repeat(MAX_DEPTH + 1))).is_err());
+        assert!(parse(&format!("{}a{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH))).is_ok());
+        assert!(parse(&"a ".repeat(MAX_TERMS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_to_sql() {
+        let mut params = Vec::new();
+        let sql = parse("out:create:* OR NOT type:smelting").unwrap().to_sql(&mut params);
+        assert_eq!(sql, "(r.result_item LIKE ?1 ESCAPE '\\' OR NOT (r.canonical_type = ?2))");
+        assert_eq!(params, vec![Value::from("create:%".to_string()), Value::from("minecraft:smelting".to_string())]);
+
+        let mut params = Vec::new();
+        let sql = parse("ns:my_pack").unwrap().to_sql(&mut params);
+        assert_eq!(sql, "r.namespace = ?1");
+        assert_eq!(params, vec![Value::from("my_pack".to_string())]);
+    }
+}
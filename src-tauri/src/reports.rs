@@ -0,0 +1,175 @@
+use crate::conflicts::RecipeConflict;
+use crate::database::{Recipe, RecipeSearch, UnobtainableItem};
+use crate::unification::TagUnification;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// An analysis `export_report` can write out as Markdown.
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReportKind {
+    Conflicts,
+    Unification,
+    Obtainability,
+    SessionDiff { old_session: i64, new_session: i64, locale: Option<String> },
+}
+
+/// Recipes a recipe book covers.
+#[derive(Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum RecipeBookSource {
+    Mod { mod_id: i64 },
+    Collection { collection_id: i64 },
+    Search { filters: Box<RecipeSearch> },
+}
+
+pub fn render_conflicts(conflicts: &[RecipeConflict]) -> String {
+    let mut out = format!("# Recipe conflicts\n\n{} conflicting pairs.\n", conflicts.len());
+    if !conflicts.is_empty() {
+        out.push_str("\n| Recipe | Mod | Makes | Recipe | Mod | Makes |\n|---|---|---|---|---|---|\n");
+        for conflict in conflicts {
+            let (a, b) = (&conflict.first, &conflict.second);
+            out.push_str(&format!(
+                "| `{}` | {} | {} | `{}` | {} | {} |\n",
+                a.recipe_id,
+                cell(&a.mod_name),
+                code(a.result_item.as_deref()),
+                b.recipe_id,
+                cell(&b.mod_name),
+                code(b.result_item.as_deref()),
+            ));
+        }
+    }
+    out
+}
+
+pub fn render_unification(tags: &[TagUnification]) -> String {
+    let mut out = format!("# Tag unification\n\n{} tags hold items from several mods.\n", tags.len());
+    for tag in tags {
+        out.push_str(&format!("\n## `#{}`\n\n", tag.tag));
+        out.push_str(&format!("Recipes taking the tag: {}\n\n", tag.tag_consumers.len()));
+        out.push_str("| Item | Mod | Made by | Asked for by id |\n|---|---|---|---|\n");
+        for variant in &tag.variants {
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                variant.item,
+                cell(variant.mod_name.as_deref().unwrap_or("")),
+                variant.producers.len(),
+                variant.consumers.len(),
+            ));
+        }
+    }
+    out
+}
+
+pub fn render_obtainability(items: &[UnobtainableItem]) -> String {
+    let mut out = format!("# Unobtainable items\n\n{} ingredients have no source.\n", items.len());
+    if !items.is_empty() {
+        out.push_str("\n| Item | Mod | Recipes using it |\n|---|---|---|\n");
+        for item in items {
+            out.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                item.item,
+                cell(item.mod_name.as_deref().unwrap_or("")),
+                item.consumer_count,
+            ));
+        }
+    }
+    out
+}
+
+/// A guide-style page per recipe: shaped grids as tables, then ingredient
+/// and result lists. `names` maps item ids to display names.
+pub fn render_recipe_book(title: &str, recipes: &[Recipe], names: &HashMap<String, String>) -> String {
+    let name = |id: &str| match id.strip_prefix('#') {
+        Some(_) => format!("`{}`", id),
+        None => names.get(id).map_or_else(|| format!("`{}`", id), |n| cell(n)),
+    };
+    let mut out = format!("# {}\n", title);
+    for recipe in recipes {
+        let heading = match &recipe.result_item {
+            Some(item) => format!("{} (`{}`)", name(item), recipe.recipe_id),
+            None => format!("`{}`", recipe.recipe_id),
+        };
+        out.push_str(&format!("\n## {}\n\n", heading));
+        out.push_str(&format!("Type `{}`, from {}\n", recipe.canonical_type, cell(&recipe.mod_name)));
+
+        if let Some(grid) = shaped_grid(&recipe.raw_json) {
+            let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+            out.push_str(&format!("\n|{}\n|{}\n", " |".repeat(width), "---|".repeat(width)));
+            for row in grid {
+                let cells: Vec<String> =
+                    (0..width).map(|i| row.get(i).cloned().flatten().map_or(String::new(), |id| name(&id))).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+        }
+        if !recipe.ingredients.is_empty() {
+            out.push_str("\nIngredients:\n\n");
+            for ingredient in &recipe.ingredients {
+                out.push_str(&format!("- {}x {}\n", ingredient.count, name(&ingredient.item)));
+            }
+        }
+        if let Some(item) = &recipe.result_item {
+            out.push_str(&format!("\nMakes {}x {}\n", recipe.result_count.unwrap_or(1), name(item)));
+        }
+    }
+    out
+}
+
+/// Rows of a shaped recipe's pattern with the item or `#tag` in each slot;
+/// slots with alternatives show the first.
+pub fn shaped_grid(raw_json: &str) -> Option<Vec<Vec<Option<String>>>> {
+    let value: Value = serde_json::from_str(raw_json).ok()?;
+    let key = value.get("key")?.as_object()?;
+    let slot = |symbol: char| -> Option<String> {
+        let mut ingredient = key.get(&symbol.to_string())?;
+        if let Some(first) = ingredient.as_array().and_then(|a| a.first()) {
+            ingredient = first;
+        }
+        if let Some(id) = ingredient.as_str() {
+            return Some(id.to_string());
+        }
+        match (ingredient.get("item").and_then(Value::as_str), ingredient.get("tag").and_then(Value::as_str)) {
+            (Some(item), _) => Some(item.to_string()),
+            (None, Some(tag)) => Some(format!("#{}", tag)),
+            (None, None) => None,
+        }
+    };
+    let rows = value.get("pattern")?.as_array()?;
+    Some(rows.iter().filter_map(Value::as_str).map(|row| row.chars().map(slot).collect()).collect())
+}
+
+/// Escapes the pipes that would end a table cell early.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn code(text: Option<&str>) -> String {
+    text.map_or(String::new(), |t| format!("`{}`", t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shaped_grid() {
+        let grid = shaped_grid(r#"{"pattern":["I ","IS"],"key":{"I":{"item":"a:ingot"},"S":[{"tag":"c:rods"},{"item":"a:stick"}]}}"#).unwrap();
+        assert_eq!(grid[0], vec![Some("a:ingot".to_string()), None]);
+        assert_eq!(grid[1][1].as_deref(), Some("#c:rods"));
+        assert!(shaped_grid(r#"{"ingredients":[]}"#).is_none());
+    }
+
+    #[test]
+    fn test_render_obtainability() {
+        let items = vec![UnobtainableItem {
+            item: "a:gear".to_string(),
+            mod_name: Some("A|B.jar".to_string()),
+            consumer_count: 3,
+        }];
+        let text = render_obtainability(&items);
+        assert!(text.starts_with("# Unobtainable items\n"));
+        assert!(text.contains("| `a:gear` | A\\|B.jar | 3 |\n"));
+    }
+}
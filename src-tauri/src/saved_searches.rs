@@ -0,0 +1,192 @@
+use crate::database::{Database, PinnedFilter, Recipe, RecipeSort};
+use crate::matching::MatchMode;
+use serde::{Deserialize, Serialize};
+
+/// A named recipe search kept for recurring audits.
+#[derive(Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: SearchSpec,
+}
+
+/// The saved searches that could be read, and why the others could not.
+#[derive(Serialize)]
+pub struct SavedSearchList {
+    pub searches: Vec<SavedSearch>,
+    pub errors: Vec<String>,
+}
+
+/// A past search, kept so it can be run again.
+#[derive(Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub searched_at: String,
+    pub result_count: i64,
+    #[serde(flatten)]
+    pub spec: SearchSpec,
+}
+
+/// What to search for and how to present the results.
+#[derive(Serialize, Deserialize)]
+pub struct SearchSpec {
+    pub mode: SearchMode,
+    pub query: String,
+    /// Ignored by full-text searches
+    #[serde(default)]
+    pub match_mode: MatchMode,
+    /// Ingredient searches only; see `Database::search_by_ingredient`
+    #[serde(default)]
+    pub expand_tags: bool,
+    /// Applied instead of the pinned filter while the search runs
+    #[serde(default)]
+    pub filter: PinnedFilter,
+    /// Unset keeps the order the search itself returns
+    #[serde(default)]
+    pub sort: Option<RecipeSort>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchMode {
+    Output,
+    Ingredient,
+    FullText,
+}
+
+/// Full-text searches return at most this many recipes unless the search
+/// sets its own limit.
+const FULL_TEXT_LIMIT: i64 = 200;
+
+pub fn save(db: &Database, search: &SavedSearch) -> Result<(), String> {
+    let definition = serde_json::to_string(search).map_err(|e| e.to_string())?;
+    db.save_search(&search.name, &definition)
+        .map_err(|e| format!("Failed to save search: {}", e))
+}
+
+/// Lists the saved searches, skipping any whose definition no longer
+/// parses, e.g. one saved by a newer version of the app.
+pub fn list(db: &Database) -> Result<SavedSearchList, String> {
+    let definitions = db
+        .list_saved_searches()
+        .map_err(|e| format!("Failed to list searches: {}", e))?;
+    let mut list = SavedSearchList { searches: Vec::new(), errors: Vec::new() };
+    for (name, definition) in definitions {
+        match parse(&definition) {
+            Ok(search) => list.searches.push(search),
+            Err(e) => list.errors.push(format!("{}: {}", name, e)),
+        }
+    }
+    Ok(list)
+}
+
+pub fn run(db: &Database, name: &str) -> Result<Vec<Recipe>, String> {
+    let definition = db
+        .get_saved_search(name)
+        .map_err(|e| format!("Failed to load search: {}", e))?
+        .ok_or_else(|| format!("No saved search named {}", name))?;
+    let search: SavedSearch = parse(&definition)?;
+    run_spec(db, &search.spec)
+}
+
+/// Runs a search on its own read connection, so its filter does not
+/// disturb the pinned one.
+pub fn run_spec(db: &Database, search: &SearchSpec) -> Result<Vec<Recipe>, String> {
+    let reader = db.open_reader().map_err(|e| format!("Failed to open read connection: {}", e))?;
+    reader.set_pinned_filter(search.filter.clone());
+    let recipes = match search.mode {
+        SearchMode::Output => {
+            reader.search_by_output(&search.query, search.match_mode, search.sort.unwrap_or_default())
+        }
+        SearchMode::Ingredient => {
+            reader.search_by_ingredient(&search.query, search.match_mode, search.expand_tags, search.sort.unwrap_or_default())
+        }
+        SearchMode::FullText => reader
+            .search_recipes_fulltext(&search.query, search.limit.unwrap_or(FULL_TEXT_LIMIT), search.sort)
+            .map(|hits| hits.into_iter().map(|hit| hit.recipe).collect()),
+    };
+    let mut recipes = recipes.map_err(|e| format!("Search failed: {}", e))?;
+    if let Some(limit) = search.limit {
+        recipes.truncate(limit.max(0) as usize);
+    }
+    Ok(recipes)
+}
+
+impl SearchSpec {
+    /// A search with default options under the current pinned filter.
+    pub fn pinned(db: &Database, mode: SearchMode, query: &str) -> SearchSpec {
+        SearchSpec {
+            mode,
+            query: query.to_string(),
+            match_mode: MatchMode::default(),
+            expand_tags: false,
+            filter: db.get_pinned_filter(),
+            sort: None,
+            limit: None,
+        }
+    }
+}
+
+/// Adds a search to the history.
+pub fn record(db: &Database, spec: &SearchSpec, result_count: usize) -> Result<(), String> {
+    let definition = serde_json::to_string(&spec).map_err(|e| e.to_string())?;
+    db.record_search(&definition, result_count as i64)
+        .map_err(|e| format!("Failed to record search: {}", e))
+}
+
+/// The most recent searches, newest first.
+pub fn history(db: &Database, limit: i64) -> Result<Vec<HistoryEntry>, String> {
+    let rows = db
+        .list_search_history(limit)
+        .map_err(|e| format!("Failed to load search history: {}", e))?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(HistoryEntry {
+                id: row.id,
+                searched_at: row.searched_at,
+                result_count: row.result_count,
+                spec: parse(&row.definition)?,
+            })
+        })
+        .collect()
+}
+
+/// Runs a search from the history again against the current data.
+pub fn replay(db: &Database, id: i64) -> Result<Vec<Recipe>, String> {
+    let definition = db
+        .get_search_history_entry(id)
+        .map_err(|e| format!("Failed to load search: {}", e))?
+        .ok_or_else(|| format!("No search history entry {}", id))?;
+    run_spec(db, &parse(&definition)?)
+}
+
+fn parse<T: serde::de::DeserializeOwned>(definition: &str) -> Result<T, String> {
+    serde_json::from_str(definition).map_err(|e| format!("Invalid saved search: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_db;
+
+    #[test]
+    fn test_definition_defaults() {
+        let search: SavedSearch = parse(r#"{"name": "Plates", "mode": "output", "query": "plate"}"#).unwrap();
+        assert!(search.spec.sort.is_none());
+        assert!(search.spec.filter.namespace.is_none());
+        assert!(parse::<SavedSearch>(r#"{"name": "Plates", "mode": "fuzzy", "query": "plate"}"#).is_err());
+    }
+
+    #[test]
+    fn test_list_skips_invalid_definitions() {
+        let db = temp_db();
+        db.save_search("Plates", r#"{"name": "Plates", "mode": "output", "query": "plate"}"#).unwrap();
+        db.save_search("Gears", r#"{"name": "Gears", "mode": "fuzzy", "query": "gear"}"#).unwrap();
+        let list = list(&db).unwrap();
+        assert_eq!(list.searches.len(), 1);
+        assert_eq!(list.searches[0].name, "Plates");
+        assert_eq!(list.errors.len(), 1);
+        assert!(list.errors[0].starts_with("Gears: "));
+    }
+}
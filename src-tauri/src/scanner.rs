@@ -1,4 +1,5 @@
 use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek};
 use std::path::Path;
 use serde::Serialize;
 use zip::ZipArchive;
@@ -42,11 +43,75 @@ pub fn scan_directory(path: &str) -> Result<Vec<FileInfo>, String> {
     }
 
     // Sort by name
-    files.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    files.sort_by_key(|f| f.name.to_lowercase());
 
     Ok(files)
 }
 
+/// Lists the data packs in a `datapacks` folder: zips, and folders holding a
+/// `pack.mcmeta`.
+pub fn scan_datapacks(path: &str) -> Result<Vec<FileInfo>, String> {
+    let entries = fs::read_dir(path).map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    let mut packs = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let entry_path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_pack = if entry_path.is_dir() {
+            entry_path.join("pack.mcmeta").is_file()
+        } else {
+            name.to_lowercase().ends_with(".zip")
+        };
+        if is_pack {
+            packs.push(FileInfo {
+                name,
+                path: entry_path.to_string_lossy().to_string(),
+            });
+        }
+    }
+
+    packs.sort_by_key(|f| f.name.to_lowercase());
+    Ok(packs)
+}
+
+/// Identifies a mod across versions by stripping the version from its jar
+/// name, e.g. `create-1.20.1-0.5.1.jar` becomes `create`.
+pub fn mod_key(jar_name: &str) -> String {
+    let stem = jar_name.trim_end_matches(".jar").to_lowercase();
+    let bytes = stem.as_bytes();
+    let version_start = (1..bytes.len())
+        .find(|&i| (bytes[i - 1] == b'-' || bytes[i - 1] == b'_') && bytes[i].is_ascii_digit());
+    match version_start {
+        Some(i) => stem[..i - 1].to_string(),
+        None => stem,
+    }
+}
+
+/// Reads one file out of a jar. `jar_path` may point into nested jars using
+/// `outer.jar!/META-INF/jars/inner.jar`, as recorded for Jar-in-Jar mods.
+pub fn read_jar_file(jar_path: &str, entry_name: &str) -> Result<Vec<u8>, String> {
+    let mut segments = jar_path.split("!/");
+    let outer = segments.next().unwrap_or(jar_path);
+    let mut names = segments.chain(std::iter::once(entry_name));
+
+    let file = File::open(outer).map_err(|e| format!("Failed to open jar: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read jar: {}", e))?;
+    let mut bytes = read_zip_entry(&mut archive, names.next().unwrap_or(entry_name))?;
+    for name in names {
+        let mut nested = ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Failed to read jar: {}", e))?;
+        bytes = read_zip_entry(&mut nested, name)?;
+    }
+    Ok(bytes)
+}
+
+fn read_zip_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = archive.by_name(name).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(bytes)
+}
+
 #[derive(Serialize)]
 pub struct JarEntry {
     pub name: String,
@@ -75,8 +140,21 @@ pub fn read_jar_contents(path: &str) -> Result<Vec<JarEntry>, String> {
     }
 
     // Sort entries alphabetically
-    entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    entries.sort_by_key(|e| e.name.to_lowercase());
 
     Ok(entries)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_key() {
+        assert_eq!(mod_key("create-1.20.1-0.5.1.f.jar"), "create");
+        assert_eq!(mod_key("Mekanism_10.4.5.jar"), "mekanism");
+        assert_eq!(mod_key("appliedenergistics2-forge-15.0.15.jar"), "appliedenergistics2-forge");
+        assert_eq!(mod_key("somemod.jar"), "somemod");
+    }
+}
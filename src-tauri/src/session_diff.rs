@@ -0,0 +1,528 @@
+use crate::recipe_parser::{self, ParsedRecipe};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// What a finished extraction indexed, as stored for later comparison.
+/// Mods are keyed by `scanner::mod_key` so version bumps match up.
+pub struct SessionSnapshot {
+    pub mods: Vec<SessionMod>,
+    /// Mod key, recipe id and raw JSON
+    pub recipes: Vec<(String, String, String)>,
+    /// Tag and entry
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Clone, PartialEq)]
+pub struct SessionMod {
+    pub mod_key: String,
+    /// Jar name
+    pub name: String,
+    pub display_name: Option<String>,
+    pub version: Option<String>,
+}
+
+/// A mod present in both sessions under a different jar.
+#[derive(Serialize)]
+pub struct ModChange {
+    pub mod_key: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RecipeRef {
+    pub mod_key: String,
+    pub recipe_id: String,
+}
+
+#[derive(Serialize)]
+pub struct RecipeChange {
+    pub mod_key: String,
+    pub recipe_id: String,
+    pub changes: Vec<JsonChange>,
+}
+
+/// One value that differs, at a JSON pointer such as `/key/#/item`.
+#[derive(Serialize)]
+pub struct JsonChange {
+    pub path: String,
+    /// `None` where the value was added
+    pub old: Option<Value>,
+    /// `None` where the value was removed
+    pub new: Option<Value>,
+}
+
+#[derive(Serialize)]
+pub struct TagChange {
+    pub tag: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Recipes in the open workspace using the tag, which a membership
+    /// change can quietly break; filled in by the caller
+    pub consumers: i64,
+}
+
+#[derive(Serialize, Default)]
+pub struct SessionDiff {
+    pub added_mods: Vec<SessionMod>,
+    pub removed_mods: Vec<SessionMod>,
+    pub updated_mods: Vec<ModChange>,
+    pub added_recipes: Vec<RecipeRef>,
+    pub removed_recipes: Vec<RecipeRef>,
+    pub changed_recipes: Vec<RecipeChange>,
+    pub changed_tags: Vec<TagChange>,
+}
+
+/// A user override or removal aimed at a recipe an update no longer ships.
+#[derive(Serialize)]
+pub struct StaleTarget {
+    pub recipe_id: String,
+    pub kind: StaleKind,
+    /// Datapack jar holding the override
+    pub source: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleKind {
+    /// A datapack copy of the recipe
+    Override,
+    /// A recipe note marked "remove"
+    Removal,
+}
+
+/// What updating one jar changes, from extracting both versions alone.
+#[derive(Serialize)]
+pub struct UpdateImpact {
+    pub diff: SessionDiff,
+    pub stale: Vec<StaleTarget>,
+    /// Problems reading entries of either jar
+    pub errors: Vec<String>,
+}
+
+/// A recipe's history over the sessions with snapshots.
+#[derive(Serialize)]
+pub struct RecipeHistory {
+    pub mod_key: String,
+    pub recipe_id: String,
+    /// Sessions where the recipe appeared, changed or disappeared, oldest first
+    pub events: Vec<RecipeEvent>,
+    /// Session of the last event, after which the recipe stayed the same
+    pub unchanged_since: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct RecipeEvent {
+    pub session_id: i64,
+    pub started_at: String,
+    pub change: HistoryChange,
+    /// Top-level fields that changed, such as `ingredients` or `result`
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryChange {
+    Added,
+    Changed,
+    Removed,
+}
+
+/// Builds a recipe's history from its JSON in each snapshotted session
+/// (id, start time, JSON if the recipe was there), oldest first.
+pub fn recipe_history(mod_key: &str, recipe_id: &str, sessions: &[(i64, String, Option<String>)]) -> RecipeHistory {
+    let mut events = Vec::new();
+    let mut previous: Option<&str> = None;
+    for (session_id, started_at, json) in sessions {
+        let event = |change, fields| RecipeEvent { session_id: *session_id, started_at: started_at.clone(), change, fields };
+        match (previous, json.as_deref()) {
+            (None, Some(_)) => events.push(event(HistoryChange::Added, Vec::new())),
+            (Some(_), None) => events.push(event(HistoryChange::Removed, Vec::new())),
+            (Some(old), Some(new)) if old != new => {
+                let changes = json_changes(old, new);
+                if !changes.is_empty() {
+                    let fields: BTreeSet<String> = changes
+                        .iter()
+                        .map(|c| c.path.trim_start_matches('/').split('/').next().unwrap_or_default().to_string())
+                        .collect();
+                    events.push(event(HistoryChange::Changed, fields.into_iter().collect()));
+                }
+            }
+            _ => {}
+        }
+        previous = json.as_deref();
+    }
+    RecipeHistory {
+        mod_key: mod_key.to_string(),
+        recipe_id: recipe_id.to_string(),
+        unchanged_since: events.last().map(|e| e.session_id),
+        events,
+    }
+}
+
+/// Everything that differs going from `old` to `new`.
+pub fn diff(old: &SessionSnapshot, new: &SessionSnapshot) -> SessionDiff {
+    let mut result = SessionDiff::default();
+
+    let old_mods: BTreeMap<&str, &SessionMod> = old.mods.iter().map(|m| (m.mod_key.as_str(), m)).collect();
+    let new_mods: BTreeMap<&str, &SessionMod> = new.mods.iter().map(|m| (m.mod_key.as_str(), m)).collect();
+    for (key, new_mod) in &new_mods {
+        match old_mods.get(key) {
+            None => result.added_mods.push((*new_mod).clone()),
+            Some(old_mod) if (&old_mod.name, &old_mod.version) != (&new_mod.name, &new_mod.version) => {
+                result.updated_mods.push(ModChange {
+                    mod_key: key.to_string(),
+                    old_name: old_mod.name.clone(),
+                    new_name: new_mod.name.clone(),
+                    old_version: old_mod.version.clone(),
+                    new_version: new_mod.version.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, old_mod) in &old_mods {
+        if !new_mods.contains_key(key) {
+            result.removed_mods.push((*old_mod).clone());
+        }
+    }
+
+    let recipes = |snapshot: &SessionSnapshot| -> BTreeMap<(String, String), String> {
+        snapshot.recipes.iter().map(|(key, id, json)| ((key.clone(), id.clone()), json.clone())).collect()
+    };
+    let (old_recipes, new_recipes) = (recipes(old), recipes(new));
+    let recipe_ref = |(mod_key, recipe_id): &(String, String)| RecipeRef { mod_key: mod_key.clone(), recipe_id: recipe_id.clone() };
+    for (key, new_json) in &new_recipes {
+        match old_recipes.get(key) {
+            None => result.added_recipes.push(recipe_ref(key)),
+            Some(old_json) if old_json != new_json => {
+                let changes = json_changes(old_json, new_json);
+                if !changes.is_empty() {
+                    result.changed_recipes.push(RecipeChange { mod_key: key.0.clone(), recipe_id: key.1.clone(), changes });
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    for key in old_recipes.keys().filter(|key| !new_recipes.contains_key(*key)) {
+        result.removed_recipes.push(recipe_ref(key));
+    }
+
+    let tags = |snapshot: &SessionSnapshot| -> BTreeMap<String, BTreeSet<String>> {
+        let mut tags: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (tag, entry) in &snapshot.tags {
+            tags.entry(tag.clone()).or_default().insert(entry.clone());
+        }
+        tags
+    };
+    let (old_tags, new_tags) = (tags(old), tags(new));
+    let empty = BTreeSet::new();
+    let names: BTreeSet<&String> = old_tags.keys().chain(new_tags.keys()).collect();
+    for tag in names {
+        let before = old_tags.get(tag).unwrap_or(&empty);
+        let after = new_tags.get(tag).unwrap_or(&empty);
+        if before != after {
+            result.changed_tags.push(TagChange {
+                tag: tag.clone(),
+                added: after.difference(before).cloned().collect(),
+                removed: before.difference(after).cloned().collect(),
+                consumers: 0,
+            });
+        }
+    }
+    result
+}
+
+/// Raw JSON of a snapshot's recipes by mod key and recipe id.
+fn recipe_texts(snapshot: &SessionSnapshot) -> HashMap<(&str, &str), &str> {
+    snapshot.recipes.iter().map(|(key, id, json)| ((key.as_str(), id.as_str()), json.as_str())).collect()
+}
+
+/// The diff from `old` to `new` as a Markdown changelog with a section per
+/// mod. `name` gives an item's display name.
+pub fn changelog(old: &SessionSnapshot, new: &SessionSnapshot, name: &dyn Fn(&str) -> String) -> String {
+    let diff = diff(old, new);
+    // Old mods first, so new names win
+    let mods: HashMap<&str, &SessionMod> =
+        old.mods.iter().chain(&new.mods).map(|m| (m.mod_key.as_str(), m)).collect();
+    let heading = |key: &str| match mods.get(key) {
+        Some(m) => m.display_name.clone().unwrap_or_else(|| m.name.clone()),
+        None => key.to_string(),
+    };
+    let (old_json, new_json) = (recipe_texts(old), recipe_texts(new));
+    let json = |texts: &HashMap<(&str, &str), &str>, r: &(String, String)| -> Option<ParsedRecipe> {
+        recipe_parser::parse_recipe(texts.get(&(r.0.as_str(), r.1.as_str()))?).ok()
+    };
+    let subject = |id: &str, recipe: Option<&ParsedRecipe>| match recipe.and_then(|r| r.result_item.as_deref()) {
+        Some(item) => format!("{} (`{}`)", name(item), id),
+        None => format!("`{}`", id),
+    };
+
+    let mut sections: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for m in &diff.added_mods {
+        sections.entry(heading(&m.mod_key)).or_default().push(format!("Added {}", version_label(&m.name, &m.version)));
+    }
+    for m in &diff.removed_mods {
+        sections.entry(heading(&m.mod_key)).or_default().push(format!("Removed {}", version_label(&m.name, &m.version)));
+    }
+    for m in &diff.updated_mods {
+        sections.entry(heading(&m.mod_key)).or_default().push(format!(
+            "Updated {} to {}",
+            version_label(&m.old_name, &m.old_version),
+            version_label(&m.new_name, &m.new_version)
+        ));
+    }
+    for r in &diff.added_recipes {
+        let key = (r.mod_key.clone(), r.recipe_id.clone());
+        let recipe = json(&new_json, &key);
+        let mut line = format!("Added recipe {}", subject(&r.recipe_id, recipe.as_ref()));
+        if let Some(recipe) = &recipe {
+            line.push_str(&format!(": {}", ingredient_list(recipe, name)));
+        }
+        sections.entry(heading(&r.mod_key)).or_default().push(line);
+    }
+    for r in &diff.removed_recipes {
+        let recipe = json(&old_json, &(r.mod_key.clone(), r.recipe_id.clone()));
+        sections
+            .entry(heading(&r.mod_key))
+            .or_default()
+            .push(format!("Removed recipe {}", subject(&r.recipe_id, recipe.as_ref())));
+    }
+    for r in &diff.changed_recipes {
+        let key = (r.mod_key.clone(), r.recipe_id.clone());
+        let lines = sections.entry(heading(&r.mod_key)).or_default();
+        let (Some(before), Some(after)) = (json(&old_json, &key), json(&new_json, &key)) else {
+            lines.push(format!("Changed recipe `{}` ({} fields)", r.recipe_id, r.changes.len()));
+            continue;
+        };
+        let subject = subject(&r.recipe_id, Some(&after));
+        let mut described = false;
+        let (was, now) = (ingredient_list(&before, name), ingredient_list(&after, name));
+        if was != now {
+            lines.push(format!("{} now requires {} (was {})", subject, now, was));
+            described = true;
+        }
+        let (was, now) = (result_label(&before, name), result_label(&after, name));
+        if was != now {
+            lines.push(format!("{} now makes {} (was {})", subject, now, was));
+            described = true;
+        }
+        if before.recipe_type != after.recipe_type {
+            lines.push(format!("{} is now `{}` (was `{}`)", subject, after.recipe_type, before.recipe_type));
+            described = true;
+        }
+        if !described {
+            lines.push(format!("Changed recipe {} ({} fields)", subject, r.changes.len()));
+        }
+    }
+
+    let mut out = String::from("# Changelog\n");
+    for (heading, lines) in &sections {
+        out.push_str(&format!("\n## {}\n\n", heading));
+        for line in lines {
+            out.push_str(&format!("- {}\n", line));
+        }
+    }
+    if !diff.changed_tags.is_empty() {
+        out.push_str("\n## Tags\n\n");
+        for tag in &diff.changed_tags {
+            let mut parts = Vec::new();
+            if !tag.added.is_empty() {
+                parts.push(format!("added {}", tag.added.iter().map(|i| name(i)).collect::<Vec<_>>().join(", ")));
+            }
+            if !tag.removed.is_empty() {
+                parts.push(format!("removed {}", tag.removed.iter().map(|i| name(i)).collect::<Vec<_>>().join(", ")));
+            }
+            out.push_str(&format!("- `#{}`: {}\n", tag.tag, parts.join("; ")));
+        }
+    }
+    if sections.is_empty() && diff.changed_tags.is_empty() {
+        out.push_str("\nNo changes.\n");
+    }
+    out
+}
+
+fn version_label(name: &str, version: &Option<String>) -> String {
+    match version {
+        Some(version) => format!("{} {}", name, version),
+        None => name.to_string(),
+    }
+}
+
+/// Item inputs such as `2x Andesite Alloy, 1x #c:plates`; tags keep their id.
+fn ingredient_list(recipe: &ParsedRecipe, name: &dyn Fn(&str) -> String) -> String {
+    let items: Vec<String> = recipe
+        .ingredients
+        .iter()
+        .map(|i| format!("{}x {}", i.count, if i.item.starts_with('#') { i.item.clone() } else { name(&i.item) }))
+        .chain(recipe.fluids.iter().filter(|f| !f.is_output).map(|f| fluid_label(f, name)))
+        .collect();
+    if items.is_empty() {
+        "nothing".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+fn result_label(recipe: &ParsedRecipe, name: &dyn Fn(&str) -> String) -> String {
+    let mut outputs: Vec<String> = recipe
+        .result_item
+        .iter()
+        .map(|item| format!("{}x {}", recipe.result_count.unwrap_or(1), name(item)))
+        .collect();
+    outputs.extend(recipe.fluids.iter().filter(|f| f.is_output).map(|f| fluid_label(f, name)));
+    if outputs.is_empty() {
+        "nothing".to_string()
+    } else {
+        outputs.join(", ")
+    }
+}
+
+fn fluid_label(fluid: &recipe_parser::FluidAmount, name: &dyn Fn(&str) -> String) -> String {
+    match fluid.amount {
+        Some(amount) => format!("{} mB {}", amount, name(&fluid.fluid)),
+        None => name(&fluid.fluid),
+    }
+}
+
+/// Differences between two JSON documents; text that is not valid JSON is
+/// compared as a whole.
+pub fn json_changes(old: &str, new: &str) -> Vec<JsonChange> {
+    let parse = |text: &str| serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()));
+    let mut changes = Vec::new();
+    compare("", &parse(old), &parse(new), &mut changes);
+    changes
+}
+
+fn compare(path: &str, old: &Value, new: &Value, changes: &mut Vec<JsonChange>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => compare(&child, x, y, changes),
+                    (x, y) => changes.push(JsonChange { path: child, old: x.cloned(), new: y.cloned() }),
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for i in 0..a.len().max(b.len()) {
+                let child = format!("{}/{}", path, i);
+                match (a.get(i), b.get(i)) {
+                    (Some(x), Some(y)) => compare(&child, x, y, changes),
+                    (x, y) => changes.push(JsonChange { path: child, old: x.cloned(), new: y.cloned() }),
+                }
+            }
+        }
+        _ if old != new => changes.push(JsonChange { path: path.to_string(), old: Some(old.clone()), new: Some(new.clone()) }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_changes() {
+        let changes = json_changes(
+            r##"{"result":{"item":"a:x","count":1},"key":{"#":"a:y"}}"##,
+            r##"{"result":{"item":"a:x","count":2},"group":"g"}"##,
+        );
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["/group", "/key", "/result/count"]);
+        assert!(changes[0].old.is_none());
+        assert!(changes[1].new.is_none());
+    }
+
+    fn session_mod(key: &str, name: &str, version: Option<&str>) -> SessionMod {
+        SessionMod {
+            mod_key: key.to_string(),
+            name: name.to_string(),
+            display_name: None,
+            version: version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = SessionSnapshot {
+            mods: vec![session_mod("create", "create-1.0.jar", Some("1.0")), session_mod("old", "old.jar", None)],
+            recipes: vec![("create".into(), "create:a".into(), "{\"n\":1}".into()), ("old".into(), "old:b".into(), "{}".into())],
+            tags: vec![("c:ingots".into(), "a:x".into())],
+        };
+        let new = SessionSnapshot {
+            mods: vec![session_mod("create", "create-1.1.jar", Some("1.1")), session_mod("new", "new.jar", None)],
+            recipes: vec![("create".into(), "create:a".into(), "{\"n\":2}".into()), ("new".into(), "new:c".into(), "{}".into())],
+            tags: vec![("c:ingots".into(), "a:y".into())],
+        };
+        let diff = diff(&old, &new);
+        assert_eq!(diff.added_mods[0].mod_key, "new");
+        assert_eq!(diff.removed_mods[0].mod_key, "old");
+        assert_eq!(diff.updated_mods[0].new_version.as_deref(), Some("1.1"));
+        assert_eq!(diff.added_recipes[0].recipe_id, "new:c");
+        assert_eq!(diff.removed_recipes[0].recipe_id, "old:b");
+        assert_eq!(diff.changed_recipes[0].changes[0].path, "/n");
+        assert_eq!(diff.changed_tags[0].added, vec!["a:y"]);
+        assert_eq!(diff.changed_tags[0].removed, vec!["a:x"]);
+    }
+
+    #[test]
+    fn test_recipe_history() {
+        let session = |id: i64, json: Option<&str>| (id, format!("day {}", id), json.map(str::to_string));
+        let sessions = vec![
+            session(1, None),
+            session(2, Some(r#"{"ingredients":["a:x"],"result":"a:y"}"#)),
+            session(3, Some(r#"{"ingredients":["a:x"],"result":"a:y"}"#)),
+            session(4, Some(r#"{"ingredients":["a:z"],"result":"a:y"}"#)),
+            session(5, None),
+            session(6, Some(r#"{"ingredients":["a:z"],"result":"a:y"}"#)),
+        ];
+        let history = recipe_history("a", "a:y", &sessions);
+        let changes: Vec<HistoryChange> = history.events.iter().map(|e| e.change).collect();
+        assert_eq!(
+            changes,
+            vec![HistoryChange::Added, HistoryChange::Changed, HistoryChange::Removed, HistoryChange::Added]
+        );
+        assert_eq!(history.events[1].session_id, 4);
+        assert_eq!(history.events[1].fields, vec!["ingredients"]);
+        assert_eq!(history.unchanged_since, Some(6));
+    }
+
+    #[test]
+    fn test_changelog() {
+        let recipe = |count: i32| {
+            format!(
+                r#"{{"type":"minecraft:crafting_shapeless","ingredients":[{}],"result":{{"item":"create:crushing_wheel"}}}}"#,
+                vec![r#"{"item":"create:andesite_alloy"}"#; count as usize].join(",")
+            )
+        };
+        let mut create = session_mod("create", "create-1.0.jar", Some("1.0"));
+        create.display_name = Some("Create".into());
+        let old = SessionSnapshot {
+            mods: vec![create.clone()],
+            recipes: vec![("create".into(), "create:crushing_wheel".into(), recipe(1))],
+            tags: vec![],
+        };
+        let new = SessionSnapshot {
+            mods: vec![create],
+            recipes: vec![("create".into(), "create:crushing_wheel".into(), recipe(2))],
+            tags: vec![("c:wheels".into(), "create:crushing_wheel".into())],
+        };
+        let name = |id: &str| match id {
+            "create:crushing_wheel" => "Crushing Wheel".to_string(),
+            "create:andesite_alloy" => "Andesite Alloy".to_string(),
+            _ => id.to_string(),
+        };
+        let text = changelog(&old, &new, &name);
+        assert!(text.contains("## Create\n"));
+        assert!(text.contains(
+            "- Crushing Wheel (`create:crushing_wheel`) now requires 2x Andesite Alloy (was 1x Andesite Alloy)"
+        ));
+        assert!(text.contains("- `#c:wheels`: added Crushing Wheel"));
+    }
+}
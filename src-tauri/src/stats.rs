@@ -0,0 +1,82 @@
+use crate::database::Database;
+use rusqlite::Result as SqliteResult;
+use serde::{Deserialize, Serialize};
+
+/// Which sections to include in an exported stats bundle.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StatsOptions {
+    pub recipe_types: bool,
+    pub parser_coverage: bool,
+    pub error_categories: bool,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        StatsOptions {
+            recipe_types: true,
+            parser_coverage: true,
+            error_categories: true,
+        }
+    }
+}
+
+/// Anonymized summary of the last extraction, safe to attach to bug reports:
+/// counts only, with no raw JSON, file paths or mod names.
+#[derive(Serialize)]
+pub struct PackStats {
+    pub app_version: String,
+    pub mod_count: i64,
+    pub recipe_types: Option<Vec<CategoryCount>>,
+    pub parser_coverage: Option<ParserCoverage>,
+    pub error_categories: Option<Vec<CategoryCount>>,
+}
+
+#[derive(Serialize)]
+pub struct CategoryCount {
+    pub name: String,
+    pub count: i64,
+}
+
+/// How many stored recipes the parser understood well enough to index.
+#[derive(Serialize)]
+pub struct ParserCoverage {
+    pub recipes: i64,
+    pub with_result: i64,
+    pub with_ingredients: i64,
+    pub with_fluids: i64,
+}
+
+pub fn collect_pack_stats(db: &Database, options: &StatsOptions) -> SqliteResult<PackStats> {
+    Ok(PackStats {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        mod_count: db.get_mod_count()?,
+        recipe_types: options.recipe_types.then(|| db.recipe_type_counts()).transpose()?,
+        parser_coverage: options.parser_coverage.then(|| db.parser_coverage()).transpose()?,
+        error_categories: options.error_categories.then(|| db.error_category_counts()).transpose()?,
+    })
+}
+
+/// Reduces an extraction error to its message kind, dropping the mod and
+/// entry it was reported against and any details after the kind, e.g.
+/// `create.jar:data/create/recipes/x.json: Invalid JSON: expected value`
+/// becomes `Invalid JSON`.
+pub fn error_category(error: &str) -> String {
+    let message = error.split_once(": ").map_or(error, |(_, message)| message);
+    let kind = message.split(':').next().unwrap_or(message).trim();
+    kind.chars().take(80).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_category() {
+        assert_eq!(
+            error_category("create.jar:data/create/recipes/x.json: Invalid JSON: expected value at line 1"),
+            "Invalid JSON"
+        );
+        assert_eq!(error_category("/mods/broken.jar: invalid Zip archive: No valid central directory"), "invalid Zip archive");
+    }
+}
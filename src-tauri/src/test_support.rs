@@ -0,0 +1,61 @@
+use crate::database::{Database, ModInfo, Recipe};
+use crate::recipe_parser;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A shapeless recipe turning bamboo into a stick.
+pub const STICK: &str = r#"{"type":"minecraft:crafting_shapeless","ingredients":[{"item":"minecraft:bamboo"}],"result":{"item":"minecraft:stick"}}"#;
+
+/// A mod as `list_mods` would return it, with only its jar name and loader
+/// mod id filled in. Set other fields with `..mod_info(id)`.
+pub fn mod_info(mod_id: &str) -> ModInfo {
+    ModInfo {
+        name: format!("{}.jar", mod_id),
+        loader_mod_id: Some(mod_id.to_string()),
+        ..Default::default()
+    }
+}
+
+/// A recipe with only its id and result filled in. Set other fields with
+/// `..recipe(id, result)`.
+pub fn recipe(recipe_id: &str, result_item: Option<&str>) -> Recipe {
+    Recipe {
+        recipe_id: recipe_id.to_string(),
+        canonical_type: "minecraft:crafting_shaped".to_string(),
+        result_item: result_item.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+/// Adds a shapeless recipe making `output` from one of each ingredient; a
+/// leading `#` makes an ingredient a tag.
+pub fn add_shapeless(db: &Database, mod_id: i64, recipe_id: &str, output: &str, ingredients: &[&str]) {
+    let ingredients: Vec<serde_json::Value> = ingredients
+        .iter()
+        .map(|i| match i.strip_prefix('#') {
+            Some(tag) => serde_json::json!({ "tag": tag }),
+            None => serde_json::json!({ "item": i }),
+        })
+        .collect();
+    let json = serde_json::json!({
+        "type": "minecraft:crafting_shapeless",
+        "ingredients": ingredients,
+        "result": { "item": output },
+    })
+    .to_string();
+    let path = format!("data/{}.json", recipe_id.replace(':', "/recipe/"));
+    db.insert_recipe(mod_id, &path, recipe_id, &json, &recipe_parser::parse_recipe(&json).unwrap()).unwrap();
+}
+
+/// A fresh database in the temp directory, unique to the calling test.
+pub fn temp_db() -> Database {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "modpack-workbench-test-{}-{}.db",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    Database::new(path).unwrap()
+}
@@ -0,0 +1,169 @@
+use crate::recipe_parser::IngredientCount;
+use serde_json::Value;
+
+/// One offer: what the trader wants and what it gives back.
+#[derive(Debug, PartialEq)]
+pub struct ParsedTrade {
+    pub profession: Option<String>,
+    pub level: Option<i32>,
+    pub costs: Vec<IngredientCount>,
+    pub result: IngredientCount,
+}
+
+/// Keys a trade uses for its payment, across the formats mods ship.
+const COST_KEYS: &[&str] = &["buy", "buyA", "buyB", "wants", "cost", "price", "input", "inputs"];
+
+/// Keys a trade uses for the item it hands out.
+const RESULT_KEYS: &[&str] = &["sell", "gives", "result", "output"];
+
+/// Returns `<namespace>:<path>` for JSON files under `data/<namespace>/` that
+/// sit in a trade folder such as `trades/`, `villager_trades/` or
+/// `wandering_trader/trades/`. Recipe folders are left to the recipe
+/// reader, whatever their subfolders are called.
+pub fn trade_file_id(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("data/")?.strip_suffix(".json")?;
+    let (namespace, rest) = rest.split_once('/')?;
+    let (folders, _) = rest.rsplit_once('/')?;
+    if matches!(folders.split('/').next(), Some("recipe" | "recipes")) {
+        return None;
+    }
+    if !folders.split('/').any(|folder| folder.ends_with("trades") || folder == "trade") {
+        return None;
+    }
+    Some(format!("{}:{}", namespace, rest))
+}
+
+/// Finds every trade offer in the file. Offers may be nested under
+/// profession or level groups; those are carried down to the offers.
+pub fn parse_trades(json_str: &str) -> Result<Vec<ParsedTrade>, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let mut trades = Vec::new();
+    collect_trades(&value, None, None, &mut trades);
+    Ok(trades)
+}
+
+fn collect_trades(
+    value: &Value,
+    profession: Option<&str>,
+    level: Option<i32>,
+    trades: &mut Vec<ParsedTrade>,
+) {
+    match value {
+        Value::Array(arr) => {
+            for v in arr {
+                collect_trades(v, profession, level, trades);
+            }
+        }
+        Value::Object(obj) => {
+            let profession = obj.get("profession").and_then(|v| v.as_str()).or(profession);
+            let level = obj.get("level")
+                .or(obj.get("tier"))
+                .and_then(|v| v.as_i64())
+                .map(|v| v as i32)
+                .or(level);
+
+            if let Some(trade) = parse_offer(obj, profession, level) {
+                trades.push(trade);
+                return;
+            }
+            for v in obj.values() {
+                collect_trades(v, profession, level, trades);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_offer(
+    obj: &serde_json::Map<String, Value>,
+    profession: Option<&str>,
+    level: Option<i32>,
+) -> Option<ParsedTrade> {
+    let result = RESULT_KEYS
+        .iter()
+        .find_map(|key| obj.get(*key))
+        .and_then(|v| stacks(v).into_iter().next())?;
+
+    let costs: Vec<IngredientCount> = COST_KEYS
+        .iter()
+        .filter_map(|key| obj.get(*key))
+        .flat_map(stacks)
+        .collect();
+    if costs.is_empty() {
+        return None;
+    }
+
+    Some(ParsedTrade {
+        profession: profession.map(|s| s.to_string()),
+        level,
+        costs,
+        result,
+    })
+}
+
+/// Reads an item stack or a list of them. Stacks are an id string or an
+/// object with `item`/`id`/`tag` and an optional `count`/`quantity`.
+fn stacks(value: &Value) -> Vec<IngredientCount> {
+    match value {
+        Value::String(s) => vec![IngredientCount { item: s.clone(), count: 1 }],
+        Value::Array(arr) => arr.iter().flat_map(stacks).collect(),
+        Value::Object(obj) => {
+            let item = obj.get("item")
+                .or(obj.get("id"))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .or_else(|| obj.get("tag").and_then(|v| v.as_str()).map(|s| format!("#{}", s)));
+            let count = obj.get("count")
+                .or(obj.get("quantity"))
+                .and_then(|v| v.as_i64())
+                .unwrap_or(1) as i32;
+            item.map(|item| vec![IngredientCount { item, count }]).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_file_id() {
+        assert_eq!(
+            trade_file_id("data/easy_villagers/trades/farmer.json"),
+            Some("easy_villagers:trades/farmer".to_string())
+        );
+        assert_eq!(
+            trade_file_id("data/numismatics/villager_trades/banker/novice.json"),
+            Some("numismatics:villager_trades/banker/novice".to_string())
+        );
+        assert_eq!(trade_file_id("data/create/recipes/trades.json"), None);
+        assert_eq!(trade_file_id("data/mypack/recipes/villager_trades/emerald.json"), None);
+        assert_eq!(trade_file_id("data/mypack/recipe/wandering_trades/map.json"), None);
+    }
+
+    #[test]
+    fn test_parse_trades() {
+        let json = r##"{
+            "profession": "minecraft:farmer",
+            "tiers": [{
+                "level": 2,
+                "trades": [
+                    {"buy": {"item": "minecraft:wheat", "count": 20}, "sell": "minecraft:emerald"},
+                    {"wants": [{"item": "minecraft:emerald", "quantity": 3}, "minecraft:bowl"],
+                     "gives": [{"item": "minecraft:mushroom_stew"}]}
+                ]
+            }]
+        }"##;
+
+        let trades = parse_trades(json).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].profession, Some("minecraft:farmer".to_string()));
+        assert_eq!(trades[0].level, Some(2));
+        assert_eq!(trades[0].costs, vec![IngredientCount { item: "minecraft:wheat".to_string(), count: 20 }]);
+        assert_eq!(trades[1].costs.len(), 2);
+        assert_eq!(trades[1].result.item, "minecraft:mushroom_stew");
+    }
+}
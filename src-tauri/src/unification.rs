@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// An item some recipe makes.
+#[derive(Serialize, Clone)]
+pub struct OutputItem {
+    pub item: String,
+    pub display_name: Option<String>,
+    /// Owner of the item's namespace
+    pub mod_name: Option<String>,
+    pub recipe_count: i64,
+}
+
+/// Items from several namespaces that look like the same thing.
+#[derive(Serialize)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    /// The shared tag, or the name the items share
+    pub key: String,
+    pub items: Vec<OutputItem>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// Listed in the same specific tag, such as `forge:ingots/bronze`
+    Tag,
+    /// Same display name, or same id path when there is no name
+    Name,
+}
+
+/// A tag whose items come from several mods, with how recipes make and use
+/// each of them.
+#[derive(Serialize)]
+pub struct TagUnification {
+    pub tag: String,
+    pub variants: Vec<TagVariant>,
+    /// Recipe ids taking the tag itself, which accept every variant
+    pub tag_consumers: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TagVariant {
+    pub item: String,
+    pub mod_name: Option<String>,
+    /// Recipe ids making this variant
+    pub producers: Vec<String>,
+    /// Recipe ids asking for this variant by id rather than through the tag
+    pub consumers: Vec<String>,
+}
+
+/// Tags, out of (tag, item) pairs, whose items span more than one namespace.
+pub fn multi_namespace_tags(members: &[(String, String)]) -> BTreeMap<&str, Vec<&str>> {
+    let mut tags: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (tag, item) in members {
+        tags.entry(tag).or_default().push(item);
+    }
+    tags.retain(|_, items| {
+        items.sort();
+        items.dedup();
+        items.iter().map(|item| namespace(item)).collect::<HashSet<_>>().len() > 1
+    });
+    tags
+}
+
+/// Groups recipe outputs from different namespaces that share a specific
+/// tag or a name. Broad tags like `forge:ingots` group everything of a kind,
+/// so only tags with a `/` in their path count. A name group with the same
+/// items as a tag group is left out.
+pub fn duplicate_groups(outputs: &[OutputItem], tag_entries: &[(String, String)]) -> Vec<DuplicateGroup> {
+    let by_item: HashMap<&str, &OutputItem> = outputs.iter().map(|o| (o.item.as_str(), o)).collect();
+
+    let mut by_tag: BTreeMap<&str, Vec<&OutputItem>> = BTreeMap::new();
+    for (tag, entry) in tag_entries {
+        let specific = tag.split_once(':').is_some_and(|(_, path)| path.contains('/'));
+        if let Some(output) = by_item.get(entry.as_str()).filter(|_| specific) {
+            by_tag.entry(tag).or_default().push(output);
+        }
+    }
+    let mut by_name: BTreeMap<String, Vec<&OutputItem>> = BTreeMap::new();
+    for output in outputs {
+        by_name.entry(name_key(output)).or_default().push(output);
+    }
+
+    let mut groups = Vec::new();
+    let mut seen: HashSet<Vec<&str>> = HashSet::new();
+    let candidates = by_tag
+        .into_iter()
+        .map(|(tag, items)| (DuplicateReason::Tag, format!("#{}", tag), items))
+        .chain(by_name.into_iter().map(|(name, items)| (DuplicateReason::Name, name, items)));
+    for (reason, key, mut items) in candidates {
+        items.sort_by(|a, b| a.item.cmp(&b.item));
+        items.dedup_by(|a, b| a.item == b.item);
+        let namespaces: HashSet<&str> = items.iter().map(|o| namespace(&o.item)).collect();
+        let ids: Vec<&str> = items.iter().map(|o| o.item.as_str()).collect();
+        if namespaces.len() > 1 && seen.insert(ids) {
+            groups.push(DuplicateGroup { reason, key, items: items.into_iter().cloned().collect() });
+        }
+    }
+    groups
+}
+
+/// Lowercased display name, or id path, with only letters and digits kept.
+fn name_key(output: &OutputItem) -> String {
+    let name = output.display_name.as_deref().unwrap_or_else(|| output.item.split_once(':').map_or(&output.item, |(_, path)| path));
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words.join(" ")
+}
+
+fn namespace(item: &str) -> &str {
+    item.split_once(':').map_or("", |(namespace, _)| namespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(item: &str, display_name: Option<&str>) -> OutputItem {
+        OutputItem {
+            item: item.to_string(),
+            display_name: display_name.map(str::to_string),
+            mod_name: None,
+            recipe_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_duplicate_groups() {
+        let outputs = vec![
+            output("create:bronze_ingot", None),
+            output("thermal:bronze_ingot", Some("Bronze Ingot")),
+            output("mekanism:ingot_tin", Some("Tin Ingot")),
+            output("thermal:tin_ingot", Some("Tin Ingot")),
+            output("minecraft:iron_ingot", Some("Iron Ingot")),
+        ];
+        let tags = vec![
+            ("forge:ingots/tin".to_string(), "mekanism:ingot_tin".to_string()),
+            ("forge:ingots/tin".to_string(), "thermal:tin_ingot".to_string()),
+            ("forge:ingots".to_string(), "minecraft:iron_ingot".to_string()),
+            ("forge:ingots".to_string(), "thermal:tin_ingot".to_string()),
+        ];
+        let groups = duplicate_groups(&outputs, &tags);
+        let keys: Vec<(DuplicateReason, &str)> = groups.iter().map(|g| (g.reason, g.key.as_str())).collect();
+        assert_eq!(keys, vec![(DuplicateReason::Tag, "#forge:ingots/tin"), (DuplicateReason::Name, "bronze ingot")]);
+    }
+}
@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Settings key holding the user's seed values as a JSON object.
+pub const SEEDS_SETTING: &str = "value_seeds";
+
+/// Ratio of output to input value below which a recipe is reported.
+pub const DEFAULT_OUTLIER_RATIO: f64 = 0.5;
+
+/// Passes over the recipes before giving up on values still settling,
+/// which only happens around loops that make items from nothing.
+const MAX_PASSES: usize = 100;
+
+/// Base values for vanilla materials, on the familiar EMC scale.
+const VANILLA_PRESET: &[(&str, f64)] = &[
+    ("minecraft:cobblestone", 1.0),
+    ("minecraft:dirt", 1.0),
+    ("minecraft:sand", 1.0),
+    ("minecraft:gravel", 4.0),
+    ("minecraft:stick", 4.0),
+    ("#minecraft:logs", 32.0),
+    ("#minecraft:planks", 8.0),
+    ("minecraft:string", 12.0),
+    ("minecraft:feather", 48.0),
+    ("minecraft:leather", 64.0),
+    ("minecraft:flint", 4.0),
+    ("minecraft:coal", 128.0),
+    ("minecraft:redstone", 64.0),
+    ("minecraft:lapis_lazuli", 864.0),
+    ("minecraft:quartz", 256.0),
+    ("minecraft:copper_ingot", 128.0),
+    ("minecraft:iron_ingot", 256.0),
+    ("minecraft:gold_ingot", 2048.0),
+    ("minecraft:diamond", 8192.0),
+    ("minecraft:emerald", 16384.0),
+    ("minecraft:netherite_scrap", 12288.0),
+    ("minecraft:glowstone_dust", 384.0),
+    ("minecraft:ender_pearl", 1024.0),
+    ("minecraft:blaze_rod", 1536.0),
+    ("minecraft:slime_ball", 32.0),
+    ("minecraft:nether_star", 139264.0),
+];
+
+/// Seed values of a built-in preset.
+pub fn preset(name: &str) -> Option<HashMap<String, f64>> {
+    let values = match name {
+        "vanilla" => VANILLA_PRESET,
+        _ => return None,
+    };
+    Some(values.iter().map(|(item, value)| (item.to_string(), *value)).collect())
+}
+
+/// A recipe with the amounts value propagation needs.
+pub struct ValueRecipe {
+    pub id: i64,
+    pub recipe_id: String,
+    pub output: String,
+    pub output_count: i64,
+    /// Item or tag ids with counts
+    pub ingredients: Vec<(String, i64)>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct ValueOptions {
+    /// Preset whose seeds apply under the user's own
+    pub preset: Option<String>,
+    /// Report recipes whose output is worth less than this share of their
+    /// inputs; defaults to `DEFAULT_OUTLIER_RATIO`
+    pub outlier_ratio: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct ItemValue {
+    /// Item id, or tag id starting with `#`
+    pub item: String,
+    pub value: f64,
+    pub seeded: bool,
+    /// Row id of the cheapest recipe, which set the value
+    pub recipe: Option<i64>,
+}
+
+/// A recipe costing far more than its output is worth, usually because
+/// another recipe makes the same item much more cheaply.
+#[derive(Serialize)]
+pub struct ValueOutlier {
+    pub recipe: i64,
+    pub recipe_id: String,
+    pub output: String,
+    pub input_value: f64,
+    pub output_value: f64,
+    pub ratio: f64,
+}
+
+#[derive(Serialize)]
+pub struct ValueReport {
+    pub values: Vec<ItemValue>,
+    /// Lowest ratio first
+    pub outliers: Vec<ValueOutlier>,
+}
+
+/// Spreads seed values through the recipes. An item is worth its cheapest
+/// recipe's inputs divided by the recipe's output count, and a tag its
+/// cheapest item. Seeded values are never replaced.
+pub fn propagate(recipes: &[ValueRecipe], tag_entries: &[(String, String)], seeds: &HashMap<String, f64>, outlier_ratio: f64) -> ValueReport {
+    let mut values: HashMap<String, (f64, Option<i64>)> =
+        seeds.iter().map(|(item, value)| (item.clone(), (*value, None))).collect();
+    let mut members: HashMap<String, Vec<&str>> = HashMap::new();
+    for (tag, entry) in tag_entries {
+        members.entry(format!("#{}", tag)).or_default().push(entry);
+    }
+
+    for _ in 0..MAX_PASSES {
+        let mut changed = false;
+        for (tag, entries) in &members {
+            if seeds.contains_key(tag) {
+                continue;
+            }
+            let cheapest = entries.iter().filter_map(|e| values.get(*e).map(|v| v.0)).min_by(f64::total_cmp);
+            if let Some(value) = cheapest {
+                if values.get(tag).is_none_or(|v| value < v.0) {
+                    values.insert(tag.clone(), (value, None));
+                    changed = true;
+                }
+            }
+        }
+        for recipe in recipes {
+            if seeds.contains_key(&recipe.output) {
+                continue;
+            }
+            let Some(cost) = input_value(recipe, &values) else {
+                continue;
+            };
+            let value = cost / recipe.output_count.max(1) as f64;
+            if values.get(&recipe.output).is_none_or(|v| value < v.0 - 1e-9) {
+                values.insert(recipe.output.clone(), (value, Some(recipe.id)));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut outliers: Vec<ValueOutlier> = recipes
+        .iter()
+        .filter_map(|recipe| {
+            let input_value = input_value(recipe, &values).filter(|v| *v > 0.0)?;
+            let output_value = values.get(&recipe.output)?.0 * recipe.output_count.max(1) as f64;
+            let ratio = output_value / input_value;
+            (ratio < outlier_ratio).then(|| ValueOutlier {
+                recipe: recipe.id,
+                recipe_id: recipe.recipe_id.clone(),
+                output: recipe.output.clone(),
+                input_value,
+                output_value,
+                ratio,
+            })
+        })
+        .collect();
+    outliers.sort_by(|a, b| a.ratio.total_cmp(&b.ratio).then_with(|| a.recipe_id.cmp(&b.recipe_id)));
+
+    let mut values: Vec<ItemValue> = values
+        .into_iter()
+        .map(|(item, (value, recipe))| ItemValue { seeded: seeds.contains_key(&item), item, value, recipe })
+        .collect();
+    values.sort_by(|a, b| a.item.cmp(&b.item));
+    ValueReport { values, outliers }
+}
+
+/// What a recipe's inputs are worth, once all of them have values. None
+/// for recipes without item ingredients, e.g. fluid-only ones, which would
+/// otherwise make their output free.
+fn input_value(recipe: &ValueRecipe, values: &HashMap<String, (f64, Option<i64>)>) -> Option<f64> {
+    if recipe.ingredients.is_empty() {
+        return None;
+    }
+    recipe
+        .ingredients
+        .iter()
+        .map(|(item, count)| values.get(item).map(|v| v.0 * (*count).max(1) as f64))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(id: i64, output: &str, output_count: i64, ingredients: &[(&str, i64)]) -> ValueRecipe {
+        ValueRecipe {
+            id,
+            recipe_id: format!("a:r{}", id),
+            output: output.to_string(),
+            output_count,
+            ingredients: ingredients.iter().map(|(i, c)| (i.to_string(), *c)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_propagate() {
+        let recipes = vec![
+            recipe(1, "a:block", 1, &[("minecraft:iron_ingot", 9)]),
+            recipe(2, "a:nugget", 9, &[("minecraft:iron_ingot", 1)]),
+            recipe(3, "a:gear", 1, &[("#c:nuggets", 4)]),
+            recipe(4, "a:gear", 1, &[("a:block", 1)]),
+        ];
+        let tags = vec![("c:nuggets".to_string(), "a:nugget".to_string())];
+        let report = propagate(&recipes, &tags, &preset("vanilla").unwrap(), DEFAULT_OUTLIER_RATIO);
+        let value = |item: &str| report.values.iter().find(|v| v.item == item).unwrap();
+
+        assert_eq!(value("a:block").value, 2304.0);
+        assert!((value("a:gear").value - 4.0 * 256.0 / 9.0).abs() < 1e-6);
+        assert_eq!(value("a:gear").recipe, Some(3));
+        assert!(value("minecraft:iron_ingot").seeded);
+        assert_eq!(report.outliers.len(), 1);
+        assert_eq!(report.outliers[0].recipe, 4);
+    }
+
+    #[test]
+    fn test_recipes_without_ingredients_are_skipped() {
+        let recipes = vec![recipe(1, "a:ice", 1, &[]), recipe(2, "a:ice", 1, &[("minecraft:cobblestone", 2)])];
+        let report = propagate(&recipes, &[], &preset("vanilla").unwrap(), DEFAULT_OUTLIER_RATIO);
+        let ice = report.values.iter().find(|v| v.item == "a:ice").unwrap();
+        assert_eq!(ice.value, 2.0);
+        assert_eq!(ice.recipe, Some(2));
+    }
+}
@@ -0,0 +1,207 @@
+use crate::database::Recipe;
+use crate::reports;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 960px; margin: 0 auto; padding: 1em; }
+.recipe { border: 1px solid #ccc; border-radius: 4px; padding: 0.5em 1em; margin: 1em 0; }
+.recipe h3 { margin: 0.2em 0; }
+.meta { color: #666; font-size: 0.9em; }
+table.grid td { border: 1px solid #ccc; width: 6em; height: 2em; text-align: center; font-size: 0.8em; }
+ul.columns { columns: 3; }
+#search { width: 100%; padding: 0.4em; font-size: 1em; }
+";
+
+const SEARCH_SCRIPT: &str = "<script>
+document.getElementById('search').addEventListener('input', e => {
+  const query = e.target.value.toLowerCase();
+  for (const li of document.querySelectorAll('#items li')) {
+    li.hidden = !li.textContent.toLowerCase().includes(query);
+  }
+});
+</script>";
+
+/// Pages of a static recipe reference: an index of mods, recipe types and
+/// items with a search box, and a page for each. Item pages show recipes
+/// making the item and those using it. Returns paths inside the site and
+/// their contents.
+pub fn build_site(title: &str, recipes: &[Recipe], names: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut by_mod: BTreeMap<&str, Vec<&Recipe>> = BTreeMap::new();
+    let mut by_type: BTreeMap<&str, Vec<&Recipe>> = BTreeMap::new();
+    let mut making: BTreeMap<&str, Vec<&Recipe>> = BTreeMap::new();
+    let mut using: BTreeMap<&str, Vec<&Recipe>> = BTreeMap::new();
+    for recipe in recipes {
+        by_mod.entry(&recipe.mod_name).or_default().push(recipe);
+        by_type.entry(&recipe.canonical_type).or_default().push(recipe);
+        if let Some(item) = &recipe.result_item {
+            making.entry(item).or_default().push(recipe);
+        }
+        let ingredients: BTreeSet<&str> = recipe.ingredients.iter().map(|i| i.item.as_str()).collect();
+        for item in ingredients.into_iter().filter(|i| !i.starts_with('#')) {
+            using.entry(item).or_default().push(recipe);
+        }
+    }
+    let items: BTreeSet<&str> = making.keys().chain(using.keys()).copied().collect();
+    let name = |id: &str| names.get(id).cloned().unwrap_or_else(|| id.to_string());
+
+    let mut files = vec![("style.css".to_string(), STYLE.to_string())];
+    let mut index = format!("<h1>{}</h1>\n<h2>Mods</h2>\n<ul class=\"columns\">\n", esc(title));
+    for (mod_name, list) in &by_mod {
+        index.push_str(&format!("<li><a href=\"mods/{}.html\">{}</a> ({})</li>\n", slug(mod_name), esc(mod_name), list.len()));
+    }
+    index.push_str("</ul>\n<h2>Recipe types</h2>\n<ul class=\"columns\">\n");
+    for (recipe_type, list) in &by_type {
+        index.push_str(&format!("<li><a href=\"types/{}.html\">{}</a> ({})</li>\n", slug(recipe_type), esc(recipe_type), list.len()));
+    }
+    index.push_str("</ul>\n<h2>Items</h2>\n<input id=\"search\" placeholder=\"Search items\">\n<ul id=\"items\" class=\"columns\">\n");
+    for item in &items {
+        index.push_str(&format!(
+            "<li><a href=\"items/{}.html\">{}</a> <span class=\"meta\">{}</span></li>\n",
+            slug(item),
+            esc(&name(item)),
+            esc(item)
+        ));
+    }
+    index.push_str("</ul>\n");
+    index.push_str(SEARCH_SCRIPT);
+    files.push(("index.html".to_string(), page(title, "", &index)));
+
+    let list_page = |heading: &str, list: &[&Recipe]| {
+        let body: String = list.iter().map(|r| recipe_card(r, &name)).collect();
+        page(heading, "../", &format!("<p><a href=\"../index.html\">Index</a></p>\n<h1>{}</h1>\n{}", esc(heading), body))
+    };
+    for (mod_name, list) in &by_mod {
+        files.push((format!("mods/{}.html", slug(mod_name)), list_page(mod_name, list)));
+    }
+    for (recipe_type, list) in &by_type {
+        files.push((format!("types/{}.html", slug(recipe_type)), list_page(recipe_type, list)));
+    }
+    for item in &items {
+        let heading = name(item);
+        let mut body = format!(
+            "<p><a href=\"../index.html\">Index</a></p>\n<h1>{}</h1>\n<p class=\"meta\">{}</p>\n",
+            esc(&heading),
+            esc(item)
+        );
+        for (label, list) in [("Recipes", making.get(item)), ("Used in", using.get(item))] {
+            if let Some(list) = list {
+                body.push_str(&format!("<h2>{}</h2>\n", label));
+                body.extend(list.iter().map(|r| recipe_card(r, &name)));
+            }
+        }
+        files.push((format!("items/{}.html", slug(item)), page(&heading, "../", &body)));
+    }
+    files
+}
+
+/// Writes the site's files under `dir`, creating folders as needed.
+pub fn write_site(files: &[(String, String)], dir: &Path) -> Result<(), String> {
+    for (path, contents) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"{}style.css\">\n</head>\n<body>\n{}</body>\n</html>\n",
+        esc(title),
+        root,
+        body
+    )
+}
+
+/// A recipe with links to the item pages of everything in it. Pages sit
+/// one folder deep, so item links go through `../items/`.
+fn recipe_card(recipe: &Recipe, name: &dyn Fn(&str) -> String) -> String {
+    let link = |id: &str| {
+        if id.starts_with('#') {
+            format!("<code>{}</code>", esc(id))
+        } else {
+            format!("<a href=\"../items/{}.html\">{}</a>", slug(id), esc(&name(id)))
+        }
+    };
+    let mut out = format!(
+        "<div class=\"recipe\">\n<h3>{}</h3>\n<p class=\"meta\">{} from {}</p>\n",
+        esc(&recipe.recipe_id),
+        esc(&recipe.canonical_type),
+        esc(&recipe.mod_name)
+    );
+    if let Some(grid) = reports::shaped_grid(&recipe.raw_json) {
+        out.push_str("<table class=\"grid\">\n");
+        for row in grid {
+            let cells: String = row.iter().map(|slot| format!("<td>{}</td>", slot.as_deref().map(link).unwrap_or_default())).collect();
+            out.push_str(&format!("<tr>{}</tr>\n", cells));
+        }
+        out.push_str("</table>\n");
+    }
+    if !recipe.ingredients.is_empty() {
+        out.push_str("<ul>\n");
+        for ingredient in &recipe.ingredients {
+            out.push_str(&format!("<li>{}x {}</li>\n", ingredient.count, link(&ingredient.item)));
+        }
+        out.push_str("</ul>\n");
+    }
+    if let Some(item) = &recipe.result_item {
+        out.push_str(&format!("<p>Makes {}x {}</p>\n", recipe.result_count.unwrap_or(1), link(item)));
+    }
+    out.push_str("</div>\n");
+    out
+}
+
+/// File name for an id, e.g. `create-crushing_wheel` for `create:crushing_wheel`.
+/// Other characters, capitals included, become their hex code between `+`s,
+/// so distinct ids never share a file even on case-insensitive disks.
+fn slug(id: &str) -> String {
+    id.chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '_' | '.' => c.to_string(),
+            ':' => "-".to_string(),
+            _ => format!("+{:x}+", c as u32),
+        })
+        .collect()
+}
+
+fn esc(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe_parser::IngredientCount;
+    use crate::test_support::recipe;
+
+    #[test]
+    fn test_slug_and_esc() {
+        assert_eq!(slug("create:crushing_wheel"), "create-crushing_wheel");
+        assert_eq!(slug("create:crushing/ore"), "create-crushing+2f+ore");
+        assert_eq!(slug("Create 1.0.jar"), "+43+reate+20+1.0.jar");
+        assert_ne!(slug("a:b/c"), slug("a:b-c"));
+        assert_eq!(esc("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn test_build_site() {
+        let gear = Recipe {
+            mod_name: "Create".to_string(),
+            ingredients: vec![IngredientCount { item: "a:plate/iron".to_string(), count: 2 }],
+            ..recipe("a:gear", Some("a:Gear"))
+        };
+        let plate = Recipe { mod_name: "create".to_string(), ..recipe("a:plate", Some("a:plate-iron")) };
+        let names = HashMap::from([("a:Gear".to_string(), "Gear".to_string())]);
+        let files = build_site("Pack", &[gear, plate], &names);
+        let paths: BTreeSet<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths.len(), files.len());
+        for path in ["mods/+43+reate.html", "mods/create.html", "items/a-+47+ear.html", "items/a-plate+2f+iron.html", "items/a-plate+2d+iron.html"] {
+            assert!(paths.contains(path), "{}", path);
+        }
+        let index = &files.iter().find(|(path, _)| path == "index.html").unwrap().1;
+        assert!(index.contains("<a href=\"items/a-+47+ear.html\">Gear</a>"));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::values::ValueRecipe;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What one workspace's recipes and items look like next to another's.
+#[derive(Serialize)]
+pub struct WorkspaceComparison {
+    pub shared_recipes: Vec<SharedRecipe>,
+    /// Items only the open workspace knows
+    pub only_left: Vec<String>,
+    /// Items only the comparison workspace knows
+    pub only_right: Vec<String>,
+    pub cost_differences: Vec<CostDifference>,
+}
+
+/// A recipe id both workspaces ship.
+#[derive(Serialize)]
+pub struct SharedRecipe {
+    pub recipe_id: String,
+    /// Same output, count and ingredients on both sides
+    pub same_cost: bool,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RecipeCost {
+    pub recipe_id: String,
+    pub output_count: i64,
+    /// Item or tag ids with counts, sorted
+    pub ingredients: Vec<(String, i64)>,
+}
+
+/// An output both workspaces make, but from different inputs.
+#[derive(Serialize)]
+pub struct CostDifference {
+    pub output: String,
+    pub left: Vec<RecipeCost>,
+    pub right: Vec<RecipeCost>,
+}
+
+/// Compares the recipes and item registries of two workspaces. An output
+/// differs in cost when the inputs and counts of its recipes differ,
+/// whatever the recipes are called.
+pub fn compare(
+    left_recipes: &[ValueRecipe],
+    left_items: &[String],
+    right_recipes: &[ValueRecipe],
+    right_items: &[String],
+) -> WorkspaceComparison {
+    let (left, right) = (by_output(left_recipes), by_output(right_recipes));
+    let mut cost_differences = Vec::new();
+    for (output, left_costs) in &left {
+        let Some(right_costs) = right.get(output) else {
+            continue;
+        };
+        let shape = |costs: &[RecipeCost]| -> BTreeSet<(i64, Vec<(String, i64)>)> {
+            costs.iter().map(|c| (c.output_count, c.ingredients.clone())).collect()
+        };
+        if shape(left_costs) != shape(right_costs) {
+            cost_differences.push(CostDifference {
+                output: output.to_string(),
+                left: left_costs.clone(),
+                right: right_costs.clone(),
+            });
+        }
+    }
+
+    let costs = |recipes: &[ValueRecipe]| -> BTreeMap<String, (String, RecipeCost)> {
+        recipes.iter().map(|r| (r.recipe_id.clone(), (r.output.clone(), cost(r)))).collect()
+    };
+    let (left_ids, right_ids) = (costs(left_recipes), costs(right_recipes));
+    let shared_recipes = left_ids
+        .iter()
+        .filter_map(|(id, a)| right_ids.get(id).map(|b| SharedRecipe { recipe_id: id.clone(), same_cost: a == b }))
+        .collect();
+
+    let left_items: BTreeSet<&String> = left_items.iter().collect();
+    let right_items: BTreeSet<&String> = right_items.iter().collect();
+    WorkspaceComparison {
+        shared_recipes,
+        only_left: left_items.difference(&right_items).map(|i| i.to_string()).collect(),
+        only_right: right_items.difference(&left_items).map(|i| i.to_string()).collect(),
+        cost_differences,
+    }
+}
+
+fn cost(recipe: &ValueRecipe) -> RecipeCost {
+    let mut ingredients = recipe.ingredients.clone();
+    ingredients.sort();
+    RecipeCost { recipe_id: recipe.recipe_id.clone(), output_count: recipe.output_count, ingredients }
+}
+
+fn by_output(recipes: &[ValueRecipe]) -> BTreeMap<&str, Vec<RecipeCost>> {
+    let mut outputs: BTreeMap<&str, Vec<RecipeCost>> = BTreeMap::new();
+    for recipe in recipes {
+        outputs.entry(&recipe.output).or_default().push(cost(recipe));
+    }
+    for costs in outputs.values_mut() {
+        costs.sort();
+    }
+    outputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(recipe_id: &str, output: &str, ingredients: &[(&str, i64)]) -> ValueRecipe {
+        ValueRecipe {
+            id: 0,
+            recipe_id: recipe_id.to_string(),
+            output: output.to_string(),
+            output_count: 1,
+            ingredients: ingredients.iter().map(|(i, c)| (i.to_string(), *c)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let left = vec![
+            recipe("a:gear", "a:gear", &[("a:ingot", 4)]),
+            recipe("a:plate", "a:plate", &[("a:ingot", 1)]),
+        ];
+        let right = vec![
+            recipe("a:gear", "a:gear", &[("a:ingot", 2)]),
+            recipe("b:plate", "a:plate", &[("a:ingot", 1)]),
+        ];
+        let items = |ids: &[&str]| ids.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        let result = compare(&left, &items(&["a:gear", "a:old"]), &right, &items(&["a:gear", "a:new"]));
+
+        assert_eq!(result.shared_recipes.len(), 1);
+        assert!(!result.shared_recipes[0].same_cost);
+        assert_eq!(result.only_left, vec!["a:old"]);
+        assert_eq!(result.only_right, vec!["a:new"]);
+        assert_eq!(result.cost_differences.len(), 1);
+        assert_eq!(result.cost_differences[0].output, "a:gear");
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Workspace created on first start; it keeps the database file used before
+/// workspaces existed so earlier extractions are not lost.
+pub const DEFAULT_WORKSPACE: &str = "Default";
+const DEFAULT_FILE: &str = "recipes.db";
+const REGISTRY_FILE: &str = "workspaces.json";
+
+/// A named workspace with its own database, e.g. one per modpack.
+#[derive(Serialize, Deserialize, Clone)]
+struct Workspace {
+    name: String,
+    /// Database file, relative to the app data directory unless it was
+    /// moved elsewhere
+    file: String,
+}
+
+#[derive(Serialize)]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub active: bool,
+    /// Where the workspace's database lives
+    pub path: String,
+}
+
+/// The known workspaces and which one is open, saved as `workspaces.json`
+/// in the app data directory.
+#[derive(Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    active: String,
+    workspaces: Vec<Workspace>,
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+impl WorkspaceRegistry {
+    /// Loads the registry from `dir`, starting with just the default
+    /// workspace when none has been saved yet.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let registry_path = dir.join(REGISTRY_FILE);
+        let mut registry = if registry_path.exists() {
+            let contents = fs::read_to_string(&registry_path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&contents).map_err(|e| format!("Invalid {}: {}", REGISTRY_FILE, e))?
+        } else {
+            WorkspaceRegistry {
+                active: DEFAULT_WORKSPACE.to_string(),
+                workspaces: vec![Workspace {
+                    name: DEFAULT_WORKSPACE.to_string(),
+                    file: DEFAULT_FILE.to_string(),
+                }],
+                dir: PathBuf::new(),
+            }
+        };
+        registry.dir = dir.to_path_buf();
+        Ok(registry)
+    }
+
+    pub fn list(&self) -> Vec<WorkspaceInfo> {
+        self.workspaces
+            .iter()
+            .map(|w| WorkspaceInfo {
+                name: w.name.clone(),
+                active: w.name == self.active,
+                path: self.dir.join(&w.file).to_string_lossy().to_string(),
+            })
+            .collect()
+    }
+
+    pub fn active_name(&self) -> &str {
+        &self.active
+    }
+
+    /// `name`, or `name (2)`, `name (3)`, ... if it is taken.
+    pub fn unique_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.workspaces.iter().any(|w| w.name.eq_ignore_ascii_case(candidate));
+        (1..)
+            .map(|n| match n {
+                1 => name.trim().to_string(),
+                _ => format!("{} ({})", name.trim(), n),
+            })
+            .find(|candidate| !taken(candidate))
+            .unwrap()
+    }
+
+    pub fn active_path(&self) -> PathBuf {
+        self.path_of(&self.active).unwrap_or_else(|_| self.dir.join(DEFAULT_FILE))
+    }
+
+    /// Database file of the named workspace.
+    pub fn path_of(&self, name: &str) -> Result<PathBuf, String> {
+        Ok(self.dir.join(&self.find(name)?.file))
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_new_name(name)?;
+        let slug: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        // Names can differ only in punctuation, so number clashing files
+        let file = (1..)
+            .map(|n| match n {
+                1 => format!("workspaces/{}.db", slug),
+                _ => format!("workspaces/{}-{}.db", slug, n),
+            })
+            .find(|file| !self.workspaces.iter().any(|w| &w.file == file) && !self.dir.join(file).exists())
+            .unwrap();
+        self.workspaces.push(Workspace { name, file });
+        self.save()
+    }
+
+    /// Records that the workspace's database now lives at `path`.
+    pub fn set_location(&mut self, name: &str, path: &Path) -> Result<(), String> {
+        self.find(name)?;
+        let workspace = self.workspaces.iter_mut().find(|w| w.name == name).unwrap();
+        workspace.file = path.to_string_lossy().to_string();
+        self.save()
+    }
+
+    pub fn set_active(&mut self, name: &str) -> Result<(), String> {
+        self.active = self.find(name)?.name.clone();
+        self.save()
+    }
+
+    /// Renames a workspace; its database file keeps its name.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        self.find(name)?;
+        let new_name = self.check_new_name(new_name)?;
+        if self.active == name {
+            self.active = new_name.clone();
+        }
+        let workspace = self.workspaces.iter_mut().find(|w| w.name == name).unwrap();
+        workspace.name = new_name;
+        self.save()
+    }
+
+    /// Removes a workspace and its database files. The open workspace can't
+    /// be deleted; switch to another one first.
+    pub fn delete(&mut self, name: &str) -> Result<(), String> {
+        if self.active == name {
+            return Err("Cannot delete the open workspace".to_string());
+        }
+        remove_database_files(&self.path_of(name)?)?;
+        self.workspaces.retain(|w| w.name != name);
+        self.save()
+    }
+
+    fn find(&self, name: &str) -> Result<&Workspace, String> {
+        self.workspaces
+            .iter()
+            .find(|w| w.name == name)
+            .ok_or_else(|| format!("No workspace named {}", name))
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Workspace name is empty".to_string());
+        }
+        if self.workspaces.iter().any(|w| w.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A workspace named {} already exists", name));
+        }
+        Ok(name.to_string())
+    }
+
+    fn save(&self) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let contents = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(self.dir.join(REGISTRY_FILE), contents).map_err(|e| e.to_string())
+    }
+}
+
+/// A new, empty folder in the temp directory for scratch files, unique to
+/// this call so concurrent tasks never share it. The caller removes it.
+pub fn scratch_dir(label: &str) -> Result<PathBuf, String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    loop {
+        let dir = std::env::temp_dir().join(format!(
+            "modpack-workbench-{}-{}-{}",
+            label,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            // Left behind by an earlier run with the same process id
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create {}: {}", dir.display(), e)),
+        }
+    }
+}
+
+/// Deletes a database file along with its write-ahead log and shared memory files.
+pub fn remove_database_files(path: &Path) -> Result<(), String> {
+    for suffix in ["", "-wal", "-shm"] {
+        let file = PathBuf::from(format!("{}{}", path.display(), suffix));
+        if file.exists() {
+            fs::remove_file(&file).map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_lifecycle() {
+        let dir = std::env::temp_dir().join(format!("workspaces-test-{}", std::process::id()));
+        let mut registry = WorkspaceRegistry::load(&dir).unwrap();
+        assert_eq!(registry.active_path(), dir.join(DEFAULT_FILE));
+
+        registry.create("ATM 10").unwrap();
+        assert!(registry.create("atm 10").is_err());
+        registry.set_active("ATM 10").unwrap();
+        assert_eq!(registry.active_path(), dir.join("workspaces/atm-10.db"));
+
+        assert_eq!(registry.unique_name("atm 10"), "atm 10 (2)");
+        registry.rename("ATM 10", "All the Mods").unwrap();
+        let reloaded = WorkspaceRegistry::load(&dir).unwrap();
+        let names: Vec<_> = reloaded.list().into_iter().filter(|w| w.active).map(|w| w.name).collect();
+        assert_eq!(names, vec!["All the Mods"]);
+        assert!(registry.delete("All the Mods").is_err());
+
+        let moved = dir.join("elsewhere/atm.db");
+        registry.set_location("All the Mods", &moved).unwrap();
+        assert_eq!(registry.active_path(), moved);
+
+        registry.set_active(DEFAULT_WORKSPACE).unwrap();
+        registry.delete("All the Mods").unwrap();
+        assert_eq!(registry.list().len(), 1);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,93 @@
+use serde_json::Value;
+
+/// Returns (kind, id) for worldgen files under `data/<namespace>/worldgen/<kind>/`
+/// and for biome modifiers under `data/<namespace>/forge/biome_modifier/` or
+/// `data/<namespace>/neoforge/biome_modifier/`.
+pub fn worldgen_entry(path: &str) -> Option<(String, String)> {
+    let rest = path.strip_prefix("data/")?.strip_suffix(".json")?;
+    let (namespace, rest) = rest.split_once('/')?;
+
+    let (kind, name) = if let Some(rest) = rest.strip_prefix("worldgen/") {
+        rest.split_once('/')?
+    } else {
+        let name = rest.strip_prefix("forge/biome_modifier/")
+            .or(rest.strip_prefix("neoforge/biome_modifier/"))?;
+        ("biome_modifier", name)
+    };
+
+    Some((kind.to_string(), format!("{}:{}", namespace, name)))
+}
+
+/// Collects every namespaced id (blocks, items, features, biome tags) the
+/// file mentions, so entries can be found by what they reference.
+pub fn collect_references(json_str: &str) -> Result<Vec<String>, String> {
+    let value: Value = serde_json::from_str(json_str)
+        .map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    let mut references = Vec::new();
+    collect_ids(&value, &mut references);
+    references.sort();
+    references.dedup();
+    Ok(references)
+}
+
+fn collect_ids(value: &Value, ids: &mut Vec<String>) {
+    match value {
+        Value::String(s) if is_namespaced_id(s) => ids.push(s.clone()),
+        Value::Array(arr) => arr.iter().for_each(|v| collect_ids(v, ids)),
+        Value::Object(obj) => obj.values().for_each(|v| collect_ids(v, ids)),
+        _ => {}
+    }
+}
+
+fn is_namespaced_id(s: &str) -> bool {
+    let s = s.strip_prefix('#').unwrap_or(s);
+    match s.split_once(':') {
+        Some((namespace, path)) => {
+            !namespace.is_empty()
+                && !path.is_empty()
+                && s.chars().filter(|&c| c == ':').count() == 1
+                && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-./:".contains(c))
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worldgen_entry() {
+        assert_eq!(
+            worldgen_entry("data/create/worldgen/configured_feature/zinc_ore.json"),
+            Some(("configured_feature".to_string(), "create:zinc_ore".to_string()))
+        );
+        assert_eq!(
+            worldgen_entry("data/create/neoforge/biome_modifier/zinc_ore.json"),
+            Some(("biome_modifier".to_string(), "create:zinc_ore".to_string()))
+        );
+        assert_eq!(worldgen_entry("data/create/recipes/zinc_ore.json"), None);
+    }
+
+    #[test]
+    fn test_collect_references() {
+        let json = r##"{
+            "type": "minecraft:ore",
+            "config": {
+                "size": 9,
+                "targets": [{
+                    "state": {"Name": "create:zinc_ore"},
+                    "target": {"predicate_type": "minecraft:tag_match", "tag": "minecraft:stone_ore_replaceables"}
+                }]
+            },
+            "biomes": "#minecraft:is_overworld",
+            "step": "underground_ores"
+        }"##;
+
+        let refs = collect_references(json).unwrap();
+        assert!(refs.contains(&"create:zinc_ore".to_string()));
+        assert!(refs.contains(&"#minecraft:is_overworld".to_string()));
+        assert!(!refs.contains(&"underground_ores".to_string()));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::conflicts::RecipeConflict;
+use crate::database::Recipe;
+use crate::recipe_export;
+use crate::stats::PackStats;
+use crate::unification::{DuplicateGroup, DuplicateReason};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+#[derive(Debug, PartialEq)]
+enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<Option<&str>> for Cell {
+    fn from(text: Option<&str>) -> Self {
+        Cell::Text(text.unwrap_or_default().to_string())
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(number: i64) -> Self {
+        Cell::Number(number as f64)
+    }
+}
+
+/// A workbook with a sheet each for recipes, conflicts, unification
+/// candidates and pack stats, as the bytes of an .xlsx file.
+pub fn workbook(
+    recipes: &[Recipe],
+    conflicts: &[RecipeConflict],
+    duplicates: &[DuplicateGroup],
+    stats: &PackStats,
+) -> Result<Vec<u8>, String> {
+    let mut workbook = Workbook::new();
+
+    let rows = recipes.iter().map(recipe_row);
+    let headers = ["Recipe", "Mod", "Type", "Result", "Count", "Ingredients", "Path"];
+    add_sheet(&mut workbook, "Recipes", &headers, rows).map_err(|e| e.to_string())?;
+
+    let rows = conflicts.iter().map(|c| {
+        vec![
+            c.first.recipe_id.as_str().into(),
+            c.first.mod_name.as_str().into(),
+            c.first.result_item.as_deref().into(),
+            c.second.recipe_id.as_str().into(),
+            c.second.mod_name.as_str().into(),
+            c.second.result_item.as_deref().into(),
+        ]
+    });
+    let headers = ["Recipe", "Mod", "Makes", "Conflicts with", "Mod", "Makes"];
+    add_sheet(&mut workbook, "Conflicts", &headers, rows).map_err(|e| e.to_string())?;
+
+    let rows = duplicates.iter().flat_map(|group| {
+        let reason = match group.reason {
+            DuplicateReason::Tag => "tag",
+            DuplicateReason::Name => "name",
+        };
+        group.items.iter().map(move |item| {
+            vec![
+                reason.into(),
+                group.key.as_str().into(),
+                item.item.as_str().into(),
+                item.display_name.as_deref().into(),
+                item.mod_name.as_deref().into(),
+                item.recipe_count.into(),
+            ]
+        })
+    });
+    let headers = ["Shared by", "Tag or name", "Item", "Name", "Mod", "Recipes"];
+    add_sheet(&mut workbook, "Unification", &headers, rows).map_err(|e| e.to_string())?;
+
+    let mut rows: Vec<Vec<Cell>> = vec![vec!["Mods".into(), stats.mod_count.into()]];
+    if let Some(coverage) = &stats.parser_coverage {
+        rows.push(vec!["Recipes".into(), coverage.recipes.into()]);
+        rows.push(vec!["With result".into(), coverage.with_result.into()]);
+        rows.push(vec!["With ingredients".into(), coverage.with_ingredients.into()]);
+        rows.push(vec!["With fluids".into(), coverage.with_fluids.into()]);
+    }
+    for (label, counts) in [("Type", &stats.recipe_types), ("Error", &stats.error_categories)] {
+        for count in counts.iter().flatten() {
+            rows.push(vec![format!("{}: {}", label, count.name).as_str().into(), count.count.into()]);
+        }
+    }
+    add_sheet(&mut workbook, "Stats", &["Metric", "Value"], rows).map_err(|e| e.to_string())?;
+
+    workbook.save_to_buffer().map_err(|e| e.to_string())
+}
+
+/// A recipe's cells, counted as in the CSV export.
+fn recipe_row(r: &Recipe) -> Vec<Cell> {
+    vec![
+        r.recipe_id.as_str().into(),
+        r.mod_name.as_str().into(),
+        r.canonical_type.as_str().into(),
+        r.result_item.as_deref().into(),
+        i64::from(recipe_export::result_count(r)).into(),
+        recipe_export::ingredient_summary(r).as_str().into(),
+        r.path.as_str().into(),
+    ]
+}
+
+/// Adds a sheet with a bold, frozen header row.
+fn add_sheet(
+    workbook: &mut Workbook,
+    name: &str,
+    headers: &[&str],
+    rows: impl IntoIterator<Item = Vec<Cell>>,
+) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(name)?;
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, &bold)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    for (index, row) in rows.into_iter().enumerate() {
+        let row_num = index as u32 + 1;
+        for (col, cell) in row.into_iter().enumerate() {
+            match cell {
+                Cell::Text(text) => sheet.write_string(row_num, col as u16, text)?,
+                Cell::Number(number) => sheet.write_number(row_num, col as u16, number)?,
+            };
+        }
+    }
+    sheet.autofit();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::recipe;
+
+    #[test]
+    fn test_workbook() {
+        let stats = PackStats {
+            app_version: "0.1.0".to_string(),
+            mod_count: 2,
+            recipe_types: Some(vec![crate::stats::CategoryCount { name: "minecraft:crafting_shaped".to_string(), count: 5 }]),
+            parser_coverage: None,
+            error_categories: None,
+        };
+        let bytes = workbook(&[], &[], &[], &stats).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+
+        let recipes = [recipe("a:gear", Some("a:gear")), recipe("a:broken", None)];
+        assert!(workbook(&recipes, &[], &[], &stats).unwrap().starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_recipe_row_counts() {
+        let counted = Recipe { result_count: Some(4), ..recipe("a:gear", Some("a:gear")) };
+        assert_eq!(recipe_row(&counted)[4], Cell::Number(4.0));
+        assert_eq!(recipe_row(&recipe("a:gear", Some("a:gear")))[4], Cell::Number(1.0));
+        assert_eq!(recipe_row(&recipe("a:broken", None))[4], Cell::Number(0.0));
+    }
+}